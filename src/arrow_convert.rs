@@ -0,0 +1,129 @@
+use crate::workbook::CellValue;
+use arrow_array::cast::AsArray;
+use arrow_array::temporal_conversions::{
+    date32_to_datetime, date64_to_datetime, timestamp_ms_to_datetime, timestamp_ns_to_datetime,
+    timestamp_s_to_datetime, timestamp_us_to_datetime,
+};
+use arrow_array::types::*;
+use arrow_array::{Array, ArrowPrimitiveType, RecordBatch};
+use arrow_cast::display::{ArrayFormatter, FormatOptions};
+use arrow_schema::{DataType, TimeUnit};
+use chrono::NaiveDateTime;
+
+/// Converts a record batch into rows of cells
+pub fn batch_to_rows(batch: &RecordBatch) -> Vec<Vec<CellValue>> {
+    let mut rows = vec![Vec::with_capacity(batch.num_columns()); batch.num_rows()];
+    for column in batch.columns() {
+        for (row, cell) in rows.iter_mut().zip(column_cells(column.as_ref())) {
+            row.push(cell);
+        }
+    }
+    rows
+}
+
+/// Converts one column. Nulls become `Empty`, dates and timestamps (UTC for
+/// zoned timestamps) become `DateTime`, decimals become `Float`, and anything
+/// without a natural cell type (lists, structs, times, binary) is shown as text.
+fn column_cells(array: &dyn Array) -> Vec<CellValue> {
+    match array.data_type() {
+        DataType::Boolean => array
+            .as_boolean()
+            .iter()
+            .map(|v| v.map_or(CellValue::Empty, CellValue::Bool))
+            .collect(),
+        DataType::Int8 => primitive_cells::<Int8Type>(array, |v| CellValue::Int(v.into())),
+        DataType::Int16 => primitive_cells::<Int16Type>(array, |v| CellValue::Int(v.into())),
+        DataType::Int32 => primitive_cells::<Int32Type>(array, |v| CellValue::Int(v.into())),
+        DataType::Int64 => primitive_cells::<Int64Type>(array, CellValue::Int),
+        DataType::UInt8 => primitive_cells::<UInt8Type>(array, |v| CellValue::Int(v.into())),
+        DataType::UInt16 => primitive_cells::<UInt16Type>(array, |v| CellValue::Int(v.into())),
+        DataType::UInt32 => primitive_cells::<UInt32Type>(array, |v| CellValue::Int(v.into())),
+        DataType::UInt64 => primitive_cells::<UInt64Type>(array, |v| {
+            i64::try_from(v).map_or(CellValue::Float(v as f64), CellValue::Int)
+        }),
+        DataType::Float16 => {
+            primitive_cells::<Float16Type>(array, |v| CellValue::Float(v.to_f64()))
+        }
+        DataType::Float32 => primitive_cells::<Float32Type>(array, |v| CellValue::Float(v.into())),
+        DataType::Float64 => primitive_cells::<Float64Type>(array, CellValue::Float),
+        DataType::Decimal128(_, scale) => {
+            let divisor = 10f64.powi((*scale).into());
+            primitive_cells::<Decimal128Type>(array, |v| CellValue::Float(v as f64 / divisor))
+        }
+        // No lossless native conversion; go through the decimal text
+        DataType::Decimal256(_, _) => formatted_cells(array)
+            .into_iter()
+            .map(|cell| match cell {
+                CellValue::String(s) => s.parse().map_or(CellValue::String(s), CellValue::Float),
+                other => other,
+            })
+            .collect(),
+        DataType::Utf8 => string_cells(array.as_string::<i32>().iter()),
+        DataType::LargeUtf8 => string_cells(array.as_string::<i64>().iter()),
+        DataType::Utf8View => string_cells(array.as_string_view().iter()),
+        DataType::Date32 => temporal_cells::<Date32Type>(array, date32_to_datetime),
+        DataType::Date64 => temporal_cells::<Date64Type>(array, date64_to_datetime),
+        DataType::Timestamp(TimeUnit::Second, _) => {
+            temporal_cells::<TimestampSecondType>(array, timestamp_s_to_datetime)
+        }
+        DataType::Timestamp(TimeUnit::Millisecond, _) => {
+            temporal_cells::<TimestampMillisecondType>(array, timestamp_ms_to_datetime)
+        }
+        DataType::Timestamp(TimeUnit::Microsecond, _) => {
+            temporal_cells::<TimestampMicrosecondType>(array, timestamp_us_to_datetime)
+        }
+        DataType::Timestamp(TimeUnit::Nanosecond, _) => {
+            temporal_cells::<TimestampNanosecondType>(array, timestamp_ns_to_datetime)
+        }
+        _ => formatted_cells(array),
+    }
+}
+
+fn primitive_cells<T: ArrowPrimitiveType>(
+    array: &dyn Array,
+    to_cell: impl Fn(T::Native) -> CellValue,
+) -> Vec<CellValue> {
+    array
+        .as_primitive::<T>()
+        .iter()
+        .map(|v| v.map_or(CellValue::Empty, &to_cell))
+        .collect()
+}
+
+fn string_cells<'a>(values: impl Iterator<Item = Option<&'a str>>) -> Vec<CellValue> {
+    values
+        .map(|v| v.map_or(CellValue::Empty, |s| CellValue::String(s.to_string())))
+        .collect()
+}
+
+/// `DateTime` cells; values outside the representable range are kept as text
+fn temporal_cells<T: ArrowPrimitiveType>(
+    array: &dyn Array,
+    to_datetime: fn(T::Native) -> Option<NaiveDateTime>,
+) -> Vec<CellValue>
+where
+    T::Native: std::fmt::Display,
+{
+    primitive_cells::<T>(array, |v| match to_datetime(v) {
+        Some(dt) => {
+            CellValue::from_naive_datetime(dt).unwrap_or_else(|| CellValue::String(dt.to_string()))
+        }
+        None => CellValue::String(v.to_string()),
+    })
+}
+
+/// Falls back to Arrow's own display formatting
+fn formatted_cells(array: &dyn Array) -> Vec<CellValue> {
+    let Ok(formatter) = ArrayFormatter::try_new(array, &FormatOptions::default()) else {
+        return vec![CellValue::String(format!("<{}>", array.data_type())); array.len()];
+    };
+    (0..array.len())
+        .map(|row| {
+            if array.is_null(row) {
+                CellValue::Empty
+            } else {
+                CellValue::String(formatter.value(row).to_string())
+            }
+        })
+        .collect()
+}
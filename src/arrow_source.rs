@@ -0,0 +1,344 @@
+use crate::arrow_convert::batch_to_rows;
+use crate::styles::CellFormats;
+use crate::workbook::{CellValue, HEADER_ORIGIN, SheetData};
+use anyhow::{Context, Result, anyhow, bail};
+use arrow_array::RecordBatch;
+use arrow_ipc::reader::{FileReader, StreamReader, read_footer_length};
+use arrow_ipc::{root_as_footer, root_as_message};
+use arrow_schema::SchemaRef;
+use std::fs::File;
+use std::io::{BufReader, Cursor, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+/// Leading bytes of the Arrow IPC file format (Feather v2)
+pub const ARROW_FILE_MAGIC: [u8; 6] = *b"ARROW1";
+/// Continuation marker that starts every message of the Arrow IPC stream format
+pub const ARROW_STREAM_MAGIC: [u8; 4] = [0xFF; 4];
+
+/// Whether files with this extension are opened as Arrow IPC
+pub fn is_arrow_extension(ext: &str) -> bool {
+    matches!(
+        ext.to_ascii_lowercase().as_str(),
+        "arrow" | "arrows" | "feather" | "ipc"
+    )
+}
+
+/// Where record batches are read from
+enum ArrowInput {
+    /// IPC file format on disk; batches are read by index
+    File(PathBuf),
+    /// Streamed (or piped) data; batches are already decoded
+    Batches(Vec<RecordBatch>),
+}
+
+/// An Arrow IPC / Feather file shown as a single sheet. Record batches are
+/// converted to cells only for the rows being displayed.
+pub struct ArrowFile {
+    name: String,
+    input: ArrowInput,
+    /// First row of each record batch, plus the total row count at the end
+    batch_starts: Vec<usize>,
+    pub headers: Vec<String>,
+}
+
+impl ArrowFile {
+    pub fn open(path: &Path, name: &str) -> Result<Self> {
+        let mut file = BufReader::new(File::open(path)?);
+        let mut magic = [0u8; ARROW_FILE_MAGIC.len()];
+        let is_file_format = file.read_exact(&mut magic).is_ok() && magic == ARROW_FILE_MAGIC;
+        file.rewind()?;
+
+        if !is_file_format {
+            let reader = StreamReader::try_new(file, None)
+                .context("Not an Arrow IPC file or stream (Feather v1 is not supported)")?;
+            return Self::from_batches(name, reader.schema(), reader);
+        }
+
+        // Only row counts are kept, taken from the batch metadata; batches are
+        // decoded when their rows are shown
+        let schema = FileReader::try_new(&mut file, None)?.schema();
+        let mut batch_starts = vec![0];
+        let mut total = 0;
+        for rows in batch_lengths(&mut file)? {
+            total += rows;
+            batch_starts.push(total);
+        }
+
+        Ok(Self {
+            name: name.to_string(),
+            input: ArrowInput::File(path.to_path_buf()),
+            batch_starts,
+            headers: headers(&schema),
+        })
+    }
+
+    /// Arrow IPC data already in memory (e.g. read from stdin), in file or stream format
+    pub fn from_bytes(bytes: Vec<u8>, name: &str) -> Result<Self> {
+        if bytes.starts_with(&ARROW_FILE_MAGIC) {
+            let reader = FileReader::try_new(Cursor::new(bytes), None)?;
+            Self::from_batches(name, reader.schema(), reader)
+        } else {
+            let reader = StreamReader::try_new(Cursor::new(bytes), None)?;
+            Self::from_batches(name, reader.schema(), reader)
+        }
+    }
+
+    fn from_batches(
+        name: &str,
+        schema: SchemaRef,
+        batches: impl Iterator<Item = Result<RecordBatch, arrow_schema::ArrowError>>,
+    ) -> Result<Self> {
+        let batches = batches.collect::<Result<Vec<_>, _>>()?;
+        let mut batch_starts = vec![0];
+        let mut total = 0;
+        for batch in &batches {
+            total += batch.num_rows();
+            batch_starts.push(total);
+        }
+        Ok(Self {
+            name: name.to_string(),
+            input: ArrowInput::Batches(batches),
+            batch_starts,
+            headers: headers(&schema),
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn width(&self) -> usize {
+        self.headers.len()
+    }
+
+    pub fn height(&self) -> usize {
+        self.batch_starts.last().copied().unwrap_or(0)
+    }
+
+    /// Zero-indexed rows, converted from the record batches overlapping the range
+    pub fn read_rows(&self, start: usize, count: usize) -> Result<Vec<Vec<CellValue>>> {
+        let end = (start + count).min(self.height());
+        if start >= end {
+            return Ok(Vec::new());
+        }
+        // Index of the batch containing `start`
+        let first = self.batch_starts.partition_point(|&s| s <= start) - 1;
+
+        let mut rows = Vec::with_capacity(end - start);
+        let mut take = |index: usize, batch: &RecordBatch| {
+            let batch_start = self.batch_starts[index];
+            let from = start.max(batch_start) - batch_start;
+            let to = end.min(batch_start + batch.num_rows()) - batch_start;
+            rows.extend(batch_to_rows(&batch.slice(from, to - from)));
+        };
+
+        match &self.input {
+            ArrowInput::File(path) => {
+                let mut reader = FileReader::try_new(BufReader::new(File::open(path)?), None)?;
+                reader.set_index(first)?;
+                for (index, batch) in (first..).zip(reader) {
+                    if self.batch_starts[index] >= end {
+                        break;
+                    }
+                    take(index, &batch?);
+                }
+            }
+            ArrowInput::Batches(batches) => {
+                for (index, batch) in batches.iter().enumerate().skip(first) {
+                    if self.batch_starts[index] >= end {
+                        break;
+                    }
+                    take(index, batch);
+                }
+            }
+        }
+        Ok(rows)
+    }
+
+    /// Converts every row into memory
+    pub fn to_sheet_data(&self) -> Result<SheetData> {
+        let rows = self.read_rows(0, self.height())?;
+        let (width, height) = (self.width(), rows.len());
+        Ok(SheetData {
+            headers: self.headers.clone(),
+            rows,
+            formulas: vec![vec![None; width]; height],
+            merges: Vec::new(),
+            formats: CellFormats::default(),
+            origin: HEADER_ORIGIN,
+            width,
+            height,
+        })
+    }
+}
+
+/// Row counts of the record batches of an IPC file, read from the footer's
+/// block list and each batch's message header without touching the data
+fn batch_lengths(file: &mut (impl Read + Seek)) -> Result<Vec<usize>> {
+    let file_len = file.seek(SeekFrom::End(0))?;
+    // Lengths are checked against the file before anything is allocated for them
+    let within_file = |start: u64, len: usize| start.checked_add(len as u64) <= Some(file_len);
+    let mut trailer = [0u8; 10];
+    if file_len < trailer.len() as u64 {
+        bail!("Truncated Arrow file");
+    }
+    file.seek(SeekFrom::End(-(trailer.len() as i64)))?;
+    file.read_exact(&mut trailer)?;
+    let footer_len = read_footer_length(trailer)?;
+    if !within_file(trailer.len() as u64, footer_len) {
+        bail!("Truncated Arrow file footer");
+    }
+    let mut footer = vec![0u8; footer_len];
+    file.seek(SeekFrom::End(-((trailer.len() + footer_len) as i64)))?;
+    file.read_exact(&mut footer)?;
+    let footer = root_as_footer(&footer).map_err(|e| anyhow!("Invalid Arrow file footer: {e}"))?;
+
+    let mut lengths = Vec::new();
+    for block in footer.recordBatches().iter().flatten() {
+        let (offset, len) = (
+            u64::try_from(block.offset())?,
+            usize::try_from(block.metaDataLength())?,
+        );
+        if !within_file(offset, len) {
+            bail!("Arrow record batch lies outside the file");
+        }
+        let mut metadata = vec![0u8; len];
+        file.seek(SeekFrom::Start(offset))?;
+        file.read_exact(&mut metadata)?;
+        // Messages start with a length, preceded by a continuation marker since format 0.15
+        let message = metadata
+            .strip_prefix(&ARROW_STREAM_MAGIC)
+            .unwrap_or(&metadata)
+            .get(4..)
+            .context("Truncated Arrow record batch message")?;
+        let rows = root_as_message(message)
+            .map_err(|e| anyhow!("Invalid Arrow record batch message: {e}"))?
+            .header_as_record_batch()
+            .context("Arrow file block is not a record batch")?
+            .length();
+        lengths.push(usize::try_from(rows)?);
+    }
+    Ok(lengths)
+}
+
+fn headers(schema: &SchemaRef) -> Vec<String> {
+    schema
+        .fields()
+        .iter()
+        .map(|field| field.name().clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::{ArrayRef, Date32Array, Float64Array, Int32Array, StringArray};
+    use arrow_ipc::writer::{FileWriter, StreamWriter};
+    use std::sync::Arc;
+
+    /// Three batches of 4, 3 and 5 rows
+    fn sample_batches() -> Vec<RecordBatch> {
+        [4, 3, 5]
+            .into_iter()
+            .scan(0, |next, len| {
+                let ids: Vec<i32> = (*next..*next + len).collect();
+                *next += len;
+                let id: ArrayRef = Arc::new(Int32Array::from(ids.clone()));
+                let label: ArrayRef = Arc::new(StringArray::from_iter(
+                    ids.iter()
+                        .map(|i| (i % 3 != 0).then(|| format!("item {i}"))),
+                ));
+                let score: ArrayRef = Arc::new(Float64Array::from_iter_values(
+                    ids.iter().map(|&i| f64::from(i) / 2.0),
+                ));
+                // 19737 days after 1970-01-01 is 2024-01-15
+                let day: ArrayRef = Arc::new(Date32Array::from(vec![19737; ids.len()]));
+                Some(
+                    RecordBatch::try_from_iter([
+                        ("id", id),
+                        ("label", label),
+                        ("score", score),
+                        ("day", day),
+                    ])
+                    .unwrap(),
+                )
+            })
+            .collect()
+    }
+
+    fn sample_file() -> Vec<u8> {
+        let batches = sample_batches();
+        let mut out = Vec::new();
+        let mut writer = FileWriter::try_new(&mut out, &batches[0].schema()).unwrap();
+        for batch in &batches {
+            writer.write(batch).unwrap();
+        }
+        writer.finish().unwrap();
+        drop(writer);
+        out
+    }
+
+    #[test]
+    fn test_arrow_file_reads_across_batches() {
+        let path = std::env::temp_dir().join(format!("xleak-{}.feather", std::process::id()));
+        std::fs::write(&path, sample_file()).unwrap();
+        let arrow = ArrowFile::open(&path, "data").unwrap();
+
+        assert_eq!(arrow.headers, vec!["id", "label", "score", "day"]);
+        assert_eq!(arrow.height(), 12);
+        assert_eq!(
+            batch_lengths(&mut Cursor::new(sample_file())).unwrap(),
+            vec![4, 3, 5]
+        );
+        let rows = arrow.read_rows(2, 7).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let ids: Vec<String> = rows.iter().map(|r| r[0].to_raw_string()).collect();
+        assert_eq!(ids, vec!["2", "3", "4", "5", "6", "7", "8"]);
+        assert!(matches!(rows[1][1], CellValue::Empty));
+        assert!(matches!(&rows[0][1], CellValue::String(s) if s == "item 2"));
+        assert!(matches!(rows[0][2], CellValue::Float(f) if f == 1.0));
+        assert_eq!(rows[0][3].to_raw_string(), "2024-01-15");
+    }
+
+    #[test]
+    fn test_corrupt_arrow_file_is_an_error() {
+        let file = sample_file();
+        // Truncated files and damaged bytes anywhere must not panic
+        for cut in 0..file.len() {
+            let _ = batch_lengths(&mut Cursor::new(&file[..cut]));
+        }
+        for i in 0..file.len() - 4 {
+            let mut damaged = file.clone();
+            damaged[i] ^= 0xFF;
+            let _ = batch_lengths(&mut Cursor::new(damaged));
+            // A length too short to hold a message
+            let mut damaged = file.clone();
+            damaged[i..i + 4].copy_from_slice(&2i32.to_le_bytes());
+            let _ = batch_lengths(&mut Cursor::new(damaged));
+        }
+        assert!(batch_lengths(&mut Cursor::new(&file[..file.len() - 1])).is_err());
+        assert!(batch_lengths(&mut Cursor::new(b"\xff\xff\xff\x7fARROW1")).is_err());
+    }
+
+    #[test]
+    fn test_arrow_stream_from_bytes() {
+        let batches = sample_batches();
+        let mut out = Vec::new();
+        let mut writer = StreamWriter::try_new(&mut out, &batches[0].schema()).unwrap();
+        for batch in &batches {
+            writer.write(batch).unwrap();
+        }
+        writer.finish().unwrap();
+        drop(writer);
+        assert!(out.starts_with(&ARROW_STREAM_MAGIC));
+
+        let arrow = ArrowFile::from_bytes(out, "stdin").unwrap();
+        assert_eq!(arrow.height(), 12);
+        assert_eq!(arrow.read_rows(10, 5).unwrap().len(), 2);
+        assert_eq!(arrow.to_sheet_data().unwrap().height, 12);
+
+        let file = ArrowFile::from_bytes(sample_file(), "stdin").unwrap();
+        assert_eq!(file.read_rows(11, 1).unwrap()[0][0].to_raw_string(), "11");
+    }
+}
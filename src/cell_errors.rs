@@ -0,0 +1,154 @@
+use crate::workbook::column_letter;
+use calamine::{CellErrorType, Data, Range};
+use std::collections::BTreeMap;
+
+/// An error value in a spreadsheet cell, in the order of Excel's `ERROR.TYPE`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CellError {
+    Null,
+    Div0,
+    Value,
+    Ref,
+    Name,
+    Num,
+    NA,
+    GettingData,
+}
+
+impl CellError {
+    pub fn description(&self) -> &'static str {
+        match self {
+            CellError::Null => "ranges don't intersect",
+            CellError::Div0 => "division by zero",
+            CellError::Value => "wrong type of argument",
+            CellError::Ref => "invalid cell reference",
+            CellError::Name => "unknown name or function",
+            CellError::Num => "invalid number",
+            CellError::NA => "value not available",
+            CellError::GettingData => "still loading data",
+        }
+    }
+}
+
+impl From<&CellErrorType> for CellError {
+    fn from(error: &CellErrorType) -> Self {
+        match error {
+            CellErrorType::Null => CellError::Null,
+            CellErrorType::Div0 => CellError::Div0,
+            CellErrorType::Value => CellError::Value,
+            CellErrorType::Ref => CellError::Ref,
+            CellErrorType::Name => CellError::Name,
+            CellErrorType::Num => CellError::Num,
+            CellErrorType::NA => CellError::NA,
+            CellErrorType::GettingData => CellError::GettingData,
+        }
+    }
+}
+
+impl std::fmt::Display for CellError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            CellError::Null => "#NULL!",
+            CellError::Div0 => "#DIV/0!",
+            CellError::Value => "#VALUE!",
+            CellError::Ref => "#REF!",
+            CellError::Name => "#NAME?",
+            CellError::Num => "#NUM!",
+            CellError::NA => "#N/A",
+            CellError::GettingData => "#GETTING_DATA",
+        })
+    }
+}
+
+/// A cell holding an error value, at a zero-based sheet position
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ErrorCell {
+    pub row: u32,
+    pub col: u32,
+    pub error: CellError,
+    /// Formula that produced the error; `None` for a typed-in error
+    pub formula: Option<String>,
+}
+
+impl ErrorCell {
+    pub fn address(&self) -> String {
+        format!("{}{}", column_letter(self.col as usize), self.row + 1)
+    }
+}
+
+/// Error cells of a whole sheet, row by row
+pub fn find_errors(range: &Range<Data>, formulas: Option<&Range<String>>) -> Vec<ErrorCell> {
+    let (first_row, first_col) = range.start().unwrap_or((0, 0));
+    range
+        .cells()
+        .filter_map(|(row, col, cell)| {
+            let Data::Error(error) = cell else {
+                return None;
+            };
+            let (row, col) = (first_row + row as u32, first_col + col as u32);
+            let formula = formulas
+                .and_then(|formulas| formulas.get_value((row, col)))
+                .filter(|formula| !formula.is_empty())
+                .cloned();
+            Some(ErrorCell {
+                row,
+                col,
+                error: error.into(),
+                formula,
+            })
+        })
+        .collect()
+}
+
+/// How many cells hold each kind of error, e.g. `2 × #DIV/0!, 1 × #N/A`
+pub fn summary(errors: impl IntoIterator<Item = CellError>) -> String {
+    let mut counts = BTreeMap::new();
+    for error in errors {
+        *counts.entry(error).or_insert(0) += 1;
+    }
+    counts
+        .iter()
+        .map(|(error, count)| format!("{count} × {error}"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_errors() {
+        let mut range = Range::new((2, 1), (3, 2));
+        range.set_value((2, 1), Data::Float(1.0));
+        range.set_value((2, 2), Data::Error(CellErrorType::Div0));
+        range.set_value((3, 1), Data::Error(CellErrorType::NA));
+        range.set_value((3, 2), Data::Error(CellErrorType::Div0));
+        let mut formulas = Range::new((2, 2), (2, 2));
+        formulas.set_value((2, 2), "B3/0".to_string());
+
+        let cells = find_errors(&range, Some(&formulas));
+        let listed: Vec<(String, String, Option<&str>)> = cells
+            .iter()
+            .map(|cell| {
+                (
+                    cell.address(),
+                    cell.error.to_string(),
+                    cell.formula.as_deref(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            listed,
+            vec![
+                ("C3".to_string(), "#DIV/0!".to_string(), Some("B3/0")),
+                ("B4".to_string(), "#N/A".to_string(), None),
+                ("C4".to_string(), "#DIV/0!".to_string(), None),
+            ]
+        );
+        assert_eq!(
+            summary(cells.iter().map(|cell| cell.error)),
+            "2 × #DIV/0!, 1 × #N/A"
+        );
+    }
+}
@@ -0,0 +1,379 @@
+use crate::names;
+use crate::package;
+use crate::workbook::column_letter;
+use crate::xml::{attribute, number_attribute};
+use anyhow::Result;
+use quick_xml::events::Event;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Seek};
+use zip::ZipArchive;
+
+/// A comment or note on a cell. Each reply to a threaded comment is a separate
+/// `Comment` on the same cell, in thread order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Comment {
+    /// Zero-based sheet position
+    pub row: u32,
+    pub col: u32,
+    /// Empty when the file doesn't record one
+    pub author: String,
+    pub text: String,
+}
+
+impl Comment {
+    pub fn address(&self) -> String {
+        format!("{}{}", column_letter(self.col as usize), self.row + 1)
+    }
+}
+
+/// Comments of one sheet of an xlsx file: threaded comments, and legacy notes on
+/// cells without one, in sheet order
+pub fn read_xlsx_comments(reader: impl Read + Seek, sheet: &str) -> Result<Vec<Comment>> {
+    let mut archive = ZipArchive::new(reader)?;
+    let Some(path) = package::sheet_paths(&mut archive)?.remove(sheet) else {
+        return Ok(Vec::new());
+    };
+    let mut threaded = Vec::new();
+    let mut notes = Vec::new();
+    for relationship in package::relationships(&mut archive, &path)?.into_values() {
+        match relationship.kind.as_str() {
+            "threadedComment" if !relationship.external => {
+                let persons = read_persons(&mut archive)?;
+                let part = BufReader::new(archive.by_name(&relationship.target)?);
+                threaded.extend(parse_threaded_comments(part, &persons)?);
+            }
+            "comments" if !relationship.external => {
+                let part = BufReader::new(archive.by_name(&relationship.target)?);
+                notes.extend(parse_xlsx_notes(part)?);
+            }
+            _ => {}
+        }
+    }
+
+    // Excel also saves each threaded comment as a note, for older versions
+    notes.retain(|note| {
+        !threaded
+            .iter()
+            .any(|comment: &Comment| (comment.row, comment.col) == (note.row, note.col))
+    });
+    let mut comments = threaded;
+    comments.extend(notes);
+    // Stable, so replies stay in thread order
+    comments.sort_by_key(|comment| (comment.row, comment.col));
+    Ok(comments)
+}
+
+/// Display names of the people in threaded comments, by person id
+fn read_persons<R: Read + Seek>(archive: &mut ZipArchive<R>) -> Result<HashMap<String, String>> {
+    let Some(person) = package::relationships(archive, "xl/workbook.xml")?
+        .into_values()
+        .find(|relationship| relationship.kind == "person")
+    else {
+        return Ok(HashMap::new());
+    };
+    let mut xml = quick_xml::Reader::from_reader(BufReader::new(archive.by_name(&person.target)?));
+    let mut buf = Vec::new();
+    let mut persons = HashMap::new();
+    loop {
+        buf.clear();
+        match xml.read_event_into(&mut buf)? {
+            Event::Start(e) | Event::Empty(e) if e.local_name().as_ref() == b"person" => {
+                if let (Some(id), Some(name)) =
+                    (attribute(&e, "id")?, attribute(&e, "displayName")?)
+                {
+                    persons.insert(id, name);
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(persons)
+}
+
+/// Legacy notes from an `xl/commentsN.xml` part
+fn parse_xlsx_notes(part: impl BufRead) -> Result<Vec<Comment>> {
+    let mut xml = quick_xml::Reader::from_reader(part);
+    let mut buf = Vec::new();
+    let mut authors: Vec<String> = Vec::new();
+    let mut notes = Vec::new();
+    let mut current: Option<Comment> = None;
+    // Text lives in `t` elements, directly or in formatted runs
+    let (mut in_author, mut in_text) = (false, false);
+
+    loop {
+        buf.clear();
+        match xml.read_event_into(&mut buf)? {
+            Event::Start(e) => match e.local_name().as_ref() {
+                b"author" => {
+                    authors.push(String::new());
+                    in_author = true;
+                }
+                b"comment" => {
+                    let position = attribute(&e, "ref")?.and_then(|r| names::parse_cell(&r));
+                    let author = attribute(&e, "authorId")?
+                        .and_then(|id| id.parse::<usize>().ok())
+                        .and_then(|id| authors.get(id).cloned())
+                        .unwrap_or_default();
+                    if let Some((Some(row), Some(col))) = position {
+                        current = Some(Comment {
+                            row,
+                            col,
+                            author,
+                            text: String::new(),
+                        });
+                    }
+                }
+                b"t" => in_text = true,
+                _ => {}
+            },
+            Event::Text(text) => {
+                if in_author && let Some(author) = authors.last_mut() {
+                    author.push_str(&text.unescape()?);
+                } else if in_text && let Some(comment) = &mut current {
+                    comment.text.push_str(&text.unescape()?);
+                }
+            }
+            Event::End(e) => match e.local_name().as_ref() {
+                b"author" => in_author = false,
+                b"t" => in_text = false,
+                b"comment" => {
+                    if let Some(mut note) = current.take() {
+                        // Excel starts a note with its author's name in bold
+                        let prefix = format!("{}:", note.author);
+                        if !note.author.is_empty() && note.text.starts_with(&prefix) {
+                            note.text.drain(..prefix.len());
+                        }
+                        note.text = note.text.trim().to_string();
+                        notes.push(note);
+                    }
+                }
+                _ => {}
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(notes)
+}
+
+/// Threaded comments and their replies from an `xl/threadedComments/*.xml` part
+fn parse_threaded_comments(
+    part: impl BufRead,
+    persons: &HashMap<String, String>,
+) -> Result<Vec<Comment>> {
+    let mut xml = quick_xml::Reader::from_reader(part);
+    let mut buf = Vec::new();
+    let mut comments = Vec::new();
+    let mut current: Option<Comment> = None;
+    let mut in_text = false;
+
+    loop {
+        buf.clear();
+        match xml.read_event_into(&mut buf)? {
+            Event::Start(e) => match e.local_name().as_ref() {
+                b"threadedComment" => {
+                    let position = attribute(&e, "ref")?.and_then(|r| names::parse_cell(&r));
+                    let author = attribute(&e, "personId")?
+                        .and_then(|id| persons.get(&id).cloned())
+                        .unwrap_or_default();
+                    if let Some((Some(row), Some(col))) = position {
+                        current = Some(Comment {
+                            row,
+                            col,
+                            author,
+                            text: String::new(),
+                        });
+                    }
+                }
+                b"text" => in_text = true,
+                _ => {}
+            },
+            Event::Text(text) if in_text => {
+                if let Some(comment) = &mut current {
+                    comment.text.push_str(&text.unescape()?);
+                }
+            }
+            Event::End(e) => match e.local_name().as_ref() {
+                b"text" => in_text = false,
+                b"threadedComment" => comments.extend(current.take()),
+                _ => {}
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(comments)
+}
+
+/// Annotations on the cells of every sheet of an OpenDocument spreadsheet, keyed by sheet name
+pub fn read_ods_comments(reader: impl Read + Seek) -> Result<HashMap<String, Vec<Comment>>> {
+    let mut archive = ZipArchive::new(reader)?;
+    let content = archive.by_name("content.xml")?;
+    parse_ods_comments(BufReader::new(content))
+}
+
+fn parse_ods_comments(content: impl BufRead) -> Result<HashMap<String, Vec<Comment>>> {
+    let mut xml = quick_xml::Reader::from_reader(content);
+    let mut buf = Vec::new();
+    let mut sheets = HashMap::new();
+    let mut sheet: Option<(String, Vec<Comment>)> = None;
+    let (mut row, mut col, mut row_repeat) = (0u32, 0u32, 1u32);
+    // Column of the cell being read; `col` has already moved past it
+    let mut cell_col = 0u32;
+    let mut current: Option<Comment> = None;
+    let (mut in_creator, mut in_paragraph) = (false, false);
+
+    loop {
+        buf.clear();
+        let (element, is_empty) = match xml.read_event_into(&mut buf)? {
+            Event::Start(e) => (e, false),
+            Event::Empty(e) => (e, true),
+            Event::Text(text) => {
+                if let Some(comment) = &mut current {
+                    if in_creator {
+                        comment.author.push_str(&text.unescape()?);
+                    } else if in_paragraph {
+                        comment.text.push_str(&text.unescape()?);
+                    }
+                }
+                continue;
+            }
+            Event::End(e) => {
+                match e.name().as_ref() {
+                    b"table:table-row" => row += row_repeat,
+                    b"table:table" => sheets.extend(sheet.take()),
+                    b"dc:creator" => in_creator = false,
+                    b"text:p" => in_paragraph = false,
+                    b"office:annotation" => {
+                        if let (Some(comment), Some((_, comments))) = (current.take(), &mut sheet) {
+                            comments.push(comment);
+                        }
+                    }
+                    _ => {}
+                }
+                continue;
+            }
+            Event::Eof => break,
+            _ => continue,
+        };
+
+        match element.name().as_ref() {
+            b"table:table" => {
+                let name = attribute(&element, "table:name")?.unwrap_or_default();
+                sheet = Some((name, Vec::new()));
+                row = 0;
+            }
+            b"table:table-row" => {
+                col = 0;
+                row_repeat = number_attribute(&element, "table:number-rows-repeated")?;
+                if is_empty {
+                    row += row_repeat;
+                }
+            }
+            b"table:table-cell" | b"table:covered-table-cell" => {
+                cell_col = col;
+                col += number_attribute(&element, "table:number-columns-repeated")?;
+            }
+            b"office:annotation" if !is_empty => {
+                current = Some(Comment {
+                    row,
+                    col: cell_col,
+                    author: String::new(),
+                    text: String::new(),
+                });
+            }
+            b"dc:creator" => in_creator = !is_empty,
+            b"text:p" if current.is_some() => {
+                // One line per paragraph
+                if let Some(comment) = &mut current
+                    && !comment.text.is_empty()
+                {
+                    comment.text.push('\n');
+                }
+                in_paragraph = !is_empty;
+            }
+            b"text:s" if in_paragraph => {
+                if let Some(comment) = &mut current {
+                    let spaces = number_attribute(&element, "text:c")? as usize;
+                    comment.text.push_str(&" ".repeat(spaces));
+                }
+            }
+            b"text:line-break" if in_paragraph => {
+                if let Some(comment) = &mut current {
+                    comment.text.push('\n');
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(sheets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_xlsx_comments() {
+        let notes = r#"<comments><authors><author>Ana Ruiz</author><author>tc={1A2B}</author></authors>
+            <commentList>
+            <comment ref="C4" authorId="0"><text><r><rPr><b/></rPr><t>Ana Ruiz:</t></r><r><t xml:space="preserve">
+Check the Q3 total</t></r></text></comment>
+            <comment ref="B2" authorId="1"><text><t>[Threaded comment] ...</t></text></comment>
+            </commentList></comments>"#;
+        let notes = parse_xlsx_notes(notes.as_bytes()).unwrap();
+        assert_eq!(notes.len(), 2);
+        assert_eq!(
+            (notes[0].address(), notes[0].author.as_str()),
+            ("C4".to_string(), "Ana Ruiz")
+        );
+        assert_eq!(notes[0].text, "Check the Q3 total");
+
+        let threaded = r#"<ThreadedComments>
+            <threadedComment ref="B2" personId="{P1}" id="{C1}"><text>Is this final?</text></threadedComment>
+            <threadedComment ref="B2" personId="{P2}" id="{C2}" parentId="{C1}"><text>Yes</text></threadedComment>
+            </ThreadedComments>"#;
+        let persons = HashMap::from([
+            ("{P1}".to_string(), "Ana Ruiz".to_string()),
+            ("{P2}".to_string(), "Li Wei".to_string()),
+        ]);
+        let comments = parse_threaded_comments(threaded.as_bytes(), &persons).unwrap();
+        let listed: Vec<(&str, &str)> = comments
+            .iter()
+            .map(|comment| (comment.author.as_str(), comment.text.as_str()))
+            .collect();
+        assert_eq!(
+            listed,
+            vec![("Ana Ruiz", "Is this final?"), ("Li Wei", "Yes")]
+        );
+    }
+
+    #[test]
+    fn test_parse_ods_comments() {
+        let content = r#"<office:document-content><office:body><office:spreadsheet>
+            <table:table table:name="Budget">
+              <table:table-row table:number-rows-repeated="2"><table:table-cell/></table:table-row>
+              <table:table-row>
+                <table:table-cell table:number-columns-repeated="2"/>
+                <table:table-cell office:value-type="float" office:value="12">
+                  <office:annotation><dc:creator>Ana Ruiz</dc:creator><dc:date>2024-03-01T10:00:00</dc:date>
+                    <text:p>Too high?</text:p><text:p>See<text:s text:c="2"/>notes</text:p>
+                  </office:annotation>
+                  <text:p>12</text:p>
+                </table:table-cell>
+              </table:table-row>
+            </table:table>
+        </office:spreadsheet></office:body></office:document-content>"#;
+        let comments = parse_ods_comments(content.as_bytes()).unwrap();
+        assert_eq!(
+            comments["Budget"],
+            vec![Comment {
+                row: 2,
+                col: 2,
+                author: "Ana Ruiz".to_string(),
+                text: "Too high?\nSee  notes".to_string(),
+            }]
+        );
+    }
+}
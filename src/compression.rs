@@ -0,0 +1,109 @@
+use anyhow::Result;
+use std::io::Read;
+
+const GZIP_MAGIC: [u8; 2] = [0x1F, 0x8B];
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xB5, 0x2F, 0xFD];
+/// "BZh", a block size digit, then the block or end-of-stream signature
+const BZIP2_MAGIC: &[u8] = b"BZh";
+const BZIP2_BLOCK_MAGIC: [u8; 6] = [0x31, 0x41, 0x59, 0x26, 0x53, 0x59];
+const BZIP2_EOS_MAGIC: [u8; 6] = [0x17, 0x72, 0x45, 0x38, 0x50, 0x90];
+
+/// Number of leading bytes needed to recognize any supported format
+pub const MAGIC_LEN: usize = 10;
+
+/// Compression formats unwrapped before the data is parsed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    Gzip,
+    Zstd,
+    Bzip2,
+}
+
+impl Compression {
+    pub fn from_extension(ext: &str) -> Option<Self> {
+        match ext.to_ascii_lowercase().as_str() {
+            "gz" | "gzip" => Some(Self::Gzip),
+            "zst" | "zstd" => Some(Self::Zstd),
+            "bz2" | "bzip2" => Some(Self::Bzip2),
+            _ => None,
+        }
+    }
+
+    /// Recognizes a compressed stream from its leading bytes
+    pub fn sniff(bytes: &[u8]) -> Option<Self> {
+        if bytes.starts_with(&GZIP_MAGIC) {
+            Some(Self::Gzip)
+        } else if bytes.starts_with(&ZSTD_MAGIC) {
+            Some(Self::Zstd)
+        } else if bytes.starts_with(BZIP2_MAGIC)
+            && bytes.get(3).is_some_and(|b| (b'1'..=b'9').contains(b))
+            && bytes
+                .get(4..MAGIC_LEN)
+                .is_some_and(|sig| sig == BZIP2_BLOCK_MAGIC || sig == BZIP2_EOS_MAGIC)
+        {
+            Some(Self::Bzip2)
+        } else {
+            None
+        }
+    }
+
+    /// Wraps `input` in a streaming decoder (concatenated members are read through)
+    pub fn decoder<'a>(self, input: impl Read + 'a) -> Result<Box<dyn Read + 'a>> {
+        Ok(match self {
+            Self::Gzip => Box::new(flate2::read::MultiGzDecoder::new(input)),
+            Self::Zstd => Box::new(zstd::stream::read::Decoder::new(input)?),
+            Self::Bzip2 => Box::new(bzip2::read::MultiBzDecoder::new(input)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn compress(compression: Compression, data: &[u8]) -> Vec<u8> {
+        match compression {
+            Compression::Gzip => {
+                let mut encoder =
+                    flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+                encoder.write_all(data).unwrap();
+                encoder.finish().unwrap()
+            }
+            Compression::Zstd => zstd::encode_all(data, 0).unwrap(),
+            Compression::Bzip2 => {
+                let mut encoder =
+                    bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::fast());
+                encoder.write_all(data).unwrap();
+                encoder.finish().unwrap()
+            }
+        }
+    }
+
+    #[test]
+    fn test_compression_from_extension() {
+        assert_eq!(Compression::from_extension("GZ"), Some(Compression::Gzip));
+        assert_eq!(Compression::from_extension("zst"), Some(Compression::Zstd));
+        assert_eq!(Compression::from_extension("bz2"), Some(Compression::Bzip2));
+        assert_eq!(Compression::from_extension("csv"), None);
+    }
+
+    #[test]
+    fn test_sniff_and_decode_round_trip() {
+        let data = b"name,value\nAlice,1\n";
+        for compression in [Compression::Gzip, Compression::Zstd, Compression::Bzip2] {
+            let packed = compress(compression, data);
+            assert_eq!(Compression::sniff(&packed), Some(compression));
+
+            let mut unpacked = Vec::new();
+            compression
+                .decoder(packed.as_slice())
+                .unwrap()
+                .read_to_end(&mut unpacked)
+                .unwrap();
+            assert_eq!(unpacked, data);
+        }
+        assert_eq!(Compression::sniff(b"BZh,name\n1,2\n"), None);
+        assert_eq!(Compression::sniff(data), None);
+    }
+}
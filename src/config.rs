@@ -1,3 +1,4 @@
+use crate::csv_source::{ColumnType, DateOrder, TypeInference};
 use anyhow::{Context, Result};
 use crossterm::event::{KeyCode, KeyModifiers};
 use serde::{Deserialize, Serialize};
@@ -13,6 +14,7 @@ pub struct Config {
     pub theme: ThemeConfig,
     pub ui: UiConfig,
     pub keybindings: KeybindingsConfig,
+    pub csv: CsvConfig,
 }
 
 /// Theme configuration
@@ -44,6 +46,29 @@ pub struct KeybindingsConfig {
     pub custom: HashMap<String, String>,
 }
 
+/// CSV loading configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CsvConfig {
+    /// Type inference mode: "standard" or "conservative"
+    pub infer: TypeInference,
+    /// Order of day and month in numeric dates: "ymd", "dmy" or "mdy"
+    pub date_order: DateOrder,
+    /// Extra chrono date formats to recognize, e.g. "%d %b %Y"
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub date_formats: Vec<String>,
+    /// Column type overrides for files whose name matches a pattern
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub column_types: Vec<ColumnTypeRule>,
+}
+
+/// Column types applied to files matching a glob-style pattern (`*` and `?`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColumnTypeRule {
+    pub pattern: String,
+    pub columns: HashMap<String, ColumnType>,
+}
+
 impl Default for ThemeConfig {
     fn default() -> Self {
         Self {
@@ -173,6 +198,8 @@ profile = "default"
 # copy_row = "C"
 # jump = "Ctrl+g"
 # show_cell_detail = "Enter"
+# follow_link = "o"
+# show_macros = "m"
 
 # VIM-style navigation (when profile = "vim")
 # up = "k"
@@ -185,6 +212,22 @@ profile = "default"
 # jump_to_bottom = "G"
 # jump_to_row_start = "0"
 # jump_to_row_end = "$"
+
+[csv]
+# Type inference: "standard" (numbers become numbers) or "conservative"
+# (zero-padded and very long digit strings such as ZIP codes and IDs stay text)
+infer = "standard"
+
+# Numeric dates: "ymd" (only 2024-03-04 style), "dmy" (04/03/2024) or "mdy" (03/04/2024)
+date_order = "ymd"
+
+# Extra date formats to recognize (chrono syntax)
+# date_formats = ["%d %b %Y", "%Y%m%d"]
+
+# Column types for files matching a pattern (string, int, float, date, bool, auto)
+# [[csv.column_types]]
+# pattern = "customers_*.csv"
+# columns = { zip = "string", balance = "float" }
 "#
         .to_string()
     }
@@ -202,6 +245,43 @@ profile = "default"
             _ => get_default_keybinding(action),
         }
     }
+
+    /// Column type overrides for a file name, in rule order (later rules take precedence)
+    pub fn csv_column_types_for(&self, file_name: &str) -> Vec<(&str, ColumnType)> {
+        self.csv
+            .column_types
+            .iter()
+            .filter(|rule| wildcard_match(&rule.pattern, file_name))
+            .flat_map(|rule| rule.columns.iter().map(|(c, t)| (c.as_str(), *t)))
+            .collect()
+    }
+}
+
+/// Match a file name against a pattern where `*` matches any run of characters and `?` one character
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, n));
+            p += 1;
+        } else if let Some((star_p, star_n)) = star {
+            // Let the last `*` absorb one more character and retry
+            p = star_p + 1;
+            n = star_n + 1;
+            star = Some((star_p, star_n + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
 }
 
 /// Parse a key string like "q", "Ctrl+g", "Enter" into KeyCode and KeyModifiers
@@ -260,6 +340,8 @@ fn get_default_keybinding(action: &str) -> Option<(KeyCode, KeyModifiers)> {
         "copy_row" => ("C", KeyModifiers::SHIFT),
         "jump" => ("g", KeyModifiers::CONTROL),
         "show_cell_detail" => ("Enter", KeyModifiers::empty()),
+        "follow_link" => ("o", KeyModifiers::empty()),
+        "show_macros" => ("m", KeyModifiers::empty()),
         "next_sheet" => ("Tab", KeyModifiers::empty()),
         "prev_sheet" => ("Tab", KeyModifiers::SHIFT),
         "up" => ("Up", KeyModifiers::empty()),
@@ -549,6 +631,52 @@ page_up = "Ctrl+b"
         assert_eq!(parse_key_string("Unknown+g"), None);
     }
 
+    // =========================================================================
+    // CSV Config Tests
+    // =========================================================================
+
+    #[test]
+    fn test_csv_column_type_rules() {
+        let config_str = r#"
+[csv]
+infer = "conservative"
+date_order = "dmy"
+date_formats = ["%d %b %Y"]
+
+[[csv.column_types]]
+pattern = "*.csv"
+columns = { zip = "string" }
+
+[[csv.column_types]]
+pattern = "orders_??.csv"
+columns = { zip = "int", amount = "float" }
+"#;
+        let config: Config = toml::from_str(config_str).unwrap();
+        assert_eq!(config.csv.infer, TypeInference::Conservative);
+        assert_eq!(config.csv.date_order, DateOrder::Dmy);
+        assert_eq!(config.csv.date_formats, vec!["%d %b %Y"]);
+
+        let types = config.csv_column_types_for("orders_01.csv");
+        assert_eq!(types.len(), 3);
+        assert_eq!(types[0], ("zip", ColumnType::String));
+        assert!(types[1..].contains(&("zip", ColumnType::Int)));
+        assert!(types[1..].contains(&("amount", ColumnType::Float)));
+
+        let types = config.csv_column_types_for("customers.csv");
+        assert_eq!(types, vec![("zip", ColumnType::String)]);
+        assert!(config.csv_column_types_for("data.tsv").is_empty());
+    }
+
+    #[test]
+    fn test_wildcard_match() {
+        assert!(wildcard_match("*.csv", "data.csv"));
+        assert!(wildcard_match("data_*_2024.csv", "data_sales_2024.csv"));
+        assert!(wildcard_match("file?.tsv", "file1.tsv"));
+        assert!(wildcard_match("*", "anything"));
+        assert!(!wildcard_match("*.csv", "data.tsv"));
+        assert!(!wildcard_match("file?.tsv", "file12.tsv"));
+    }
+
     // =========================================================================
     // Profile Behavior Tests
     // =========================================================================
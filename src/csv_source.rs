@@ -0,0 +1,1294 @@
+use crate::styles::CellFormats;
+use crate::workbook::{
+    CellValue, CsvData, HeaderOptions, SheetData, column_letter, column_letter_headers,
+    flatten_header_rows,
+};
+use anyhow::{Result, bail};
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+use encoding_rs::{Encoding, UTF_8, UTF_16BE, UTF_16LE};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Cursor, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+/// Delimiters considered when sniffing, in order of preference on ties
+const SNIFF_CANDIDATES: [u8; 4] = [b',', b'\t', b';', b'|'];
+/// Number of leading bytes inspected to detect the delimiter
+const SNIFF_SAMPLE_BYTES: u64 = 64 * 1024;
+/// Maximum number of records inspected to detect the delimiter
+const SNIFF_SAMPLE_LINES: usize = 50;
+/// Digit strings longer than this can't round-trip through f64 and are kept as text
+const MAX_EXACT_DIGITS: usize = 15;
+/// Records between stored byte offsets in the row index
+const ROW_INDEX_STRIDE: usize = 128;
+/// Read buffer size used while building the row index
+const INDEX_BUFFER_BYTES: usize = 1 << 20;
+/// Unambiguous year-first date layouts, always recognized
+const YMD_FORMATS: [&str; 2] = ["%Y-%m-%d", "%Y/%m/%d"];
+/// Day-first date layouts, recognized with `DateOrder::Dmy`
+const DMY_FORMATS: [&str; 3] = ["%d/%m/%Y", "%d.%m.%Y", "%d-%m-%Y"];
+/// Month-first date layouts, recognized with `DateOrder::Mdy`
+const MDY_FORMATS: [&str; 2] = ["%m/%d/%Y", "%m-%d-%Y"];
+/// Time-of-day suffixes accepted after any date layout
+const TIME_SUFFIXES: [&str; 4] = [" %H:%M:%S%.f", "T%H:%M:%S%.f", " %H:%M", "T%H:%M"];
+
+/// Explicit type for a CSV column
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColumnType {
+    /// Infer the type of each cell
+    Auto,
+    /// Always keep the text as-is
+    #[serde(alias = "str", alias = "text")]
+    String,
+    /// Whole numbers
+    #[serde(alias = "integer")]
+    Int,
+    /// Decimal numbers
+    #[serde(alias = "number", alias = "double")]
+    Float,
+    /// Dates and timestamps
+    #[serde(alias = "datetime", alias = "timestamp")]
+    Date,
+    /// true/false, yes/no, 1/0
+    #[serde(alias = "boolean")]
+    Bool,
+}
+
+impl std::str::FromStr for ColumnType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "auto" => Ok(Self::Auto),
+            "string" | "str" | "text" => Ok(Self::String),
+            "int" | "integer" => Ok(Self::Int),
+            "float" | "number" | "double" => Ok(Self::Float),
+            "date" | "datetime" | "timestamp" => Ok(Self::Date),
+            "bool" | "boolean" => Ok(Self::Bool),
+            other => Err(format!(
+                "unknown column type '{other}' (expected string, int, float, date, bool or auto)"
+            )),
+        }
+    }
+}
+
+/// How cell types are inferred for columns without an explicit type
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum TypeInference {
+    /// Anything that parses as a number becomes a number
+    #[default]
+    Standard,
+    /// Zero-padded and over-long digit strings (ZIP codes, IDs) stay text
+    Conservative,
+}
+
+/// Field order for numeric dates such as `03/04/2024`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum DateOrder {
+    /// Only unambiguous year-first dates (`2024-04-03`) are recognized
+    #[default]
+    Ymd,
+    /// Day first (`03/04/2024` is 3 April)
+    Dmy,
+    /// Month first (`03/04/2024` is 4 March)
+    Mdy,
+}
+
+/// CSV dialect settings (user overrides or detected values)
+#[derive(Debug, Clone)]
+pub struct CsvOptions {
+    /// Field delimiter; detected from the data when `None`
+    pub delimiter: Option<u8>,
+    /// Quote character
+    pub quote: u8,
+    /// Escape character for quotes inside quoted fields (quotes are doubled when `None`)
+    pub escape: Option<u8>,
+    /// Explicit column types keyed by lowercased header name
+    pub column_types: HashMap<String, ColumnType>,
+    /// Inference mode for columns without an explicit type
+    pub inference: TypeInference,
+    /// Order used for day/month dates in addition to year-first ones
+    pub date_order: DateOrder,
+    /// Extra chrono format strings tried before the built-in date layouts
+    pub date_formats: Vec<String>,
+    /// Text encoding; detected from the data when `None`
+    pub encoding: Option<&'static Encoding>,
+    /// Leading records to skip and whether the next one names the columns
+    pub header: HeaderOptions,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: None,
+            quote: b'"',
+            escape: None,
+            column_types: HashMap::new(),
+            inference: TypeInference::default(),
+            date_order: DateOrder::default(),
+            date_formats: Vec::new(),
+            encoding: None,
+            header: HeaderOptions::default(),
+        }
+    }
+}
+
+impl CsvOptions {
+    /// Sets the type for a column, replacing any earlier setting for it
+    pub fn set_column_type(&mut self, column: &str, column_type: ColumnType) {
+        self.column_types
+            .insert(column.trim().to_lowercase(), column_type);
+    }
+
+    fn column_type(&self, header: &str) -> ColumnType {
+        self.column_types
+            .get(&header.trim().to_lowercase())
+            .copied()
+            .unwrap_or(ColumnType::Auto)
+    }
+}
+
+/// Parses one `--types` entry like `zip=string`
+pub fn parse_column_type_arg(s: &str) -> Result<(String, ColumnType), String> {
+    let (column, typ) = s
+        .rsplit_once('=')
+        .ok_or_else(|| format!("expected COLUMN=TYPE, got '{s}'"))?;
+    Ok((column.trim().to_string(), typ.parse()?))
+}
+
+/// Parses an `--encoding` label such as `utf-8`, `windows-1252`, `latin1` or `utf-16le`
+pub fn parse_encoding_arg(s: &str) -> Result<&'static Encoding, String> {
+    Encoding::for_label(s.trim().as_bytes()).ok_or_else(|| format!("unknown encoding '{s}'"))
+}
+
+/// Whether files with this extension are loaded through the CSV reader
+pub fn is_delimited_extension(ext: &str) -> bool {
+    matches!(
+        ext.to_ascii_lowercase().as_str(),
+        "csv" | "tsv" | "tab" | "psv" | "txt"
+    )
+}
+
+/// Delimiter implied by a file extension (`.csv` and `.txt` are sniffed instead)
+pub fn extension_delimiter(ext: &str) -> Option<u8> {
+    match ext.to_ascii_lowercase().as_str() {
+        "tsv" | "tab" => Some(b'\t'),
+        "psv" => Some(b'|'),
+        _ => None,
+    }
+}
+
+/// Parses a single-byte dialect character from the command line.
+/// Accepts a literal character or a name such as `tab`, `\t`, `comma`, `semicolon`, `pipe`.
+pub fn parse_char_arg(s: &str) -> Result<u8, String> {
+    let byte = match s.to_ascii_lowercase().as_str() {
+        "tab" | "\\t" => b'\t',
+        "comma" => b',',
+        "semicolon" => b';',
+        "pipe" => b'|',
+        "space" => b' ',
+        _ => match s.as_bytes() {
+            [b] if b.is_ascii() => *b,
+            _ => return Err(format!("expected a single ASCII character, got '{s}'")),
+        },
+    };
+    Ok(byte)
+}
+
+/// Guesses the field delimiter from a sample of the data.
+///
+/// Each candidate is counted per record (ignoring quoted sections); the one that
+/// splits the most records into the same number of fields as the first record wins.
+pub fn sniff_delimiter(sample: &[u8], quote: u8) -> u8 {
+    let mut best = (b',', 0usize, 0usize);
+
+    for &candidate in &SNIFF_CANDIDATES {
+        let counts = count_per_record(sample, candidate, quote);
+        let Some(&first) = counts.first() else {
+            continue;
+        };
+        if first == 0 {
+            continue;
+        }
+        let consistent = counts.iter().filter(|&&c| c == first).count();
+        if (consistent, first) > (best.1, best.2) {
+            best = (candidate, consistent, first);
+        }
+    }
+
+    best.0
+}
+
+/// Picks the text encoding of a sample and the length of any byte-order mark to skip.
+///
+/// An explicit encoding wins; otherwise a BOM, a UTF-16 NUL pattern, valid UTF-8 and
+/// finally a statistical guess (e.g. Windows-1252 for Excel's "CSV" export) are tried in turn.
+pub fn detect_encoding(
+    sample: &[u8],
+    forced: Option<&'static Encoding>,
+) -> (&'static Encoding, usize) {
+    let bom = Encoding::for_bom(sample);
+    if let Some(forced) = forced {
+        let bom_len = bom
+            .filter(|(encoding, _)| *encoding == forced)
+            .map_or(0, |(_, len)| len);
+        return (forced, bom_len);
+    }
+    if let Some(bom) = bom {
+        return bom;
+    }
+    if let Some(encoding) = sniff_utf16(sample) {
+        return (encoding, 0);
+    }
+    match std::str::from_utf8(sample) {
+        // `error_len() == None` means a character was cut off at the end of the sample
+        Ok(_) => return (UTF_8, 0),
+        Err(e) if e.error_len().is_none() => return (UTF_8, 0),
+        Err(_) => {}
+    }
+    let mut detector = chardetng::EncodingDetector::new();
+    detector.feed(sample, false);
+    (detector.guess(None, false), 0)
+}
+
+/// BOM-less UTF-16 shows up as a NUL in every other byte for mostly-ASCII text
+fn sniff_utf16(sample: &[u8]) -> Option<&'static Encoding> {
+    let pairs = sample.len() / 2;
+    if pairs == 0 {
+        return None;
+    }
+    let (mut even, mut odd) = (0, 0);
+    for pair in sample.chunks_exact(2) {
+        even += usize::from(pair[0] == 0);
+        odd += usize::from(pair[1] == 0);
+    }
+    if odd * 2 > pairs && even * 10 < pairs {
+        Some(UTF_16LE)
+    } else if even * 2 > pairs && odd * 10 < pairs {
+        Some(UTF_16BE)
+    } else {
+        None
+    }
+}
+
+/// Decodes one field, replacing invalid sequences rather than failing
+fn decode_field<'a>(encoding: &'static Encoding, bytes: &'a [u8]) -> Cow<'a, str> {
+    encoding.decode_without_bom_handling(bytes).0
+}
+
+/// Whether rows of this file can be located by raw byte offset, which needs an
+/// encoding that leaves ASCII delimiters, quotes and newlines as single bytes
+pub fn supports_indexing(path: &Path, options: &CsvOptions) -> Result<bool> {
+    let sample = read_sample(&mut File::open(path)?)?;
+    let (encoding, _) = detect_encoding(&sample, options.encoding);
+    Ok(encoding.is_ascii_compatible())
+}
+
+/// Counts `delimiter` occurrences in each complete record of the sample
+fn count_per_record(sample: &[u8], delimiter: u8, quote: u8) -> Vec<usize> {
+    let mut counts = Vec::new();
+    let mut current = 0;
+    let mut in_quotes = false;
+
+    for &b in sample {
+        if b == quote {
+            in_quotes = !in_quotes;
+        } else if in_quotes {
+            continue;
+        } else if b == delimiter {
+            current += 1;
+        } else if b == b'\n' {
+            counts.push(current);
+            current = 0;
+            if counts.len() >= SNIFF_SAMPLE_LINES {
+                break;
+            }
+        }
+    }
+
+    // Fall back to a lone unterminated record (one-line input)
+    if counts.is_empty() && current > 0 {
+        counts.push(current);
+    }
+
+    counts
+}
+
+/// Digit strings that would lose information as numbers: zero-padded codes
+/// (`02134`) and IDs too long to be represented exactly
+fn looks_like_identifier(s: &str) -> bool {
+    let digits = s.strip_prefix('-').unwrap_or(s);
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return false;
+    }
+    (digits.len() > 1 && digits.starts_with('0')) || digits.len() > MAX_EXACT_DIGITS
+}
+
+/// Recognizes dates and timestamps using the configured formats and date order
+struct DateParser {
+    custom: Vec<String>,
+    builtin: Vec<String>,
+}
+
+impl DateParser {
+    fn new(options: &CsvOptions) -> Self {
+        let order_formats: &[&str] = match options.date_order {
+            DateOrder::Ymd => &[],
+            DateOrder::Dmy => &DMY_FORMATS,
+            DateOrder::Mdy => &MDY_FORMATS,
+        };
+        let mut builtin = Vec::new();
+        for date in YMD_FORMATS.iter().chain(order_formats) {
+            builtin.push(date.to_string());
+            builtin.extend(TIME_SUFFIXES.iter().map(|time| format!("{date}{time}")));
+        }
+        Self {
+            custom: options.date_formats.clone(),
+            builtin,
+        }
+    }
+
+    fn parse(&self, s: &str) -> Option<NaiveDateTime> {
+        let s = s.trim();
+        let parsed = self
+            .custom
+            .iter()
+            .find_map(|format| parse_with_format(s, format))
+            .or_else(|| {
+                // Every built-in layout starts with a digit; skip the rest cheaply
+                if !s.starts_with(|c: char| c.is_ascii_digit()) {
+                    return None;
+                }
+                self.builtin
+                    .iter()
+                    .find_map(|format| parse_with_format(s, format))
+                    .or_else(|| {
+                        DateTime::parse_from_rfc3339(s)
+                            .ok()
+                            .map(|dt| dt.naive_local())
+                    })
+            })?;
+        // `%Y` happily accepts two-digit years; treat those as not-a-date
+        (parsed.date() >= NaiveDate::from_ymd_opt(1900, 1, 1)?).then_some(parsed)
+    }
+}
+
+/// Parses with a date-only or date-and-time chrono format
+fn parse_with_format(s: &str, format: &str) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(s, format).ok().or_else(|| {
+        NaiveDate::parse_from_str(s, format)
+            .ok()
+            .and_then(|date| date.and_hms_opt(0, 0, 0))
+    })
+}
+
+/// Spellings accepted by an explicit `bool` column
+fn parse_bool(s: &str) -> Option<bool> {
+    match s.trim().to_ascii_lowercase().as_str() {
+        "true" | "t" | "yes" | "y" | "1" => Some(true),
+        "false" | "f" | "no" | "n" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+/// Converts CSV fields to CellValues using column types and inference settings
+struct FieldParser {
+    inference: TypeInference,
+    dates: DateParser,
+}
+
+impl FieldParser {
+    fn new(options: &CsvOptions) -> Self {
+        Self {
+            inference: options.inference,
+            dates: DateParser::new(options),
+        }
+    }
+
+    /// Converts a CSV field to a CellValue using the column's explicit type or inference mode
+    fn parse(&self, s: &str, column_type: ColumnType) -> CellValue {
+        if s.is_empty() {
+            return CellValue::Empty;
+        }
+        let text = || CellValue::String(s.to_string());
+        match column_type {
+            ColumnType::String => text(),
+            // Explicit types fall back to text rather than dropping unparsable values
+            ColumnType::Int => s
+                .trim()
+                .parse::<i64>()
+                .map(CellValue::Int)
+                .unwrap_or_else(|_| text()),
+            ColumnType::Float => s
+                .trim()
+                .parse::<f64>()
+                .map(CellValue::Float)
+                .unwrap_or_else(|_| text()),
+            ColumnType::Date => self.parse_date(s).unwrap_or_else(text),
+            ColumnType::Bool => parse_bool(s).map(CellValue::Bool).unwrap_or_else(text),
+            ColumnType::Auto => {
+                if self.inference == TypeInference::Conservative && looks_like_identifier(s) {
+                    text()
+                } else {
+                    self.infer(s)
+                }
+            }
+        }
+    }
+
+    fn parse_date(&self, s: &str) -> Option<CellValue> {
+        self.dates.parse(s).and_then(CellValue::from_naive_datetime)
+    }
+
+    /// Tries integer, float, boolean and date in turn, otherwise returns the text
+    fn infer(&self, s: &str) -> CellValue {
+        if let Ok(i) = s.parse::<i64>() {
+            return CellValue::Int(i);
+        }
+        if let Ok(f) = s.parse::<f64>() {
+            return CellValue::Float(f);
+        }
+        let trimmed = s.trim();
+        if trimmed.eq_ignore_ascii_case("true") {
+            return CellValue::Bool(true);
+        }
+        if trimmed.eq_ignore_ascii_case("false") {
+            return CellValue::Bool(false);
+        }
+        self.parse_date(s)
+            .unwrap_or_else(|| CellValue::String(s.to_string()))
+    }
+}
+
+/// Reads the leading bytes used for encoding and delimiter detection
+fn read_sample(input: &mut impl Read) -> io::Result<Vec<u8>> {
+    let mut sample = Vec::new();
+    input.take(SNIFF_SAMPLE_BYTES).read_to_end(&mut sample)?;
+    Ok(sample)
+}
+
+/// The explicit delimiter, or one sniffed from the sample
+fn resolve_delimiter(sample: &[u8], options: &CsvOptions) -> u8 {
+    options
+        .delimiter
+        .unwrap_or_else(|| sniff_delimiter(sample, options.quote))
+}
+
+/// csv reader configured for the dialect in `options`
+fn reader_builder(delimiter: u8, options: &CsvOptions) -> csv::ReaderBuilder {
+    let mut builder = csv::ReaderBuilder::new();
+    builder.delimiter(delimiter).quote(options.quote);
+    if let Some(escape) = options.escape {
+        builder.escape(Some(escape)).double_quote(false);
+    }
+    builder
+}
+
+/// Consumes the first `lines` lines of `input`, ended by `\n`, `\r\n` or a
+/// lone `\r`, and returns the number of bytes skipped
+fn skip_lines(input: &mut impl BufRead, lines: usize) -> io::Result<u64> {
+    let mut skipped = 0;
+    for _ in 0..lines {
+        loop {
+            let buf = input.fill_buf()?;
+            if buf.is_empty() {
+                return Ok(skipped);
+            }
+            let Some(end) = memchr::memchr2(b'\n', b'\r', buf) else {
+                let len = buf.len();
+                input.consume(len);
+                skipped += len as u64;
+                continue;
+            };
+            let cr = buf[end] == b'\r';
+            input.consume(end + 1);
+            skipped += end as u64 + 1;
+            if cr && input.fill_buf()?.first() == Some(&b'\n') {
+                input.consume(1);
+                skipped += 1;
+            }
+            break;
+        }
+    }
+    Ok(skipped)
+}
+
+/// Byte offsets of record starts, one checkpoint every `ROW_INDEX_STRIDE` records
+#[derive(Debug, Default)]
+struct RowIndex {
+    checkpoints: Vec<u64>,
+    records: usize,
+}
+
+impl RowIndex {
+    /// Scans `input`, which begins at byte `start` of the file, for record boundaries
+    /// without parsing fields. Newlines inside quoted fields don't end a record, and
+    /// blank lines are skipped like the csv reader does. As in the csv reader, only a
+    /// quote at the start of a field opens a quoted field; elsewhere it is literal.
+    fn build(
+        mut input: impl Read,
+        start: u64,
+        delimiter: u8,
+        quote: u8,
+        escape: Option<u8>,
+    ) -> io::Result<Self> {
+        let mut index = Self::default();
+        let mut buf = vec![0u8; INDEX_BUFFER_BYTES];
+        let mut offset = start;
+        let mut record_start = start;
+        let mut in_quotes = false;
+        let mut escaped_at = None;
+        // Quotes are doubled inside quoted fields unless there is an escape character
+        let double_quote = escape.is_none();
+        let mut closed_at = None;
+        let mut prev = b'\n';
+
+        loop {
+            let n = match input.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            let chunk = &buf[..n];
+            // `\r`, `\n` and `\r\n` all end a record, as in the csv reader
+            let hits: Box<dyn Iterator<Item = usize>> = match escape {
+                Some(escape) if escape != quote => Box::new(
+                    chunk
+                        .iter()
+                        .enumerate()
+                        .filter(move |&(_, &b)| {
+                            matches!(b, b'\n' | b'\r') || b == quote || b == escape
+                        })
+                        .map(|(i, _)| i),
+                ),
+                _ => Box::new(memchr::memchr3_iter(b'\n', b'\r', quote, chunk)),
+            };
+            for i in hits {
+                let pos = offset + i as u64;
+                if escaped_at == Some(pos) {
+                    continue;
+                }
+                let b = chunk[i];
+                let before = if i > 0 { chunk[i - 1] } else { prev };
+                if in_quotes && Some(b) == escape && b != quote {
+                    escaped_at = Some(pos + 1);
+                } else if b == quote {
+                    if in_quotes {
+                        in_quotes = false;
+                        closed_at = Some(pos);
+                    } else if double_quote && closed_at.map(|at| at + 1) == Some(pos) {
+                        // `""` inside a quoted field
+                        in_quotes = true;
+                    } else {
+                        in_quotes = matches!(before, b'\n' | b'\r') || before == delimiter;
+                    }
+                } else if matches!(b, b'\n' | b'\r') && !in_quotes {
+                    index.push_record(record_start, pos);
+                    record_start = pos + 1;
+                }
+            }
+            prev = chunk[n - 1];
+            offset += n as u64;
+        }
+        // Final record without a trailing newline
+        index.push_record(record_start, offset);
+
+        Ok(index)
+    }
+
+    /// Records the span `start..end` unless it is a blank line (or the `\n` of a `\r\n`)
+    fn push_record(&mut self, start: u64, end: u64) {
+        if start == end {
+            return;
+        }
+        if self.records.is_multiple_of(ROW_INDEX_STRIDE) {
+            self.checkpoints.push(start);
+        }
+        self.records += 1;
+    }
+}
+
+/// A delimited file on disk whose rows are parsed on demand. Only a sparse index
+/// of record offsets is kept in memory, so opening cost is one fast scan of the file.
+pub struct IndexedCsv {
+    name: String,
+    path: PathBuf,
+    delimiter: u8,
+    encoding: &'static Encoding,
+    options: CsvOptions,
+    parser: FieldParser,
+    column_types: Vec<ColumnType>,
+    index: RowIndex,
+    /// Record number of the first data row
+    data_start: usize,
+    /// Line of the file (zero-based) of the first data row
+    first_row: usize,
+    pub headers: Vec<String>,
+}
+
+impl IndexedCsv {
+    pub fn open(path: &Path, name: &str, options: &CsvOptions) -> Result<Self> {
+        let mut file = File::open(path)?;
+        let sample = read_sample(&mut file)?;
+        let (encoding, bom_len) = detect_encoding(&sample, options.encoding);
+        if !encoding.is_ascii_compatible() {
+            bail!(
+                "{} files can't be indexed; load them in memory",
+                encoding.name()
+            );
+        }
+        let delimiter = resolve_delimiter(&sample[bom_len..], options);
+        file.seek(SeekFrom::Start(bom_len as u64))?;
+        // Skipped title lines are left out of the index altogether
+        let mut file = BufReader::new(file);
+        let skipped = skip_lines(&mut file, options.header.skip_rows)?;
+        let index = RowIndex::build(
+            file,
+            bom_len as u64 + skipped,
+            delimiter,
+            options.quote,
+            options.escape,
+        )?;
+
+        let mut csv = Self {
+            name: name.to_string(),
+            path: path.to_path_buf(),
+            delimiter,
+            encoding,
+            options: options.clone(),
+            parser: FieldParser::new(options),
+            column_types: Vec::new(),
+            index,
+            data_start: 0,
+            first_row: 0,
+            headers: Vec::new(),
+        };
+        let header = options.header;
+        let header_rows = header.header_rows.min(csv.index.records);
+        csv.data_start = header_rows;
+        csv.first_row = header.skip_rows + header_rows;
+        if header.has_header() {
+            let header_records = csv.read_records(0, header_rows)?;
+            csv.headers = flatten_header_rows(
+                header_records
+                    .iter()
+                    .map(|record| {
+                        record
+                            .iter()
+                            .map(|field| decode_field(encoding, field).into_owned())
+                            .collect()
+                    })
+                    .collect(),
+            );
+        } else {
+            // Without a header the width comes from the first rows; longer rows are cut
+            let width = csv
+                .read_records(csv.data_start, ROW_INDEX_STRIDE)?
+                .iter()
+                .map(csv::ByteRecord::len)
+                .max()
+                .unwrap_or(0);
+            csv.headers = column_letter_headers(0, width);
+        }
+        csv.column_types = csv.headers.iter().map(|h| options.column_type(h)).collect();
+
+        Ok(csv)
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn width(&self) -> usize {
+        self.headers.len()
+    }
+
+    /// Number of data rows (header and skipped rows excluded)
+    pub fn height(&self) -> usize {
+        self.index.records.saturating_sub(self.data_start)
+    }
+
+    /// Zero-indexed data rows; rows are padded or cut to the header width
+    pub fn read_rows(&self, start: usize, count: usize) -> Result<Vec<Vec<CellValue>>> {
+        let count = count.min(self.height().saturating_sub(start));
+        let records = self.read_records(start + self.data_start, count)?;
+        Ok(records
+            .iter()
+            .map(|record| {
+                let mut row: Vec<CellValue> = record
+                    .iter()
+                    .zip(&self.column_types)
+                    .map(|(field, column_type)| {
+                        self.parser
+                            .parse(&decode_field(self.encoding, field), *column_type)
+                    })
+                    .collect();
+                row.resize(self.width(), CellValue::Empty);
+                row
+            })
+            .collect())
+    }
+
+    /// Line of the file (0 is the first) of the first data row
+    pub fn first_row(&self) -> usize {
+        self.first_row
+    }
+
+    /// Parses every row into memory
+    pub fn to_sheet_data(&self) -> Result<SheetData> {
+        self.head(self.height())
+    }
+
+    /// Parses the first `count` rows into memory
+    pub fn head(&self, count: usize) -> Result<SheetData> {
+        let rows = self.read_rows(0, count)?;
+        let (width, height) = (self.width(), rows.len());
+        Ok(SheetData {
+            headers: self.headers.clone(),
+            rows,
+            formulas: vec![vec![None; width]; height],
+            merges: Vec::new(),
+            formats: CellFormats::default(),
+            origin: (self.first_row, 0),
+            width,
+            height,
+        })
+    }
+
+    /// Reads `count` raw records starting at record `first` (0 is the first indexed record)
+    fn read_records(&self, first: usize, count: usize) -> Result<Vec<csv::ByteRecord>> {
+        let Some(&checkpoint) = self.index.checkpoints.get(first / ROW_INDEX_STRIDE) else {
+            return Ok(Vec::new());
+        };
+        let mut file = File::open(&self.path)?;
+        file.seek(SeekFrom::Start(checkpoint))?;
+        let mut reader = reader_builder(self.delimiter, &self.options)
+            .has_headers(false)
+            .flexible(true)
+            .from_reader(file);
+
+        let mut record = csv::ByteRecord::new();
+        for _ in 0..first % ROW_INDEX_STRIDE {
+            if !reader.read_byte_record(&mut record)? {
+                return Ok(Vec::new());
+            }
+        }
+        let mut records = Vec::with_capacity(count);
+        while records.len() < count && reader.read_byte_record(&mut record)? {
+            records.push(record.clone());
+        }
+        Ok(records)
+    }
+}
+
+/// Loads CSV records from any reader into a CsvData object.
+pub fn load_csv_data(mut input: impl Read, name: &str, options: &CsvOptions) -> Result<CsvData> {
+    // Read a sample up front for detection, then replay it before the rest
+    let mut sample = read_sample(&mut input)?;
+    let (encoding, bom_len) = detect_encoding(&sample, options.encoding);
+    sample.drain(..bom_len);
+    if encoding != UTF_8 {
+        // Transcode everything up front so the parser below only sees UTF-8
+        input.read_to_end(&mut sample)?;
+        let decoded = decode_field(encoding, &sample);
+        let utf8 = CsvOptions {
+            encoding: Some(UTF_8),
+            ..options.clone()
+        };
+        return load_csv_data(decoded.as_bytes(), name, &utf8);
+    }
+
+    let delimiter = resolve_delimiter(&sample, options);
+    let header = options.header;
+    let ragged = header.skip_rows > 0 || header.header_rows != 1;
+    // `skip_rows` counts lines of the file, blank ones included
+    let mut input = BufReader::new(Cursor::new(sample).chain(input));
+    skip_lines(&mut input, header.skip_rows)?;
+    let mut reader = reader_builder(delimiter, options)
+        .has_headers(false)
+        // Title rows and parent header rows rarely have as many fields as the table below
+        .flexible(ragged)
+        .from_reader(input);
+    let mut records = reader.byte_records();
+
+    let mut header_records = Vec::with_capacity(header.header_rows);
+    for record in records.by_ref().take(header.header_rows) {
+        header_records.push(
+            record?
+                .iter()
+                .map(|field| decode_field(UTF_8, field).into_owned())
+                .collect(),
+        );
+    }
+    let mut headers = flatten_header_rows(header_records);
+    let mut column_types: Vec<ColumnType> =
+        headers.iter().map(|h| options.column_type(h)).collect();
+    let parser = FieldParser::new(options);
+
+    let mut rows = Vec::new();
+    for result in records {
+        let record = result?;
+        if !header.has_header() {
+            // Columns are named by letter, so types given as e.g. `B=string` still apply
+            while column_types.len() < record.len() {
+                column_types.push(options.column_type(&column_letter(column_types.len())));
+            }
+        }
+        let row: Vec<CellValue> = record
+            .iter()
+            .enumerate()
+            .map(|(col, field)| {
+                let column_type = column_types.get(col).copied().unwrap_or(ColumnType::Auto);
+                parser.parse(&decode_field(UTF_8, field), column_type)
+            })
+            .collect();
+        rows.push(row);
+    }
+
+    if !header.has_header() {
+        headers = column_letter_headers(0, column_types.len());
+    }
+    let width = headers.len();
+    if ragged {
+        for row in &mut rows {
+            row.resize(width, CellValue::Empty);
+        }
+    }
+    let height = rows.len();
+
+    let sheet_data = SheetData {
+        headers,
+        rows,
+        formulas: vec![vec![None; width]; height], // CSVs don't have formulas
+        merges: Vec::new(),
+        formats: CellFormats::default(),
+        origin: (header.skip_rows + header.header_rows, 0),
+        width,
+        height,
+    };
+
+    Ok(CsvData {
+        name: name.to_string(),
+        data: sheet_data,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sniff_comma() {
+        assert_eq!(sniff_delimiter(b"a,b,c\n1,2,3\n4,5,6\n", b'"'), b',');
+    }
+
+    #[test]
+    fn test_sniff_semicolon_with_decimal_commas() {
+        let sample = "name;amount;city\nAlice;1,50;Berlin\nBob;2,75;Paris\n";
+        assert_eq!(sniff_delimiter(sample.as_bytes(), b'"'), b';');
+    }
+
+    #[test]
+    fn test_sniff_tab_and_pipe() {
+        assert_eq!(sniff_delimiter(b"a\tb\n1\t2\n", b'"'), b'\t');
+        assert_eq!(sniff_delimiter(b"a|b|c\n1|2|3\n", b'"'), b'|');
+    }
+
+    #[test]
+    fn test_sniff_ignores_quoted_delimiters() {
+        let sample = "id;note\n1;\"a, b, c\"\n2;\"d, e\"\n";
+        assert_eq!(sniff_delimiter(sample.as_bytes(), b'"'), b';');
+    }
+
+    #[test]
+    fn test_sniff_single_column_defaults_to_comma() {
+        assert_eq!(sniff_delimiter(b"value\n1\n2\n", b'"'), b',');
+        assert_eq!(sniff_delimiter(b"", b'"'), b',');
+    }
+
+    #[test]
+    fn test_parse_char_arg() {
+        assert_eq!(parse_char_arg("tab"), Ok(b'\t'));
+        assert_eq!(parse_char_arg("\\t"), Ok(b'\t'));
+        assert_eq!(parse_char_arg(";"), Ok(b';'));
+        assert_eq!(parse_char_arg("pipe"), Ok(b'|'));
+        assert!(parse_char_arg("ab").is_err());
+        assert!(parse_char_arg("").is_err());
+    }
+
+    #[test]
+    fn test_extension_handling() {
+        assert!(is_delimited_extension("TSV"));
+        assert!(is_delimited_extension("txt"));
+        assert!(!is_delimited_extension("xlsx"));
+        assert_eq!(extension_delimiter("tsv"), Some(b'\t'));
+        assert_eq!(extension_delimiter("psv"), Some(b'|'));
+        assert_eq!(extension_delimiter("csv"), None);
+    }
+
+    #[test]
+    fn test_load_semicolon_csv() {
+        let input = "name;amount\nAlice;10\nBob;20\n";
+        let csv = load_csv_data(input.as_bytes(), "data", &CsvOptions::default()).unwrap();
+        assert_eq!(csv.data.headers, vec!["name", "amount"]);
+        assert_eq!(csv.data.width, 2);
+        assert!(matches!(csv.data.rows[1][1], CellValue::Int(20)));
+    }
+
+    #[test]
+    fn test_load_with_escape_and_quote_overrides() {
+        let input = "a,b\n'x\\'y',2\n";
+        let options = CsvOptions {
+            delimiter: Some(b','),
+            quote: b'\'',
+            escape: Some(b'\\'),
+            ..CsvOptions::default()
+        };
+        let csv = load_csv_data(input.as_bytes(), "data", &options).unwrap();
+        assert!(matches!(&csv.data.rows[0][0], CellValue::String(s) if s == "x'y"));
+    }
+
+    #[test]
+    fn test_standard_inference_strips_leading_zeros() {
+        let csv = load_csv_data("zip\n02134\n".as_bytes(), "d", &CsvOptions::default()).unwrap();
+        assert!(matches!(csv.data.rows[0][0], CellValue::Int(2134)));
+    }
+
+    #[test]
+    fn test_conservative_inference_keeps_identifiers() {
+        let options = CsvOptions {
+            inference: TypeInference::Conservative,
+            ..CsvOptions::default()
+        };
+        let input = "zip,id,amount,zero\n02134,12345678901234567890,0.50,0\n";
+        let csv = load_csv_data(input.as_bytes(), "d", &options).unwrap();
+        let row = &csv.data.rows[0];
+        assert!(matches!(&row[0], CellValue::String(s) if s == "02134"));
+        assert!(matches!(&row[1], CellValue::String(s) if s == "12345678901234567890"));
+        assert!(matches!(row[2], CellValue::Float(f) if f == 0.5));
+        assert!(matches!(row[3], CellValue::Int(0)));
+    }
+
+    #[test]
+    fn test_column_type_overrides() {
+        let mut options = CsvOptions::default();
+        for arg in ["Code=string", " amount=float", "qty=int"] {
+            let (column, typ) = parse_column_type_arg(arg).unwrap();
+            options.set_column_type(&column, typ);
+        }
+        let input = "code,amount,qty,other\n007,12,n/a,42\n";
+        let csv = load_csv_data(input.as_bytes(), "d", &options).unwrap();
+        let row = &csv.data.rows[0];
+        assert!(matches!(&row[0], CellValue::String(s) if s == "007"));
+        assert!(matches!(row[1], CellValue::Float(f) if f == 12.0));
+        assert!(matches!(&row[2], CellValue::String(s) if s == "n/a"));
+        assert!(matches!(row[3], CellValue::Int(42)));
+    }
+
+    #[test]
+    fn test_infers_iso_dates_and_booleans() {
+        let input = "day,at,flag,label\n2024-01-15,2024-01-15T08:30:00Z,TRUE,1/2/24\n";
+        let csv = load_csv_data(input.as_bytes(), "d", &CsvOptions::default()).unwrap();
+        let row = &csv.data.rows[0];
+        assert!(matches!(row[0], CellValue::DateTime(_)));
+        assert_eq!(row[0].to_raw_string(), "2024-01-15");
+        assert_eq!(row[1].to_raw_string(), "2024-01-15 08:30:00");
+        assert!(matches!(row[2], CellValue::Bool(true)));
+        // Two-digit years and day/month dates need an explicit date order
+        assert!(matches!(&row[3], CellValue::String(s) if s == "1/2/24"));
+    }
+
+    #[test]
+    fn test_date_order() {
+        let input = "d\n03/04/2024\n";
+        let parse = |date_order| {
+            let options = CsvOptions {
+                date_order,
+                ..CsvOptions::default()
+            };
+            let csv = load_csv_data(input.as_bytes(), "d", &options).unwrap();
+            csv.data.rows[0][0].to_raw_string()
+        };
+        assert_eq!(parse(DateOrder::Ymd), "03/04/2024");
+        assert_eq!(parse(DateOrder::Dmy), "2024-04-03");
+        assert_eq!(parse(DateOrder::Mdy), "2024-03-04");
+    }
+
+    #[test]
+    fn test_custom_date_format_and_typed_columns() {
+        let mut options = CsvOptions {
+            date_formats: vec!["%d %b %Y".to_string()],
+            ..CsvOptions::default()
+        };
+        options.set_column_type("active", ColumnType::Bool);
+        options.set_column_type("since", ColumnType::Date);
+        let input = "when,active,since\n5 Mar 2024,yes,soon\n";
+        let csv = load_csv_data(input.as_bytes(), "d", &options).unwrap();
+        let row = &csv.data.rows[0];
+        assert_eq!(row[0].to_raw_string(), "2024-03-05");
+        assert!(matches!(row[1], CellValue::Bool(true)));
+        assert!(matches!(&row[2], CellValue::String(s) if s == "soon"));
+    }
+
+    #[test]
+    fn test_row_index_boundaries() {
+        let input = "a,b\r\n\r\n1,\"multi\nline\"\n\n2,\"x\"\"\n\"\n3,4";
+        let index = RowIndex::build(input.as_bytes(), 0, b',', b'"', None).unwrap();
+        assert_eq!(index.records, 4);
+        assert_eq!(index.checkpoints, vec![0]);
+
+        // Old Mac line endings
+        let cr_only = "a,b\r1,\"x\ry\"\r\r2,3";
+        let index = RowIndex::build(cr_only.as_bytes(), 0, b',', b'"', None).unwrap();
+        assert_eq!(index.records, 3);
+
+        let escaped = "a\n\"x\\\"\ny\"\nz\n";
+        let index = RowIndex::build(escaped.as_bytes(), 0, b',', b'"', Some(b'\\')).unwrap();
+        assert_eq!(index.records, 3);
+    }
+
+    #[test]
+    fn test_indexed_csv_matches_eager_load() {
+        let mut input = String::from("id;name;note\n");
+        for i in 0..1000 {
+            input.push_str(&format!("{i};row {i};\"line\n{i}\"\n"));
+        }
+        let path = std::env::temp_dir().join(format!("xleak-indexed-{}.csv", std::process::id()));
+        std::fs::write(&path, &input).unwrap();
+        let indexed = IndexedCsv::open(&path, "t", &CsvOptions::default()).unwrap();
+        let eager = load_csv_data(input.as_bytes(), "t", &CsvOptions::default()).unwrap();
+
+        assert_eq!(indexed.headers, eager.data.headers);
+        assert_eq!(indexed.height(), 1000);
+        for start in [0, 127, 128, 500, 995] {
+            let rows = indexed.read_rows(start, 10).unwrap();
+            assert_eq!(rows.len(), 10.min(1000 - start));
+            for (row, expected) in rows.iter().zip(&eager.data.rows[start..]) {
+                let row: Vec<String> = row.iter().map(CellValue::to_raw_string).collect();
+                let expected: Vec<String> = expected.iter().map(CellValue::to_raw_string).collect();
+                assert_eq!(row, expected);
+            }
+        }
+        assert!(indexed.read_rows(1000, 5).unwrap().is_empty());
+        let head = indexed.head(5).unwrap();
+        assert_eq!(
+            (head.height, head.rows[4][0].to_raw_string()),
+            (5, "4".into())
+        );
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_indexed_csv_with_cr_line_endings() {
+        let mut input = String::from("id,note\r");
+        for i in 0..300 {
+            input.push_str(&format!("{i},\"a\rb {i}\"\r"));
+        }
+        let path = std::env::temp_dir().join(format!("xleak-cr-{}.csv", std::process::id()));
+        std::fs::write(&path, &input).unwrap();
+        let indexed = IndexedCsv::open(&path, "t", &CsvOptions::default()).unwrap();
+        assert_eq!(indexed.height(), 300);
+        let rows = indexed.read_rows(257, 2).unwrap();
+        assert_eq!(rows[0][0].to_raw_string(), "257");
+        assert_eq!(rows[1][1].to_raw_string(), "a\rb 258");
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_indexed_csv_keeps_stray_quotes_literal() {
+        // A quote inside an unquoted field doesn't start a quoted section
+        let input = "id,desc,qty\n1,12\" pipe,3\n2,nut,4\n3,\"bolt \"\"M8\"\"\",5\n";
+        let path = std::env::temp_dir().join(format!("xleak-stray-{}.csv", std::process::id()));
+        std::fs::write(&path, input).unwrap();
+        let indexed = IndexedCsv::open(&path, "t", &CsvOptions::default()).unwrap();
+        let eager = load_csv_data(input.as_bytes(), "t", &CsvOptions::default()).unwrap();
+
+        assert_eq!(indexed.height(), 3);
+        let rows: Vec<Vec<String>> = indexed
+            .read_rows(0, 3)
+            .unwrap()
+            .iter()
+            .map(|row| row.iter().map(CellValue::to_raw_string).collect())
+            .collect();
+        let expected: Vec<Vec<String>> = eager
+            .data
+            .rows
+            .iter()
+            .map(|row| row.iter().map(CellValue::to_raw_string).collect())
+            .collect();
+        assert_eq!(rows, expected);
+        assert_eq!(rows[0][1], "12\" pipe");
+        assert_eq!(rows[2][1], "bolt \"M8\"");
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_detect_encoding() {
+        assert_eq!(detect_encoding(b"\xEF\xBB\xBFa,b\n", None), (UTF_8, 3));
+        assert_eq!(detect_encoding(b"\xFF\xFEa\0,\0", None), (UTF_16LE, 2));
+        assert_eq!(detect_encoding(b"a\0,\0b\0\n\0", None), (UTF_16LE, 0));
+        assert_eq!(detect_encoding("Größe,ışık".as_bytes(), None), (UTF_8, 0));
+        let latin = b"Language,Text\nGerman,Gr\xF6\xDFe\nFrench,Caf\xE9 r\xE9sum\xE9\n";
+        assert_eq!(detect_encoding(latin, None).0, encoding_rs::WINDOWS_1252);
+        let forced = parse_encoding_arg("latin1").unwrap();
+        assert_eq!(detect_encoding(b"\xEF\xBB\xBFa", Some(forced)), (forced, 0));
+        assert!(parse_encoding_arg("klingon").is_err());
+    }
+
+    #[test]
+    fn test_load_transcodes_to_utf8() {
+        let expected = ["Language", "Text"];
+        let utf8_bom = "\u{FEFF}Language,Text\nGerman,Größe\n".as_bytes().to_vec();
+        let latin = b"Language,Text\nGerman,Gr\xF6\xDFe\n".to_vec();
+        let utf16: Vec<u8> = [0xFF, 0xFE]
+            .into_iter()
+            .chain(
+                "Language,Text\r\nGerman,Größe\r\n"
+                    .encode_utf16()
+                    .flat_map(u16::to_le_bytes),
+            )
+            .collect();
+        for input in [utf8_bom, latin, utf16] {
+            let csv = load_csv_data(input.as_slice(), "d", &CsvOptions::default()).unwrap();
+            assert_eq!(csv.data.headers, expected);
+            assert!(matches!(&csv.data.rows[0][1], CellValue::String(s) if s == "Größe"));
+        }
+    }
+
+    #[test]
+    fn test_indexed_csv_decodes_fields() {
+        let path = std::env::temp_dir().join(format!("xleak-latin-{}.csv", std::process::id()));
+        std::fs::write(&path, b"\xEF\xBB\xBFid,text\n1,Caf\xC3\xA9\n").unwrap();
+        assert!(supports_indexing(&path, &CsvOptions::default()).unwrap());
+        let csv = IndexedCsv::open(&path, "t", &CsvOptions::default()).unwrap();
+        assert_eq!(csv.headers, vec!["id", "text"]);
+        assert_eq!(csv.read_rows(0, 1).unwrap()[0][1].to_raw_string(), "Café");
+
+        std::fs::write(&path, b"id,text\n1,Caf\xE9\n").unwrap();
+        let options = CsvOptions {
+            encoding: parse_encoding_arg("windows-1252").ok(),
+            ..CsvOptions::default()
+        };
+        let csv = IndexedCsv::open(&path, "t", &options).unwrap();
+        assert_eq!(csv.read_rows(0, 1).unwrap()[0][1].to_raw_string(), "Café");
+
+        std::fs::write(&path, b"\xFF\xFEi\0d\0").unwrap();
+        assert!(!supports_indexing(&path, &CsvOptions::default()).unwrap());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_header_row_selection_indexed_and_eager() {
+        let input = "Quarterly report\nExported 2024-01-15,,\n\nid,amount\n1,9.5\n2,7\n";
+        let path = std::env::temp_dir().join(format!("xleak-title-{}.csv", std::process::id()));
+        std::fs::write(&path, input).unwrap();
+
+        // Rows are lines of the file, so the blank line counts towards `skip_rows`
+        let options = CsvOptions {
+            header: HeaderOptions {
+                skip_rows: 3,
+                header_rows: 1,
+            },
+            ..CsvOptions::default()
+        };
+        let indexed = IndexedCsv::open(&path, "t", &options).unwrap();
+        let eager = load_csv_data(input.as_bytes(), "t", &options).unwrap().data;
+        for (headers, rows) in [
+            (&indexed.headers, indexed.to_sheet_data().unwrap().rows),
+            (&eager.headers, eager.rows.clone()),
+        ] {
+            assert_eq!(headers, &vec!["id", "amount"]);
+            assert_eq!(rows.len(), 2);
+            assert!(matches!(rows[0][1], CellValue::Float(f) if f == 9.5));
+        }
+        assert_eq!(indexed.first_row(), 4);
+        assert_eq!(eager.origin, (4, 0));
+
+        let mut options = CsvOptions {
+            header: HeaderOptions {
+                skip_rows: 4,
+                header_rows: 0,
+            },
+            ..CsvOptions::default()
+        };
+        options.set_column_type("A", ColumnType::String);
+        let indexed = IndexedCsv::open(&path, "t", &options).unwrap();
+        let eager = load_csv_data(input.as_bytes(), "t", &options).unwrap().data;
+        assert_eq!(indexed.headers, vec!["A", "B"]);
+        assert_eq!(eager.headers, indexed.headers);
+        assert_eq!(indexed.height(), 2);
+        assert_eq!(eager.height, 2);
+        assert!(
+            matches!(&indexed.read_rows(0, 1).unwrap()[0][0], CellValue::String(s) if s == "1")
+        );
+        assert!(matches!(&eager.rows[1][0], CellValue::String(s) if s == "2"));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_header_row_counts_blank_lines() {
+        let input = "Title\n\nRegion,2024,2025\n,Q1,Q1\nNorth,1,2\nSouth,3,4\n";
+        let path = std::env::temp_dir().join(format!("xleak-blank-{}.csv", std::process::id()));
+        std::fs::write(&path, input).unwrap();
+        // `--header-row 3 --header-rows 2`
+        let options = CsvOptions {
+            header: HeaderOptions {
+                skip_rows: 2,
+                header_rows: 2,
+            },
+            ..CsvOptions::default()
+        };
+        let indexed = IndexedCsv::open(&path, "t", &options).unwrap();
+        let eager = load_csv_data(input.as_bytes(), "t", &options).unwrap().data;
+        let expected = vec!["Region", "2024 / Q1", "2025 / Q1"];
+        assert_eq!(indexed.headers, expected);
+        assert_eq!(eager.headers, expected);
+        assert_eq!(indexed.height(), 2);
+        assert_eq!(eager.height, 2);
+        assert!(
+            matches!(&indexed.read_rows(0, 1).unwrap()[0][0], CellValue::String(s) if s == "North")
+        );
+        assert!(matches!(eager.rows[1][2], CellValue::Int(4)));
+        assert_eq!(indexed.first_row(), 4);
+        assert_eq!(eager.origin, (4, 0));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_stacked_header_rows() {
+        let input = "Region,2024,,2025\n,Q1,Q2,Q1\nNorth,1,2,3\n";
+        let path = std::env::temp_dir().join(format!("xleak-stacked-{}.csv", std::process::id()));
+        std::fs::write(&path, input).unwrap();
+        let options = CsvOptions {
+            header: HeaderOptions {
+                skip_rows: 0,
+                header_rows: 2,
+            },
+            ..CsvOptions::default()
+        };
+        let expected = vec!["Region", "2024 / Q1", "2024 / Q2", "2025 / Q1"];
+        let indexed = IndexedCsv::open(&path, "t", &options).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let eager = load_csv_data(input.as_bytes(), "t", &options).unwrap().data;
+        assert_eq!(indexed.headers, expected);
+        assert_eq!(eager.headers, expected);
+        assert_eq!(indexed.height(), 1);
+        assert!(matches!(eager.rows[0][3], CellValue::Int(3)));
+    }
+
+    #[test]
+    fn test_parse_column_type_arg_errors() {
+        assert!(parse_column_type_arg("zip").is_err());
+        assert!(parse_column_type_arg("zip=banana").is_err());
+        assert_eq!(
+            parse_column_type_arg("a=b=TEXT"),
+            Ok(("a=b".to_string(), ColumnType::String))
+        );
+    }
+}
@@ -1,6 +1,12 @@
+use crate::hyperlinks::{self, Hyperlink, LinkTarget};
+use crate::regions::ShownRegion;
 use crate::workbook::{CellValue, SheetData};
 use anyhow::Result;
+use prettytable::format::Alignment;
 use prettytable::{Cell, Row, Table, format};
+use std::collections::HashMap;
+use std::io::IsTerminal;
+use unicode_width::UnicodeWidthStr;
 
 /// Format a cell value with width limiting
 fn format_cell_value(value: &str, max_width: usize, wrap: bool) -> String {
@@ -31,14 +37,18 @@ fn format_cell_value(value: &str, max_width: usize, wrap: bool) -> String {
 }
 
 /// Display sheet data as a formatted table in the terminal
+#[allow(clippy::too_many_arguments)]
 pub fn display_table(
     data: &SheetData,
+    total_rows: usize,
     sheet_name: &str,
     max_rows: usize,
     all_sheets: &[&str],
     max_width: usize,
     wrap: bool,
     show_formulas: bool,
+    links: &[Hyperlink],
+    region: Option<ShownRegion>,
 ) -> Result<()> {
     // Print header info
     println!("\n╔═════════════════════════════════════════════════╗");
@@ -47,8 +57,11 @@ pub fn display_table(
     println!();
     println!(
         "Sheet: {} ({} rows × {} columns)",
-        sheet_name, data.height, data.width
+        sheet_name, total_rows, data.width
     );
+    if let Some(region) = region {
+        println!("Note: {}", region.note());
+    }
 
     if all_sheets.len() > 1 {
         println!("Available sheets: {}", all_sheets.join(", "));
@@ -60,20 +73,12 @@ pub fn display_table(
         return Ok(());
     }
 
-    // Create table
-    let mut table = Table::new();
-    table.set_format(*format::consts::FORMAT_BOX_CHARS);
-
-    // Add headers (with width limiting)
-    let header_cells: Vec<Cell> = data
+    // Header text and style (with width limiting)
+    let header_cells: Vec<(String, &str)> = data
         .headers
         .iter()
-        .map(|h| {
-            let formatted = format_cell_value(h, max_width, wrap);
-            Cell::new(&formatted).style_spec("Fgbc")
-        })
+        .map(|h| (format_cell_value(h, max_width, wrap), HEADER_SPEC))
         .collect();
-    table.set_titles(Row::new(header_cells));
 
     // Add data rows (limit if needed)
     let rows_to_show = if max_rows == 0 {
@@ -82,66 +87,225 @@ pub fn display_table(
         std::cmp::min(max_rows, data.rows.len())
     };
 
-    for (row_idx, row) in data.rows.iter().enumerate().take(rows_to_show) {
-        let cells: Vec<Cell> = row
-            .iter()
-            .enumerate()
-            .map(|(col_idx, cell)| {
-                // Get formula if it exists and show_formulas is true
-                let value = if show_formulas {
-                    data.formulas
-                        .get(row_idx)
-                        .and_then(|formula_row| formula_row.get(col_idx))
-                        .and_then(|f| f.as_ref())
-                        .cloned()
-                        .unwrap_or_else(|| cell.to_string())
-                } else {
-                    cell.to_string()
-                };
-
-                let formatted = format_cell_value(&value, max_width, wrap);
-                let cell_obj = Cell::new(&formatted);
+    let body: Vec<Vec<(String, &str)>> = data
+        .rows
+        .iter()
+        .enumerate()
+        .take(rows_to_show)
+        .map(|(row_idx, row)| {
+            row.iter()
+                .enumerate()
+                .map(|(col_idx, cell)| {
+                    // Get formula if it exists and show_formulas is true
+                    let value = if show_formulas {
+                        data.formulas
+                            .get(row_idx)
+                            .and_then(|formula_row| formula_row.get(col_idx))
+                            .and_then(|f| f.as_ref())
+                            .cloned()
+                            .unwrap_or_else(|| cell.to_string())
+                    } else {
+                        cell.to_formatted_string(
+                            data.number_format(row_idx, col_idx),
+                            data.formats.date1904(),
+                        )
+                    };
+                    (
+                        format_cell_value(&value, max_width, wrap),
+                        cell_spec(cell, show_formulas),
+                    )
+                })
+                .collect()
+        })
+        .collect();
 
-                // Style based on type (only when not showing formulas)
-                if show_formulas {
-                    cell_obj.style_spec("Fg") // Green for formulas
-                } else {
-                    match cell {
-                        CellValue::Int(_) | CellValue::Float(_) => {
-                            cell_obj.style_spec("Fr") // Right-aligned numbers
-                        }
-                        CellValue::Bool(_) => {
-                            cell_obj.style_spec("Fc") // Centered booleans
-                        }
-                        CellValue::Error(_) => {
-                            cell_obj.style_spec("Frc") // Red errors, centered
-                        }
-                        _ => cell_obj,
-                    }
-                }
-            })
-            .collect();
-        table.add_row(Row::new(cells));
+    let urls = shown_urls(data, links, rows_to_show);
+    if urls.is_empty() || !std::io::stdout().is_terminal() {
+        let mut table = Table::new();
+        table.set_format(*format::consts::FORMAT_BOX_CHARS);
+        let row = |cells: &[(String, &str)]| {
+            Row::new(
+                cells
+                    .iter()
+                    .map(|(text, spec)| Cell::new(text).style_spec(spec))
+                    .collect(),
+            )
+        };
+        table.set_titles(row(&header_cells));
+        for cells in &body {
+            table.add_row(row(cells));
+        }
+        table.printstd();
+    } else {
+        print!("{}", render_linked_table(&header_cells, &body, &urls));
     }
 
-    table.printstd();
-
     // Show row count summary
     println!();
-    if rows_to_show < data.rows.len() {
+    if rows_to_show < total_rows {
         println!(
             "⚠️  Showing {} of {} rows (use -n 0 to show all)",
-            rows_to_show,
-            data.rows.len()
+            rows_to_show, total_rows
         );
     } else {
-        println!("Total: {} rows × {} columns", data.height, data.width);
+        println!("Total: {} rows × {} columns", total_rows, data.width);
     }
 
     println!();
     Ok(())
 }
 
+/// prettytable style spec of the header cells: green, bold and centred
+const HEADER_SPEC: &str = "Fgbc";
+
+/// prettytable style spec of a data cell
+fn cell_spec(cell: &CellValue, show_formulas: bool) -> &'static str {
+    // Style based on type (only when not showing formulas)
+    if show_formulas {
+        return "Fg"; // Green for formulas
+    }
+    match cell {
+        CellValue::Int(_) | CellValue::Float(_) => "Fr", // Right-aligned numbers
+        CellValue::Bool(_) => "Fc",                      // Centered booleans
+        CellValue::Error(_) => "Frc",                    // Red errors, centered
+        _ => "",
+    }
+}
+
+/// External links of the rows shown, by data position
+fn shown_urls<'a>(
+    data: &SheetData,
+    links: &'a [Hyperlink],
+    rows_to_show: usize,
+) -> HashMap<(usize, usize), &'a str> {
+    let mut urls = HashMap::new();
+    let Some((first, last)) = data.data_extent() else {
+        return urls;
+    };
+    let Some(last_shown) = (rows_to_show as u32).checked_sub(1) else {
+        return urls;
+    };
+    let last_shown = (first.0 + last_shown, last.1);
+    for link in links {
+        let (Some(url), Some(shown)) = (link.url(), link.clamp(first, last_shown)) else {
+            continue;
+        };
+        for row in shown.first.0..=shown.last.0 {
+            for col in shown.first.1..=shown.last.1 {
+                if let Some(position) = data.data_position(row, col) {
+                    urls.insert(position, url);
+                }
+            }
+        }
+    }
+    urls
+}
+
+/// Draws the table as prettytable's box format would, with the text of linked
+/// cells wrapped in OSC 8 escapes. prettytable counts the escapes as visible
+/// text, so the padding is worked out here from the text alone.
+fn render_linked_table(
+    header: &[(String, &str)],
+    body: &[Vec<(String, &str)>],
+    urls: &HashMap<(usize, usize), &str>,
+) -> String {
+    let columns = body
+        .iter()
+        .map(Vec::len)
+        .chain([header.len()])
+        .max()
+        .unwrap_or(0);
+    let mut widths = vec![0; columns];
+    for cells in std::iter::once(header).chain(body.iter().map(Vec::as_slice)) {
+        for (col, (text, _)) in cells.iter().enumerate() {
+            let width = text.lines().map(UnicodeWidthStr::width).max().unwrap_or(0);
+            widths[col] = widths[col].max(width);
+        }
+    }
+    let separator = |left: char, middle: &str, right: char| {
+        let lines: Vec<String> = widths.iter().map(|w| "─".repeat(w + 2)).collect();
+        format!("{left}{}{right}\n", lines.join(middle))
+    };
+
+    let mut out = separator('┌', "┬", '┐');
+    out.push_str(&render_row(header, &widths, |_| None));
+    for (row, cells) in body.iter().enumerate() {
+        out.push_str(&separator('├', "┼", '┤'));
+        out.push_str(&render_row(cells, &widths, |col| {
+            urls.get(&(row, col)).copied()
+        }));
+    }
+    out.push_str(&separator('└', "┴", '┘'));
+    out
+}
+
+/// The lines of one table row; cells with line breaks make it several lines high
+fn render_row<'a>(
+    cells: &[(String, &str)],
+    widths: &[usize],
+    url: impl Fn(usize) -> Option<&'a str>,
+) -> String {
+    let height = cells
+        .iter()
+        .map(|(text, _)| text.lines().count())
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    let mut out = String::new();
+    for line in 0..height {
+        out.push('│');
+        for (col, width) in widths.iter().enumerate() {
+            let (text, spec) = cells
+                .get(col)
+                .map_or(("", ""), |(text, spec)| (text.as_str(), *spec));
+            let text = text.lines().nth(line).unwrap_or("");
+            let (sgr, align) = parse_spec(spec);
+            let fill = width.saturating_sub(UnicodeWidthStr::width(text));
+            let left = match align {
+                Alignment::LEFT => 0,
+                Alignment::RIGHT => fill,
+                Alignment::CENTER => fill / 2,
+            };
+            let linked = match url(col) {
+                Some(url) if !text.is_empty() => hyperlinks::terminal_link(url, text),
+                _ => text.to_string(),
+            };
+            let padded = format!("{}{linked}{}", " ".repeat(left), " ".repeat(fill - left));
+            if sgr.is_empty() {
+                out.push_str(&format!(" {padded} │"));
+            } else {
+                out.push_str(&format!(" \x1b[{sgr}m{padded}\x1b[0m │"));
+            }
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// SGR parameters and alignment of the prettytable style specs used above:
+/// `F` and a colour letter, `b` for bold, and `l`, `c` or `r` to align
+fn parse_spec(spec: &str) -> (String, Alignment) {
+    let mut params = Vec::new();
+    let mut align = Alignment::LEFT;
+    let mut chars = spec.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            'F' => {
+                let color = chars.next().and_then(|c| "drgybmcw".find(c));
+                if let Some(color) = color {
+                    params.push((30 + color).to_string());
+                }
+            }
+            'b' => params.push("1".to_string()),
+            'l' => align = Alignment::LEFT,
+            'c' => align = Alignment::CENTER,
+            'r' => align = Alignment::RIGHT,
+            _ => {}
+        }
+    }
+    (params.join(";"), align)
+}
+
 /// Export data as CSV to stdout
 pub fn export_csv(data: &SheetData) -> Result<()> {
     // Print headers
@@ -152,7 +316,7 @@ pub fn export_csv(data: &SheetData) -> Result<()> {
         let row_str: Vec<String> = row
             .iter()
             .map(|cell| {
-                let val = cell.to_string();
+                let val = cell.to_iso_string().unwrap_or_else(|| cell.to_string());
                 // Quote if contains comma or quotes
                 if val.contains(',') || val.contains('"') {
                     format!("\"{}\"", val.replace('"', "\"\""))
@@ -168,7 +332,7 @@ pub fn export_csv(data: &SheetData) -> Result<()> {
 }
 
 /// Export data as JSON to stdout
-pub fn export_json(data: &SheetData, sheet_name: &str) -> Result<()> {
+pub fn export_json(data: &SheetData, sheet_name: &str, links: &[Hyperlink]) -> Result<()> {
     println!("{{");
     println!("  \"sheet\": \"{sheet_name}\",");
     println!("  \"rows\": {},", data.height);
@@ -190,7 +354,10 @@ pub fn export_json(data: &SheetData, sheet_name: &str) -> Result<()> {
                 CellValue::Float(f) => f.to_string(),
                 CellValue::Bool(b) => b.to_string(),
                 CellValue::Empty => "null".to_string(),
-                _ => format!("\"{cell}\""),
+                _ => format!(
+                    "\"{}\"",
+                    cell.to_iso_string().unwrap_or_else(|| cell.to_string())
+                ),
             };
             print!("{value}");
             if j < row.len() - 1 {
@@ -201,7 +368,25 @@ pub fn export_json(data: &SheetData, sheet_name: &str) -> Result<()> {
         println!("]{comma}");
     }
 
-    println!("  ]");
+    if links.is_empty() {
+        println!("  ]");
+    } else {
+        println!("  ],");
+        println!("  \"hyperlinks\": [");
+        for (i, link) in links.iter().enumerate() {
+            let (kind, target) = match &link.target {
+                LinkTarget::Url(url) => ("url", url),
+                LinkTarget::Location(location) => ("location", location),
+            };
+            let comma = if i < links.len() - 1 { "," } else { "" };
+            println!(
+                "    {{\"cell\": \"{}\", \"{kind}\": \"{}\"}}{comma}",
+                link.address(),
+                target.replace('\\', "\\\\").replace('"', "\\\"")
+            );
+        }
+        println!("  ]");
+    }
     println!("}}");
 
     Ok(())
@@ -220,3 +405,86 @@ pub fn export_text(data: &SheetData) -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `text` without colour and link escapes
+    fn strip_escapes(text: &str) -> String {
+        let mut out = String::new();
+        let mut chars = text.chars().peekable();
+        while let Some(c) = chars.next() {
+            match (c, chars.peek()) {
+                ('\x1b', Some('[')) => {
+                    for c in chars.by_ref() {
+                        if c.is_ascii_alphabetic() {
+                            break;
+                        }
+                    }
+                }
+                // OSC, up to the `ESC \\` that ends it
+                ('\x1b', Some(']')) => {
+                    while let Some(c) = chars.next() {
+                        if c == '\x1b' && chars.next_if_eq(&'\\').is_some() {
+                            break;
+                        }
+                    }
+                }
+                _ => out.push(c),
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn test_render_linked_table() {
+        let header = vec![
+            ("Name".to_string(), HEADER_SPEC),
+            ("Total".to_string(), HEADER_SPEC),
+        ];
+        let body = vec![
+            vec![("Site │ one".to_string(), ""), ("12".to_string(), "Fr")],
+            vec![("two\nlines".to_string(), ""), ("true".to_string(), "Fc")],
+            vec![("".to_string(), ""), ("#REF!".to_string(), "Frc")],
+        ];
+        let urls = HashMap::from([
+            ((0, 0), "https://a.example"),
+            ((1, 0), "https://c.example"),
+            ((2, 0), "https://b.example"),
+        ]);
+        let rendered = render_linked_table(&header, &body, &urls);
+
+        // Laid out exactly as prettytable would, border characters in the text included
+        let mut table = Table::new();
+        table.set_format(*format::consts::FORMAT_BOX_CHARS);
+        let row = |cells: &[(String, &str)]| {
+            Row::new(
+                cells
+                    .iter()
+                    .map(|(text, spec)| Cell::new(text).style_spec(spec))
+                    .collect(),
+            )
+        };
+        table.set_titles(row(&header));
+        for cells in &body {
+            table.add_row(row(cells));
+        }
+        let mut plain = Vec::new();
+        table.print(&mut plain).unwrap();
+        assert_eq!(strip_escapes(&rendered), String::from_utf8(plain).unwrap());
+
+        // The link covers the text, not the padding; empty cells get none
+        assert!(rendered.contains(&format!(
+            "│ {} │ \x1b[31m12   \x1b[0m │",
+            hyperlinks::terminal_link("https://a.example", "Site │ one")
+        )));
+        assert!(rendered.contains(&format!(
+            "│ {}{} │",
+            hyperlinks::terminal_link("https://c.example", "two"),
+            " ".repeat(7)
+        )));
+        assert!(!rendered.contains("https://b.example"));
+        assert!(rendered.contains("\x1b[32;1m   Name   \x1b[0m"));
+    }
+}
@@ -0,0 +1,231 @@
+use crate::names;
+use crate::package;
+use crate::workbook::column_letter;
+use crate::xml::attribute;
+use anyhow::Result;
+use quick_xml::events::Event;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Seek};
+use zip::ZipArchive;
+
+/// Where a hyperlink points
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LinkTarget {
+    /// A web page, file or e-mail address
+    Url(String),
+    /// A place in the workbook: `Sheet2!B5`, `'My Sheet'!A1:C3`, a cell on the
+    /// same sheet or a defined name
+    Location(String),
+}
+
+impl std::fmt::Display for LinkTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LinkTarget::Url(url) | LinkTarget::Location(url) => f.write_str(url),
+        }
+    }
+}
+
+/// A hyperlink on a cell or a range of cells, at zero-based sheet positions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hyperlink {
+    /// Top-left cell
+    pub first: (u32, u32),
+    /// Bottom-right cell; the same as `first` for a single cell
+    pub last: (u32, u32),
+    pub target: LinkTarget,
+}
+
+impl Hyperlink {
+    /// A1-style address, e.g. `B4`, or `C2:C3` for a range
+    pub fn address(&self) -> String {
+        let cell = |(row, col): (u32, u32)| format!("{}{}", column_letter(col as usize), row + 1);
+        if self.first == self.last {
+            cell(self.first)
+        } else {
+            format!("{}:{}", cell(self.first), cell(self.last))
+        }
+    }
+
+    pub fn contains(&self, row: u32, col: u32) -> bool {
+        (self.first.0..=self.last.0).contains(&row) && (self.first.1..=self.last.1).contains(&col)
+    }
+
+    /// The part of the link within the cells `first` to `last`, if any
+    pub fn clamp(&self, first: (u32, u32), last: (u32, u32)) -> Option<Hyperlink> {
+        let top_left = (self.first.0.max(first.0), self.first.1.max(first.1));
+        let bottom_right = (self.last.0.min(last.0), self.last.1.min(last.1));
+        (top_left.0 <= bottom_right.0 && top_left.1 <= bottom_right.1).then(|| Hyperlink {
+            first: top_left,
+            last: bottom_right,
+            target: self.target.clone(),
+        })
+    }
+
+    pub fn url(&self) -> Option<&str> {
+        match &self.target {
+            LinkTarget::Url(url) => Some(url),
+            LinkTarget::Location(_) => None,
+        }
+    }
+}
+
+/// Hyperlinks of one sheet of an xlsx file, in sheet order
+pub fn read_xlsx_hyperlinks(reader: impl Read + Seek, sheet: &str) -> Result<Vec<Hyperlink>> {
+    let mut archive = ZipArchive::new(reader)?;
+    let Some(path) = package::sheet_paths(&mut archive)?.remove(sheet) else {
+        return Ok(Vec::new());
+    };
+    let urls: HashMap<String, String> = package::relationships(&mut archive, &path)?
+        .into_iter()
+        .filter(|(_, relationship)| relationship.kind == "hyperlink" && relationship.external)
+        .map(|(id, relationship)| (id, relationship.target))
+        .collect();
+    let mut links = parse_hyperlinks(BufReader::new(archive.by_name(&path)?), &urls)?;
+    links.sort_by_key(|link| link.first);
+    Ok(links)
+}
+
+/// The `hyperlink` elements of a worksheet. External targets are kept in the
+/// sheet's relationships, given here as `urls` by relationship id.
+fn parse_hyperlinks(sheet: impl BufRead, urls: &HashMap<String, String>) -> Result<Vec<Hyperlink>> {
+    let mut xml = quick_xml::Reader::from_reader(sheet);
+    let mut buf = Vec::new();
+    let mut links = Vec::new();
+
+    loop {
+        buf.clear();
+        match xml.read_event_into(&mut buf)? {
+            Event::Start(e) | Event::Empty(e) if e.local_name().as_ref() == b"hyperlink" => {
+                let url = attribute(&e, "r:id")?.and_then(|id| urls.get(&id).cloned());
+                let location = attribute(&e, "location")?.filter(|l| !l.is_empty());
+                let target = match (url, location) {
+                    // The location is then a bookmark within the linked page or file
+                    (Some(url), Some(location)) => LinkTarget::Url(format!("{url}#{location}")),
+                    (Some(url), None) => LinkTarget::Url(url),
+                    (None, Some(location)) => LinkTarget::Location(location),
+                    (None, None) => continue,
+                };
+                let Some(reference) = attribute(&e, "ref")? else {
+                    continue;
+                };
+                // One link may cover a range of cells, up to whole columns
+                let (first, last) = reference
+                    .split_once(':')
+                    .unwrap_or((&reference, &reference));
+                let (Some((Some(r1), Some(c1))), Some((Some(r2), Some(c2)))) =
+                    (names::parse_cell(first), names::parse_cell(last))
+                else {
+                    continue;
+                };
+                links.push(Hyperlink {
+                    first: (r1.min(r2), c1.min(c2)),
+                    last: (r1.max(r2), c1.max(c2)),
+                    target,
+                });
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(links)
+}
+
+/// Wraps `text` in an OSC 8 escape, which terminals that support it show as a
+/// clickable link to `url`
+pub fn terminal_link(url: &str, text: &str) -> String {
+    format!("\x1b]8;;{}\x1b\\{text}\x1b]8;;\x1b\\", escape_url(url))
+}
+
+/// `url` with C0 and C1 control characters percent-encoded. Targets come from
+/// the file, and an ESC, BEL or ST in one would end the escape early and pass
+/// the rest to the terminal.
+fn escape_url(url: &str) -> Cow<'_, str> {
+    if !url.chars().any(char::is_control) {
+        return Cow::Borrowed(url);
+    }
+    let mut escaped = String::with_capacity(url.len());
+    for c in url.chars() {
+        if c.is_control() {
+            let mut bytes = [0; 4];
+            for byte in c.encode_utf8(&mut bytes).bytes() {
+                escaped.push_str(&format!("%{byte:02X}"));
+            }
+        } else {
+            escaped.push(c);
+        }
+    }
+    Cow::Owned(escaped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hyperlinks() {
+        let sheet = r#"<worksheet><sheetData/><hyperlinks>
+            <hyperlink ref="A2" r:id="rId1"/>
+            <hyperlink ref="B2" r:id="rId2" location="Intro"/>
+            <hyperlink ref="C2:C3" location="'Q3 Budget'!B5" display="Budget"/>
+            <hyperlink ref="D2" r:id="rId9"/>
+            </hyperlinks></worksheet>"#;
+        let urls = HashMap::from([
+            ("rId1".to_string(), "https://example.com/".to_string()),
+            ("rId2".to_string(), "guide.docx".to_string()),
+        ]);
+        let links: Vec<(String, LinkTarget)> = parse_hyperlinks(sheet.as_bytes(), &urls)
+            .unwrap()
+            .into_iter()
+            .map(|link| (link.address(), link.target))
+            .collect();
+        let location = LinkTarget::Location("'Q3 Budget'!B5".to_string());
+        assert_eq!(
+            links,
+            vec![
+                (
+                    "A2".to_string(),
+                    LinkTarget::Url("https://example.com/".to_string())
+                ),
+                (
+                    "B2".to_string(),
+                    LinkTarget::Url("guide.docx#Intro".to_string())
+                ),
+                ("C2:C3".to_string(), location),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_terminal_link_escapes_control_characters() {
+        assert_eq!(
+            terminal_link("https://a.example/", "a"),
+            "\x1b]8;;https://a.example/\x1b\\a\x1b]8;;\x1b\\"
+        );
+        let link = terminal_link("https://a.example/\x1b\\\x1b]0;pwned\x07x\u{9c}", "a");
+        assert_eq!(
+            link,
+            "\x1b]8;;https://a.example/%1B\\%1B]0;pwned%07x%C2%9C\x1b\\a\x1b]8;;\x1b\\"
+        );
+        // Only the escape's own ESC characters are left
+        assert_eq!(link.matches('\x1b').count(), 4);
+    }
+
+    #[test]
+    fn test_link_ranges() {
+        let sheet = r#"<worksheet><hyperlinks>
+            <hyperlink ref="A1:XFD1048576" location="Index!A1"/>
+            </hyperlinks></worksheet>"#;
+        let links = parse_hyperlinks(sheet.as_bytes(), &HashMap::new()).unwrap();
+        assert_eq!(links.len(), 1);
+        let link = &links[0];
+        assert_eq!(link.address(), "A1:XFD1048576");
+        assert!(link.contains(1_048_575, 16_383));
+        assert!(!link.contains(1_048_576, 0));
+
+        let clamped = link.clamp((4, 1), (9, 2)).unwrap();
+        assert_eq!(clamped.address(), "B5:C10");
+        assert!(clamped.clamp((20, 0), (30, 5)).is_none());
+    }
+}
@@ -0,0 +1,165 @@
+use crate::styles::CellFormats;
+use crate::workbook::{CellValue, HEADER_ORIGIN, JsonData, SheetData};
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::io::Read;
+
+/// Header used for records that aren't JSON objects
+const VALUE_COLUMN: &str = "value";
+
+/// Whether files with this extension are loaded as JSON
+pub fn is_json_extension(ext: &str) -> bool {
+    matches!(
+        ext.to_ascii_lowercase().as_str(),
+        "json" | "ndjson" | "jsonl"
+    )
+}
+
+/// Whether piped data looks like JSON rather than delimited text
+pub fn looks_like_json(bytes: &[u8]) -> bool {
+    let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+    matches!(
+        bytes.iter().find(|b| !b.is_ascii_whitespace()),
+        Some(b'{' | b'[')
+    )
+}
+
+/// Loads a JSON array of records, or newline-delimited (or concatenated) JSON
+/// values, as one sheet. Headers are the union of object keys in first-seen order.
+pub fn load_json_data(mut input: impl Read, name: &str) -> Result<JsonData> {
+    let mut bytes = Vec::new();
+    input.read_to_end(&mut bytes)?;
+    let text = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(&bytes);
+
+    let mut values = serde_json::Deserializer::from_slice(text)
+        .into_iter::<Value>()
+        .collect::<Result<Vec<_>, _>>()
+        .context("Invalid JSON")?;
+    // A single top-level array holds the records; otherwise each value is one
+    let records = match values.as_mut_slice() {
+        [Value::Array(items)] => std::mem::take(items),
+        _ => values,
+    };
+
+    let mut headers: Vec<String> = Vec::new();
+    let mut columns: HashMap<String, usize> = HashMap::new();
+    let mut column_of = |key: &str| {
+        *columns.entry(key.to_string()).or_insert_with(|| {
+            headers.push(key.to_string());
+            headers.len() - 1
+        })
+    };
+
+    let mut sparse_rows = Vec::with_capacity(records.len());
+    for record in records {
+        let cells: Vec<(usize, CellValue)> = match record {
+            Value::Object(fields) => fields
+                .into_iter()
+                .map(|(key, value)| (column_of(&key), json_to_cellvalue(value)))
+                .collect(),
+            other => vec![(column_of(VALUE_COLUMN), json_to_cellvalue(other))],
+        };
+        sparse_rows.push(cells);
+    }
+
+    let width = headers.len();
+    let rows: Vec<Vec<CellValue>> = sparse_rows
+        .into_iter()
+        .map(|cells| {
+            let mut row = vec![CellValue::Empty; width];
+            for (col, cell) in cells {
+                row[col] = cell;
+            }
+            row
+        })
+        .collect();
+    let height = rows.len();
+
+    Ok(JsonData {
+        name: name.to_string(),
+        data: SheetData {
+            headers,
+            rows,
+            formulas: vec![vec![None; width]; height],
+            merges: Vec::new(),
+            formats: CellFormats::default(),
+            origin: HEADER_ORIGIN,
+            width,
+            height,
+        },
+    })
+}
+
+/// Indented form of a cell holding a JSON object or array, for the detail view
+pub fn pretty_print(text: &str) -> Option<String> {
+    if !looks_like_json(text.as_bytes()) {
+        return None;
+    }
+    let value: Value = serde_json::from_str(text).ok()?;
+    serde_json::to_string_pretty(&value).ok()
+}
+
+/// Scalars map to typed cells; nested objects and arrays are kept as compact JSON,
+/// and integers too large for a signed 64-bit cell as text
+fn json_to_cellvalue(value: Value) -> CellValue {
+    match value {
+        Value::Null => CellValue::Empty,
+        Value::Bool(b) => CellValue::Bool(b),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => CellValue::Int(i),
+            // 64-bit IDs above i64::MAX would lose digits as floats
+            None if n.is_u64() => CellValue::String(n.to_string()),
+            None => n.as_f64().map_or(CellValue::Empty, CellValue::Float),
+        },
+        Value::String(s) => CellValue::String(s),
+        nested => CellValue::String(nested.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_json_array_union_of_keys() {
+        let input = r#"[
+            {"id": 1, "name": "Größe", "tags": ["a", "b"]},
+            {"id": 2, "extra": {"nested": true}, "name": null, "score": 2.5},
+            {"id": 18446744073709551615}
+        ]"#;
+        let json = load_json_data(input.as_bytes(), "api").unwrap();
+        let data = &json.data;
+        assert_eq!(data.headers, vec!["id", "name", "tags", "extra", "score"]);
+        assert_eq!(data.height, 3);
+        assert!(matches!(&data.rows[0][2], CellValue::String(s) if s == r#"["a","b"]"#));
+        assert!(matches!(&data.rows[1][3], CellValue::String(s) if s == r#"{"nested":true}"#));
+        assert!(matches!(data.rows[1][1], CellValue::Empty));
+        assert!(matches!(data.rows[1][4], CellValue::Float(f) if f == 2.5));
+        assert!(matches!(&data.rows[2][0], CellValue::String(s) if s == "18446744073709551615"));
+        assert!(matches!(data.rows[2][1], CellValue::Empty));
+    }
+
+    #[test]
+    fn test_load_ndjson_and_scalars() {
+        let input = "{\"b\": 1, \"a\": true}\n\n{\"a\": false, \"c\": \"x\"}\n42\n";
+        let data = load_json_data(input.as_bytes(), "log").unwrap().data;
+        assert_eq!(data.headers, vec!["b", "a", "c", "value"]);
+        assert_eq!(data.height, 3);
+        assert!(matches!(data.rows[1][1], CellValue::Bool(false)));
+        assert!(matches!(data.rows[2][3], CellValue::Int(42)));
+    }
+
+    #[test]
+    fn test_invalid_json_reports_position() {
+        let err = load_json_data("{\"a\": 1}\n{\"a\": }\n".as_bytes(), "bad").unwrap_err();
+        assert!(format!("{err:#}").contains("line 2"));
+        assert!(looks_like_json(b"\xEF\xBB\xBF  [1]"));
+        assert!(!looks_like_json(b"a,b\n1,2\n"));
+        assert_eq!(
+            pretty_print("{\"a\":[1]}").unwrap(),
+            "{\n  \"a\": [\n    1\n  ]\n}"
+        );
+        assert!(pretty_print("[not json").is_none());
+    }
+}
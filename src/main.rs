@@ -1,19 +1,39 @@
 use anyhow::{Context, Result};
 use clap::Parser;
-use std::path::PathBuf;
-
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+
+mod arrow_convert;
+mod arrow_source;
+mod cell_errors;
+mod comments;
+mod compression;
 mod config;
+mod csv_source;
 mod display;
+mod hyperlinks;
+mod json_source;
+mod merges;
+mod names;
+mod number_format;
+mod package;
+mod parquet_source;
+mod regions;
+mod sqlite_source;
+mod styles;
 mod tui;
+mod vba;
 mod workbook;
+mod xlsb;
+mod xml;
 
 #[derive(Parser)]
 #[command(name = "xleak")]
 #[command(author, version, about = "A fast terminal viewer for Excel and CSV files.", long_about = None)]
 struct Cli {
-    /// Path to the data file (.xlsx, .xls, .xlsm, .ods, .csv)
+    /// Path to the data file (.xlsx, .xls, .xlsm, .ods, .csv, .tsv). Use - (or pipe data in) to read stdin
     #[arg(value_name = "FILE")]
-    file: PathBuf,
+    file: Option<PathBuf>,
 
     /// Sheet name or index to display (default: first sheet). For CSV, this is ignored.
     #[arg(short, long, value_name = "SHEET")]
@@ -31,6 +51,15 @@ struct Cli {
     #[arg(short, long)]
     formulas: bool,
 
+    /// Repeat the value of merged cells in every cell they cover, e.g. for filtering an export
+    #[arg(long)]
+    fill_merged: bool,
+
+    /// Export numbers and dates as formatted in the workbook (e.g. 25%, $1,234.50, 03/15/2024)
+    /// instead of their raw values (xlsx and xlsb only)
+    #[arg(long)]
+    formatted: bool,
+
     /// Maximum column width in characters (default: 30)
     #[arg(short = 'w', long, default_value = "30")]
     max_width: usize,
@@ -58,6 +87,177 @@ struct Cli {
     /// Extract a specific Excel table by name (.xlsx only)
     #[arg(short = 't', long, value_name = "TABLE")]
     table: Option<String>,
+
+    /// List the defined names in the workbook, with their scope and the cells they refer to
+    #[arg(long)]
+    list_names: bool,
+
+    /// Extract the cells a defined name refers to (e.g. Revenue, or Sheet1!Revenue for a sheet-level name)
+    #[arg(long, value_name = "NAME", conflicts_with = "table")]
+    name: Option<String>,
+
+    /// List the VBA modules of a macro-enabled workbook (.xlsm, .xlsb, .xls) and the
+    /// libraries they reference
+    #[arg(long)]
+    list_macros: bool,
+
+    /// Print the source code of a VBA module, as listed by --list-macros
+    #[arg(long, value_name = "MODULE")]
+    r#macro: Option<String>,
+
+    /// List the separate blocks of data detected in each sheet (or the one given with --sheet)
+    #[arg(long)]
+    list_regions: bool,
+
+    /// List the cells holding an error value (#DIV/0!, #N/A, ...) and the formulas behind
+    /// them, in each sheet (or the one given with --sheet). Exits with status 1 if there are any
+    #[arg(long)]
+    errors: bool,
+
+    /// List the comments and notes on cells, with their authors, in each sheet (or the one
+    /// given with --sheet)
+    #[arg(long)]
+    list_comments: bool,
+
+    /// Show data region N of the sheet, as numbered by --list-regions (default: the largest)
+    #[arg(
+        long,
+        value_name = "N",
+        value_parser = clap::value_parser!(u64).range(1..),
+        conflicts_with_all = ["header_row", "header_rows", "no_header", "skip_rows", "from_a1"]
+    )]
+    region: Option<u64>,
+
+    /// Sheet row (or line of a CSV file) holding the column names (default: 1); rows above it are skipped
+    #[arg(
+        long,
+        value_name = "N",
+        value_parser = clap::value_parser!(u64).range(1..),
+        conflicts_with_all = ["no_header", "skip_rows"]
+    )]
+    header_row: Option<u64>,
+
+    /// Number of stacked header rows, combined into names like "2024 / Q1" (default: 1)
+    #[arg(
+        long,
+        value_name = "N",
+        value_parser = clap::value_parser!(u64).range(1..),
+        conflicts_with = "no_header"
+    )]
+    header_rows: Option<u64>,
+
+    /// Treat every row as data and name the columns A, B, C, ...
+    #[arg(long)]
+    no_header: bool,
+
+    /// Skip N rows (CSV: lines) at the top of the sheet before the header (or the data, with --no-header)
+    #[arg(long, value_name = "N", default_value = "0")]
+    skip_rows: usize,
+
+    /// Start the grid at cell A1, keeping empty rows and columns before the used range.
+    /// Columns are named A, B, C, ... unless --header-row is given
+    #[arg(long)]
+    from_a1: bool,
+
+    /// Show and export hidden and very hidden sheets, marked as such in sheet lists
+    #[arg(long)]
+    include_hidden: bool,
+
+    /// CSV field delimiter, e.g. ';', '|' or 'tab' (default: detected from the data)
+    #[arg(long, value_name = "CHAR", value_parser = csv_source::parse_char_arg)]
+    delimiter: Option<u8>,
+
+    /// CSV quote character (default: ")
+    #[arg(long, value_name = "CHAR", value_parser = csv_source::parse_char_arg)]
+    quote: Option<u8>,
+
+    /// CSV escape character for quotes inside quoted fields (default: doubled quotes)
+    #[arg(long, value_name = "CHAR", value_parser = csv_source::parse_char_arg)]
+    escape: Option<u8>,
+
+    /// CSV text encoding, e.g. utf-8, windows-1252, latin1, utf-16le (default: detected)
+    #[arg(long, value_name = "ENCODING", value_parser = csv_source::parse_encoding_arg)]
+    encoding: Option<&'static encoding_rs::Encoding>,
+
+    /// CSV column types, e.g. zip=string,amount=float (types: string, int, float, date, bool, auto)
+    #[arg(
+        long,
+        value_name = "COL=TYPE,...",
+        value_delimiter = ',',
+        value_parser = csv_source::parse_column_type_arg
+    )]
+    types: Vec<(String, csv_source::ColumnType)>,
+
+    /// CSV type inference: standard, or conservative to keep zero-padded/long digit strings as text
+    #[arg(long, value_name = "MODE")]
+    infer: Option<csv_source::TypeInference>,
+
+    /// Order of day and month in numeric CSV dates like 03/04/2024 (default: ymd, i.e. ISO only)
+    #[arg(long, value_name = "ORDER")]
+    date_order: Option<csv_source::DateOrder>,
+
+    /// Extra CSV date format in chrono syntax, e.g. "%d %b %Y" (repeatable)
+    #[arg(long = "date-format", value_name = "FORMAT")]
+    date_formats: Vec<String>,
+}
+
+impl Cli {
+    /// Collect file parsing options from the config file and command line (CLI wins)
+    fn load_options(&self, config: &config::Config) -> workbook::LoadOptions {
+        let mut options = workbook::LoadOptions {
+            header: workbook::HeaderOptions {
+                skip_rows: match self.header_row {
+                    Some(row) => usize::try_from(row - 1).unwrap_or(usize::MAX),
+                    None => self.skip_rows,
+                },
+                header_rows: match self.header_rows {
+                    _ if self.no_header => 0,
+                    Some(rows) => usize::try_from(rows).unwrap_or(usize::MAX),
+                    // Row 1 is usually blank when the used range starts lower down
+                    None if self.from_a1 && self.header_row.is_none() => 0,
+                    None => 1,
+                },
+            },
+            // Explicit header settings describe the whole sheet, so don't guess
+            detect_regions: self.header_row.is_none()
+                && self.header_rows.is_none()
+                && !self.no_header
+                && self.skip_rows == 0
+                && !self.from_a1,
+            from_a1: self.from_a1,
+            include_hidden: self.include_hidden,
+            ..Default::default()
+        };
+        options.csv.delimiter = self.delimiter;
+        if let Some(quote) = self.quote {
+            options.csv.quote = quote;
+        }
+        options.csv.escape = self.escape;
+        options.csv.encoding = self.encoding;
+
+        options.csv.inference = self.infer.unwrap_or(config.csv.infer);
+        options.csv.date_order = self.date_order.unwrap_or(config.csv.date_order);
+        options.csv.date_formats = self
+            .date_formats
+            .iter()
+            .chain(&config.csv.date_formats)
+            .cloned()
+            .collect();
+        let file_name = self
+            .file
+            .as_deref()
+            .and_then(|p| p.file_name())
+            .and_then(|n| n.to_str());
+        if let Some(file_name) = file_name {
+            for (column, column_type) in config.csv_column_types_for(file_name) {
+                options.csv.set_column_type(column, column_type);
+            }
+        }
+        for (column, column_type) in &self.types {
+            options.csv.set_column_type(column, *column_type);
+        }
+        options
+    }
 }
 
 fn main() -> Result<()> {
@@ -66,14 +266,28 @@ fn main() -> Result<()> {
     // Load configuration
     let config = config::Config::load(cli.config.clone())?;
 
-    // Validate file exists
-    if !cli.file.exists() {
-        anyhow::bail!("File not found: {}", cli.file.display());
-    }
-
-    // Open the workbook (handles both Excel and CSV)
-    let mut wb = workbook::Workbook::open(&cli.file)
-        .with_context(|| format!("Failed to open file '{}'", cli.file.display()))?;
+    // Open the workbook (handles both Excel and CSV, from a file or stdin)
+    let load_options = cli.load_options(&config);
+    let mut wb = match cli.file.as_deref() {
+        Some(path) if path != Path::new("-") => {
+            // Validate file exists
+            if !path.exists() {
+                anyhow::bail!("File not found: {}", path.display());
+            }
+            workbook::Workbook::open(path, &load_options)
+                .with_context(|| format!("Failed to open file '{}'", path.display()))?
+        }
+        _ => {
+            let stdin = std::io::stdin();
+            if cli.file.is_none() && stdin.is_terminal() {
+                anyhow::bail!(
+                    "No input file given.\n\nUsage: xleak <FILE> or pipe data in, e.g. `cat data.csv | xleak -`"
+                );
+            }
+            workbook::Workbook::from_reader(stdin.lock(), "stdin", &load_options)
+                .context("Failed to read data from stdin")?
+        }
+    };
 
     // Handle table operations (Excel-only)
     if cli.list_tables {
@@ -88,11 +302,11 @@ fn main() -> Result<()> {
             for table_name in &table_names {
                 let sheet_names = wb.sheet_names();
                 for sheet in &sheet_names {
-                    if let Ok(tables_in_sheet) = wb.table_names_in_sheet(sheet) {
-                        if tables_in_sheet.contains(table_name) {
-                            println!("{sheet}\t{table_name}");
-                            break;
-                        }
+                    if let Ok(tables_in_sheet) = wb.table_names_in_sheet(sheet)
+                        && tables_in_sheet.contains(table_name)
+                    {
+                        println!("{sheet}\t{table_name}");
+                        break;
                     }
                 }
             }
@@ -125,6 +339,111 @@ fn main() -> Result<()> {
         return Ok(());
     }
 
+    // Handle VBA macros (macro-enabled spreadsheets only)
+    if cli.list_macros {
+        let Some(project) = wb.vba_project()? else {
+            println!("No VBA macros found in workbook");
+            return Ok(());
+        };
+        println!("Module\tLines");
+        println!("------\t-----");
+        for module in &project.modules {
+            println!("{}\t{}", module.name, module.line_count());
+        }
+        if !project.references.is_empty() {
+            println!("\nReference\tDescription\tPath");
+            println!("---------\t-----------\t----");
+            for reference in &project.references {
+                println!(
+                    "{}\t{}\t{}",
+                    reference.name, reference.description, reference.path
+                );
+            }
+        }
+        return Ok(());
+    }
+
+    if let Some(ref name) = cli.r#macro {
+        let project = wb
+            .vba_project()?
+            .ok_or_else(|| anyhow::anyhow!("No VBA macros found in workbook"))?;
+        let Some(module) = project.module(name) else {
+            let available: Vec<&str> = project.modules.iter().map(|m| m.name.as_str()).collect();
+            anyhow::bail!(
+                "Module '{name}' not found. Available: {}",
+                available.join(", ")
+            );
+        };
+        for line in module.code.lines() {
+            println!("{line}");
+        }
+        return Ok(());
+    }
+
+    // Handle defined names (spreadsheets only)
+    if cli.list_names {
+        let names = wb.defined_names()?;
+
+        if names.is_empty() {
+            println!("No defined names found in workbook");
+        } else {
+            println!("Name\tScope\tRefers to");
+            println!("----\t-----\t---------");
+            for defined in names {
+                println!(
+                    "{}\t{}\t{}",
+                    defined.name,
+                    defined.scope_label(),
+                    defined.refers_to
+                );
+            }
+        }
+        return Ok(());
+    }
+
+    if let Some(ref name) = cli.name {
+        let (defined, mut data) = wb.load_name(name)?;
+        if cli.fill_merged {
+            data.fill_merged();
+        }
+        if cli.formatted {
+            data.apply_number_formats();
+        }
+
+        let links = sheet_links(
+            &wb,
+            &defined.area().map(|(sheet, _)| sheet).unwrap_or_default(),
+            &data,
+        );
+
+        match cli.export.as_deref() {
+            Some("csv") => display::export_csv(&data)?,
+            Some("json") => display::export_json(&data, &defined.name, &links)?,
+            Some("text") => display::export_text(&data)?,
+            Some(format) => {
+                anyhow::bail!("Unknown export format: {format}. Use: csv, json, or text");
+            }
+            None if cli.interactive => {
+                anyhow::bail!(
+                    "Interactive mode (-i) is not supported with --name.\n\nOptions:\n• View the range in terminal: xleak file.xlsx --name \"{name}\"\n• Jump to it in the TUI: xleak file.xlsx -i, then press Ctrl+G and type {name}"
+                );
+            }
+            None => display::display_table(
+                &data,
+                data.height,
+                &format!("{} ({})", defined.name, defined.refers_to),
+                cli.max_rows,
+                &[],
+                cli.max_width,
+                cli.wrap,
+                cli.formulas,
+                &links,
+                None,
+            )?,
+        }
+        return Ok(());
+    }
+
     // Get sheet names and determine which one to show
     let sheet_names = wb.sheet_names();
     if sheet_names.is_empty() {
@@ -134,14 +453,15 @@ fn main() -> Result<()> {
     let sheet_name = if let Some(ref name) = cli.sheet {
         if sheet_names.iter().any(|s| s == name) {
             name.clone()
+        } else if wb.is_hidden(name) {
+            anyhow::bail!("Sheet '{name}' is hidden; use --include-hidden to show it");
         } else if let Ok(idx) = name.parse::<usize>() {
             if idx > 0 && idx <= sheet_names.len() {
                 sheet_names[idx - 1].clone()
             } else {
                 anyhow::bail!("Sheet index {} out of range (1-{})", idx, sheet_names.len());
             }
-        }
-        else {
+        } else {
             anyhow::bail!(
                 "Sheet '{}' not found. Available: {}",
                 name,
@@ -152,30 +472,150 @@ fn main() -> Result<()> {
         sheet_names[0].clone()
     };
 
+    if cli.list_regions {
+        let sheets = match cli.sheet {
+            Some(_) => vec![sheet_name.clone()],
+            None => sheet_names.clone(),
+        };
+        println!("Sheet\tRegion\tRange\tHeader");
+        println!("-----\t------\t-----\t------");
+        for sheet in &sheets {
+            let regions = wb.data_regions(sheet)?;
+            let best = regions::best_region(&regions);
+            for (index, region) in regions.iter().enumerate() {
+                let marker = if Some(index) == best { " *" } else { "" };
+                println!(
+                    "{sheet}\t{}{marker}\t{}\trow {}",
+                    index + 1,
+                    region.address(),
+                    region.header_row + 1
+                );
+            }
+        }
+        println!("\n* shown by default; pick another with --region N");
+        return Ok(());
+    }
+
+    if cli.errors {
+        // Hidden sheets feed visible ones, so their errors count too
+        let sheets = match cli.sheet {
+            Some(_) => vec![sheet_name.clone()],
+            None => wb.all_sheet_names(),
+        };
+        let mut found = Vec::new();
+        for sheet in &sheets {
+            found.extend(wb.error_cells(sheet)?.into_iter().map(|cell| (sheet, cell)));
+        }
+        if found.is_empty() {
+            println!("No error cells found");
+            return Ok(());
+        }
+        println!("Sheet\tCell\tError\tFormula");
+        println!("-----\t----\t-----\t-------");
+        for (sheet, cell) in &found {
+            println!(
+                "{}\t{}\t{}\t{}",
+                wb.sheet_label(sheet),
+                cell.address(),
+                cell.error,
+                cell.formula.as_deref().unwrap_or("")
+            );
+        }
+        println!(
+            "\n{} error cell(s): {}",
+            found.len(),
+            cell_errors::summary(found.iter().map(|(_, cell)| cell.error))
+        );
+        // Lets scripts and CI fail on broken formulas
+        std::process::exit(1);
+    }
+
+    if cli.list_comments {
+        let sheets = match cli.sheet {
+            Some(_) => vec![sheet_name.clone()],
+            None => sheet_names.clone(),
+        };
+        let mut found = Vec::new();
+        for sheet in &sheets {
+            found.extend(
+                wb.comments(sheet)?
+                    .into_iter()
+                    .map(|comment| (sheet, comment)),
+            );
+        }
+        if found.is_empty() {
+            println!("No comments found");
+            return Ok(());
+        }
+        println!("Sheet\tCell\tAuthor\tComment");
+        println!("-----\t----\t------\t-------");
+        for (sheet, comment) in &found {
+            // One line per comment
+            println!(
+                "{sheet}\t{}\t{}\t{}",
+                comment.address(),
+                comment.author,
+                comment.text.replace('\n', " ")
+            );
+        }
+        return Ok(());
+    }
+
+    if let Some(region) = cli.region {
+        wb.select_region(
+            &sheet_name,
+            usize::try_from(region - 1).unwrap_or(usize::MAX),
+        );
+    }
+
     // Display, export, or run TUI
     if cli.interactive {
         tui::run_tui(wb, &sheet_name, &config, cli.horizontal_scroll)?;
     } else {
-        let data = wb
-            .load_sheet(&sheet_name)
+        // The table view only needs the rows it shows; exports need all of them
+        let max_rows = if cli.export.is_some() {
+            0
+        } else {
+            cli.max_rows
+        };
+        let (mut data, total_rows) = wb
+            .load_sheet_head(&sheet_name, max_rows)
             .with_context(|| format!("Failed to load sheet '{sheet_name}'"))?;
+        if cli.fill_merged {
+            data.fill_merged();
+        }
+        if cli.formatted {
+            data.apply_number_formats();
+        }
+        let links = sheet_links(&wb, &sheet_name, &data);
+        let region = wb.shown_region(&sheet_name);
+        if cli.export.is_some()
+            && let Some(region) = region
+        {
+            eprintln!("Note: sheet '{sheet_name}': {}", region.note());
+        }
         match cli.export.as_deref() {
             Some("csv") => display::export_csv(&data)?,
-            Some("json") => display::export_json(&data, &sheet_name)?,
+            Some("json") => display::export_json(&data, &sheet_name, &links)?,
             Some("text") => display::export_text(&data)?,
             Some(format) => {
                 anyhow::bail!("Unknown export format: {format}. Use: csv, json, or text");
             }
             None => {
-                let sheet_names_refs: Vec<&str> = sheet_names.iter().map(|s| s.as_str()).collect();
+                let sheet_labels: Vec<String> =
+                    sheet_names.iter().map(|s| wb.sheet_label(s)).collect();
+                let sheet_names_refs: Vec<&str> = sheet_labels.iter().map(|s| s.as_str()).collect();
                 display::display_table(
                     &data,
+                    total_rows,
                     &sheet_name,
                     cli.max_rows,
                     &sheet_names_refs,
                     cli.max_width,
                     cli.wrap,
                     cli.formulas,
+                    &links,
+                    region,
                 )?;
             }
         }
@@ -184,6 +624,23 @@ fn main() -> Result<()> {
     Ok(())
 }
 
+/// Hyperlinks on the cells of `data`. Like merges they only affect presentation,
+/// so unreadable ones are skipped.
+fn sheet_links(
+    wb: &workbook::Workbook,
+    sheet: &str,
+    data: &workbook::SheetData,
+) -> Vec<hyperlinks::Hyperlink> {
+    let Some((first, last)) = data.data_extent() else {
+        return Vec::new();
+    };
+    wb.hyperlinks(sheet)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|link| link.clamp(first, last))
+        .collect()
+}
+
 /// Display table data in terminal (default behavior)
 fn display_table_data(table: &workbook::TableData, max_rows: usize) -> Result<()> {
     use prettytable::{Cell, Row, Table, format};
@@ -279,7 +736,10 @@ fn export_table_json(table: &workbook::TableData) -> Result<()> {
                 workbook::CellValue::Float(f) => f.to_string(),
                 workbook::CellValue::Bool(b) => b.to_string(),
                 workbook::CellValue::Empty => "null".to_string(),
-                _ => format!("\"{cell}\""),
+                _ => format!(
+                    "\"{}\"",
+                    cell.to_iso_string().unwrap_or_else(|| cell.to_string())
+                ),
             };
             print!("{value}");
             if j < row.len() - 1 {
@@ -302,7 +762,7 @@ fn export_table_csv(table: &workbook::TableData) -> Result<()> {
         let row_str: Vec<String> = row
             .iter()
             .map(|cell| {
-                let val = cell.to_raw_string();
+                let val = cell.to_iso_string().unwrap_or_else(|| cell.to_raw_string());
                 if val.contains(',') || val.contains('"') {
                     format!("\"{}\"", val.replace('"', "\"\""))
                 } else {
@@ -324,4 +784,4 @@ fn export_table_text(table: &workbook::TableData) -> Result<()> {
         println!("{}", row_str.join("\t"));
     }
     Ok(())
-}
\ No newline at end of file
+}
@@ -0,0 +1,240 @@
+use crate::workbook::{CellValue, column_letter};
+use crate::xml::{attribute, number_attribute};
+use anyhow::Result;
+use calamine::Dimensions;
+use quick_xml::events::Event;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Seek};
+
+/// A block of merged cells in data coordinates (row 0 is the first data row),
+/// end inclusive. The value lives in the top-left cell; the rest are empty.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MergedCells {
+    pub start: (usize, usize),
+    pub end: (usize, usize),
+}
+
+impl MergedCells {
+    pub fn contains(&self, row: usize, col: usize) -> bool {
+        (self.start.0..=self.end.0).contains(&row) && (self.start.1..=self.end.1).contains(&col)
+    }
+
+    pub fn rows(&self) -> usize {
+        self.end.0 - self.start.0 + 1
+    }
+
+    pub fn cols(&self) -> usize {
+        self.end.1 - self.start.1 + 1
+    }
+
+    /// Merges from sheet coordinates, moved so `origin` (the sheet position of
+    /// the first data cell) is (0, 0) and clipped to a `height` × `width` grid.
+    /// Merges whose value cell lies outside the data (e.g. in a header) are dropped.
+    pub fn from_sheet(
+        merges: &[Dimensions],
+        origin: (usize, usize),
+        height: usize,
+        width: usize,
+    ) -> Vec<Self> {
+        merges
+            .iter()
+            .filter_map(|merge| {
+                let row = (merge.start.0 as usize).checked_sub(origin.0)?;
+                let col = (merge.start.1 as usize).checked_sub(origin.1)?;
+                if row >= height || col >= width {
+                    return None;
+                }
+                let end = (
+                    (merge.end.0 as usize - origin.0).min(height - 1),
+                    (merge.end.1 as usize - origin.1).min(width - 1),
+                );
+                let merged = Self {
+                    start: (row, col),
+                    end,
+                };
+                (merged.rows() * merged.cols() > 1).then_some(merged)
+            })
+            .collect()
+    }
+}
+
+/// The merge covering a cell, if any
+pub fn merge_at(merges: &[MergedCells], row: usize, col: usize) -> Option<&MergedCells> {
+    merges.iter().find(|merge| merge.contains(row, col))
+}
+
+/// Copies each merge's value into every cell it covers
+pub fn fill_merged(rows: &mut [Vec<CellValue>], merges: &[MergedCells]) {
+    for merge in merges {
+        let Some(value) = rows
+            .get(merge.start.0)
+            .map(|row| row[merge.start.1].clone())
+        else {
+            continue;
+        };
+        let last_row = merge.end.0.min(rows.len() - 1);
+        for row in &mut rows[merge.start.0..=last_row] {
+            for cell in &mut row[merge.start.1..=merge.end.1] {
+                *cell = value.clone();
+            }
+        }
+    }
+}
+
+/// Extent of a merge for display, e.g. `A1:C2 (2 rows × 3 columns)`, with
+/// `origin` the sheet position of data cell (0, 0)
+pub fn describe(merge: &MergedCells, origin: (usize, usize)) -> String {
+    format!(
+        "{}{}:{}{} ({} row{} × {} column{})",
+        column_letter(origin.1 + merge.start.1),
+        origin.0 + merge.start.0 + 1,
+        column_letter(origin.1 + merge.end.1),
+        origin.0 + merge.end.0 + 1,
+        merge.rows(),
+        if merge.rows() == 1 { "" } else { "s" },
+        merge.cols(),
+        if merge.cols() == 1 { "" } else { "s" },
+    )
+}
+
+/// Merged regions of every sheet in an OpenDocument spreadsheet, which calamine
+/// doesn't expose. Sheet coordinates, keyed by sheet name.
+pub fn read_ods_merges(reader: impl Read + Seek) -> Result<HashMap<String, Vec<Dimensions>>> {
+    let mut archive = zip::ZipArchive::new(reader)?;
+    let content = archive.by_name("content.xml")?;
+    parse_ods_merges(BufReader::new(content))
+}
+
+fn parse_ods_merges(content: impl BufRead) -> Result<HashMap<String, Vec<Dimensions>>> {
+    let mut xml = quick_xml::Reader::from_reader(content);
+    let mut buf = Vec::new();
+    let mut sheets = HashMap::new();
+    let mut sheet: Option<(String, Vec<Dimensions>)> = None;
+    let (mut row, mut col, mut row_repeat) = (0u32, 0u32, 1u32);
+
+    loop {
+        buf.clear();
+        let (element, is_empty) = match xml.read_event_into(&mut buf)? {
+            Event::Start(e) => (e, false),
+            Event::Empty(e) => (e, true),
+            Event::End(e) => {
+                match e.name().as_ref() {
+                    b"table:table-row" => row += row_repeat,
+                    b"table:table" => sheets.extend(sheet.take()),
+                    _ => {}
+                }
+                continue;
+            }
+            Event::Eof => break,
+            _ => continue,
+        };
+
+        match element.name().as_ref() {
+            b"table:table" => {
+                let name = attribute(&element, "table:name")?.unwrap_or_default();
+                sheet = Some((name, Vec::new()));
+                row = 0;
+            }
+            b"table:table-row" => {
+                col = 0;
+                row_repeat = number_attribute(&element, "table:number-rows-repeated")?;
+                if is_empty {
+                    row += row_repeat;
+                }
+            }
+            name @ (b"table:table-cell" | b"table:covered-table-cell") => {
+                if name == b"table:table-cell" {
+                    let rows = number_attribute(&element, "table:number-rows-spanned")?;
+                    let cols = number_attribute(&element, "table:number-columns-spanned")?;
+                    if (rows > 1 || cols > 1)
+                        && let Some((_, merges)) = &mut sheet
+                    {
+                        merges.push(Dimensions::new(
+                            (row, col),
+                            (row + rows - 1, col + cols - 1),
+                        ));
+                    }
+                }
+                col += number_attribute(&element, "table:number-columns-repeated")?;
+            }
+            _ => {}
+        }
+    }
+    Ok(sheets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_sheet_clips_to_data() {
+        let merges = [
+            // Title across the header row: not part of the data
+            Dimensions::new((0, 0), (0, 3)),
+            Dimensions::new((2, 1), (4, 1)),
+            // Runs past the last data column
+            Dimensions::new((3, 2), (3, 9)),
+        ];
+        let merged = MergedCells::from_sheet(&merges, (2, 1), 3, 3);
+        assert_eq!(
+            merged,
+            vec![
+                MergedCells {
+                    start: (0, 0),
+                    end: (2, 0)
+                },
+                MergedCells {
+                    start: (1, 1),
+                    end: (1, 2)
+                },
+            ]
+        );
+        assert_eq!(describe(&merged[0], (2, 1)), "B3:B5 (3 rows × 1 column)");
+
+        let mut rows = vec![
+            vec![
+                CellValue::String("East".into()),
+                CellValue::Int(1),
+                CellValue::Empty,
+            ],
+            vec![CellValue::Empty, CellValue::Int(2), CellValue::Empty],
+            vec![CellValue::Empty, CellValue::Int(3), CellValue::Int(4)],
+        ];
+        fill_merged(&mut rows, &merged);
+        assert!(
+            rows.iter()
+                .all(|row| matches!(&row[0], CellValue::String(s) if s == "East"))
+        );
+        assert!(matches!(rows[1][2], CellValue::Int(2)));
+        assert!(merge_at(&merged, 2, 0).is_some());
+        assert!(merge_at(&merged, 2, 2).is_none());
+    }
+
+    #[test]
+    fn test_parse_ods_merges() {
+        let content = r#"<office:document-content><office:body><office:spreadsheet>
+            <table:table table:name="Sales">
+              <table:table-row>
+                <table:table-cell table:number-columns-spanned="3"><text:p>Title</text:p></table:table-cell>
+                <table:covered-table-cell table:number-columns-repeated="2"/>
+              </table:table-row>
+              <table:table-row table:number-rows-repeated="2"><table:table-cell/></table:table-row>
+              <table:table-row>
+                <table:table-cell table:number-columns-repeated="2"/>
+                <table:table-cell table:number-rows-spanned="2" table:number-columns-spanned="2"/>
+              </table:table-row>
+            </table:table>
+            <table:table table:name="Empty"><table:table-row/></table:table>
+        </office:spreadsheet></office:body></office:document-content>"#;
+        let merges = parse_ods_merges(content.as_bytes()).unwrap();
+        assert_eq!(
+            merges["Sales"],
+            vec![
+                Dimensions::new((0, 0), (0, 2)),
+                Dimensions::new((3, 2), (4, 3)),
+            ]
+        );
+        assert!(merges["Empty"].is_empty());
+    }
+}
@@ -0,0 +1,262 @@
+use crate::xml::attribute;
+use anyhow::Result;
+use calamine::Dimensions;
+use quick_xml::events::Event;
+use std::io::{BufRead, BufReader, Read, Seek};
+
+/// Last row and column of an Excel sheet, where whole-row and whole-column references end
+const MAX_ROW: u32 = 1_048_575;
+const MAX_COL: u32 = 16_383;
+
+/// A defined name: a named range, constant or formula
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DefinedName {
+    pub name: String,
+    /// Sheet the name is local to; `None` for workbook-level names
+    pub scope: Option<String>,
+    /// What the name stands for, e.g. `Sheet1!$B$2:$B$13`
+    pub refers_to: String,
+}
+
+impl DefinedName {
+    pub fn scope_label(&self) -> &str {
+        self.scope.as_deref().unwrap_or("Workbook")
+    }
+
+    /// The sheet and cells the name refers to, if it is a single rectangular range
+    pub fn area(&self) -> Option<(String, Dimensions)> {
+        parse_area(&self.refers_to)
+    }
+}
+
+/// Names as listed by calamine, which doesn't keep their scope
+pub fn from_calamine(names: &[(String, String)]) -> Vec<DefinedName> {
+    names
+        .iter()
+        .map(|(name, refers_to)| DefinedName {
+            name: name.clone(),
+            scope: None,
+            refers_to: refers_to.clone(),
+        })
+        .collect()
+}
+
+/// Looks a name up case-insensitively, as Excel does. `Sheet1!Rate` picks the
+/// name local to Sheet1; a bare name prefers one local to `current_sheet`, then
+/// the workbook-level one, then the only sheet-level name with that spelling.
+pub fn find<'a>(
+    names: &'a [DefinedName],
+    name: &str,
+    current_sheet: Option<&str>,
+) -> Option<&'a DefinedName> {
+    let (scope, bare) = match name.rsplit_once('!') {
+        Some((sheet, bare)) => (Some(sheet.trim_matches('\'')), bare),
+        None => (None, name),
+    };
+    let candidates: Vec<&DefinedName> = names
+        .iter()
+        .filter(|defined| defined.name.eq_ignore_ascii_case(bare))
+        .collect();
+    let local_to = |sheet: &str| {
+        candidates.iter().copied().find(|defined| {
+            defined
+                .scope
+                .as_deref()
+                .is_some_and(|scope| scope.eq_ignore_ascii_case(sheet))
+        })
+    };
+
+    if let Some(sheet) = scope {
+        return local_to(sheet);
+    }
+    current_sheet
+        .and_then(local_to)
+        .or_else(|| {
+            candidates
+                .iter()
+                .copied()
+                .find(|defined| defined.scope.is_none())
+        })
+        .or(match candidates.as_slice() {
+            [only] => Some(only),
+            _ => None,
+        })
+}
+
+/// Defined names of an xlsx file with their scope, read from `xl/workbook.xml`
+pub fn read_xlsx_names(reader: impl Read + Seek) -> Result<Vec<DefinedName>> {
+    let mut archive = zip::ZipArchive::new(reader)?;
+    let workbook = archive.by_name("xl/workbook.xml")?;
+    parse_xlsx_names(BufReader::new(workbook))
+}
+
+fn parse_xlsx_names(workbook: impl BufRead) -> Result<Vec<DefinedName>> {
+    let mut xml = quick_xml::Reader::from_reader(workbook);
+    let mut buf = Vec::new();
+    let mut sheets = Vec::new();
+    let mut names = Vec::new();
+    // The name being read; its text content is the reference
+    let mut current: Option<DefinedName> = None;
+
+    loop {
+        buf.clear();
+        match xml.read_event_into(&mut buf)? {
+            Event::Start(e) | Event::Empty(e) if e.local_name().as_ref() == b"sheet" => {
+                sheets.push(attribute(&e, "name")?.unwrap_or_default());
+            }
+            Event::Start(e) if e.local_name().as_ref() == b"definedName" => {
+                // Hidden names are Excel's own bookkeeping, e.g. `_xlnm._FilterDatabase`
+                if attribute(&e, "hidden")?.is_some_and(|hidden| hidden == "1" || hidden == "true")
+                {
+                    continue;
+                }
+                let scope = attribute(&e, "localSheetId")?
+                    .and_then(|id| id.parse::<usize>().ok())
+                    .and_then(|id| sheets.get(id).cloned());
+                current = Some(DefinedName {
+                    name: attribute(&e, "name")?.unwrap_or_default(),
+                    scope,
+                    refers_to: String::new(),
+                });
+            }
+            Event::Text(text) => {
+                if let Some(defined) = &mut current {
+                    defined.refers_to.push_str(&text.unescape()?);
+                }
+            }
+            Event::End(e) if e.local_name().as_ref() == b"definedName" => {
+                names.extend(current.take());
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(names)
+}
+
+/// Parses `Sheet1!$A$1:$C$10`, `'My Sheet'!B2`, whole rows/columns like `Data!$A:$C`,
+/// and OpenDocument's `$Sheet1.$A$1:.$C$10`. Constants, formulas and multi-area
+/// references yield `None`.
+pub fn parse_area(reference: &str) -> Option<(String, Dimensions)> {
+    let reference = reference.trim().trim_start_matches('=');
+    let (sheet, cells) = split_sheet(reference)?;
+    let (first, last) = cells.split_once(':').unwrap_or((cells, cells));
+    // OpenDocument repeats the sheet (or at least the separator) before the end cell
+    let last = split_sheet(last).map_or(last, |(_, cell)| cell);
+    let last = last.strip_prefix('.').unwrap_or(last);
+
+    let (first_row, first_col) = parse_cell(first)?;
+    let (last_row, last_col) = parse_cell(last)?;
+    let start = (first_row.unwrap_or(0), first_col.unwrap_or(0));
+    let end = (last_row.unwrap_or(MAX_ROW), last_col.unwrap_or(MAX_COL));
+    (start.0 <= end.0 && start.1 <= end.1).then(|| (sheet, Dimensions::new(start, end)))
+}
+
+/// Splits a sheet-qualified reference into the unquoted sheet name and the cell part
+fn split_sheet(reference: &str) -> Option<(String, &str)> {
+    // OpenDocument references start with `$` and separate the sheet with `.`
+    let (reference, separators) = match reference.strip_prefix('$') {
+        Some(reference) => (reference, ['!', '.']),
+        None => (reference, ['!', '!']),
+    };
+    if let Some(quoted) = reference.strip_prefix('\'') {
+        // Quotes inside a quoted sheet name are doubled
+        let mut sheet = String::new();
+        let mut chars = quoted.char_indices();
+        while let Some((i, c)) = chars.next() {
+            if c != '\'' {
+                sheet.push(c);
+            } else if quoted[i + 1..].starts_with('\'') {
+                sheet.push('\'');
+                chars.next();
+            } else {
+                let rest = &quoted[i + 1..];
+                return Some((sheet, rest.strip_prefix(separators)?));
+            }
+        }
+        return None;
+    }
+    let (sheet, cells) = reference.split_once(separators)?;
+    // Sheet names with spaces or symbols are quoted, so this is part of a formula
+    if sheet.contains(|c: char| "()+-*/^&=<>,;\" ".contains(c)) {
+        return None;
+    }
+    Some((sheet.to_string(), cells))
+}
+
+/// Zero-based row and column of `$B$7`, `B7`, a whole column (`$B`) or a whole row (`7`)
+pub fn parse_cell(cell: &str) -> Option<(Option<u32>, Option<u32>)> {
+    let cell = cell.replace('$', "");
+    let digits = cell
+        .find(|c: char| c.is_ascii_digit())
+        .unwrap_or(cell.len());
+    let (letters, number) = cell.split_at(digits);
+    if !letters.chars().all(|c| c.is_ascii_alphabetic()) || letters.len() > 3 {
+        return None;
+    }
+    let col = (!letters.is_empty()).then(|| {
+        letters.chars().fold(0, |col, c| {
+            col * 26 + (c.to_ascii_uppercase() as u32 - 'A' as u32 + 1)
+        }) - 1
+    });
+    let row = match number {
+        "" => None,
+        number => Some(number.parse::<u32>().ok()?.checked_sub(1)?),
+    };
+    (row.is_some() || col.is_some()).then_some((row, col))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn area(sheet: &str, start: (u32, u32), end: (u32, u32)) -> Option<(String, Dimensions)> {
+        Some((sheet.to_string(), Dimensions::new(start, end)))
+    }
+
+    #[test]
+    fn test_parse_area() {
+        assert_eq!(
+            parse_area("Sheet1!$B$2:$D$13"),
+            area("Sheet1", (1, 1), (12, 3))
+        );
+        assert_eq!(
+            parse_area("'Q1 ''24'!AA10"),
+            area("Q1 '24", (9, 26), (9, 26))
+        );
+        assert_eq!(parse_area("Data!$C:$C"), area("Data", (0, 2), (MAX_ROW, 2)));
+        assert_eq!(parse_area("Data!$3:$4"), area("Data", (2, 0), (3, MAX_COL)));
+        assert_eq!(
+            parse_area("$Sheet1.$A$1:.$B$5"),
+            area("Sheet1", (0, 0), (4, 1))
+        );
+        assert_eq!(parse_area("0.07"), None);
+        assert_eq!(parse_area("SUM(Sheet1!$A$1:$A$3)"), None);
+        assert_eq!(parse_area("Sheet1!$A$1,Sheet1!$C$1"), None);
+        assert_eq!(parse_area("Sheet1!#REF!"), None);
+    }
+
+    #[test]
+    fn test_parse_xlsx_names_and_find() {
+        let workbook = r#"<workbook><sheets>
+            <sheet name="Summary" sheetId="1"/><sheet name="Q1 &amp; Q2" sheetId="2"/>
+            </sheets><definedNames>
+            <definedName name="_xlnm._FilterDatabase" localSheetId="0" hidden="1">Summary!$A$1:$C$9</definedName>
+            <definedName name="Rate">Summary!$B$1</definedName>
+            <definedName name="Rate" localSheetId="1">'Q1 &amp; Q2'!$B$1</definedName>
+            <definedName name="Total" localSheetId="1">'Q1 &amp; Q2'!$C$20</definedName>
+            </definedNames></workbook>"#;
+        let names = parse_xlsx_names(workbook.as_bytes()).unwrap();
+        assert_eq!(names.len(), 3);
+        assert_eq!(names[0].scope_label(), "Workbook");
+        assert_eq!(names[1].scope.as_deref(), Some("Q1 & Q2"));
+        assert_eq!(names[1].refers_to, "'Q1 & Q2'!$B$1");
+
+        assert_eq!(find(&names, "rate", None), Some(&names[0]));
+        assert_eq!(find(&names, "Rate", Some("Q1 & Q2")), Some(&names[1]));
+        assert_eq!(find(&names, "'Q1 & Q2'!RATE", None), Some(&names[1]));
+        assert_eq!(find(&names, "Total", Some("Summary")), Some(&names[2]));
+        assert_eq!(find(&names, "Summary!Total", None), None);
+        assert_eq!(find(&names, "Missing", None), None);
+    }
+}
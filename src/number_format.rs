@@ -0,0 +1,667 @@
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, Timelike};
+
+/// An Excel number format code such as `#,##0.00`, `0.0%`, `"$"#,##0` or `d-mmm-yy`,
+/// parsed once and applied to cell values
+#[derive(Debug, Clone, PartialEq)]
+pub struct NumberFormat {
+    code: String,
+    /// Up to four `;`-separated sections: positive, negative, zero and text
+    sections: Vec<Vec<Token>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Literal(String),
+    General,
+    /// `@`, the cell's text
+    Text,
+    /// Digit placeholder: `0` (always shown), `#` (only significant digits) or `?` (space instead)
+    Digit(char),
+    Point,
+    /// A comma: thousands separators between digits, a scale of 1,000 after them
+    Comma,
+    Percent,
+    /// `E+` shows the exponent's sign always, `E-` only when negative
+    Exponent {
+        always_sign: bool,
+    },
+    Year(usize),
+    Month(usize),
+    Minute(usize),
+    Day(usize),
+    Hour(usize),
+    Second(usize),
+    /// Digits of fractional seconds, e.g. the `.00` of `ss.00`
+    SubSecond(usize),
+    /// Total elapsed hours, minutes or seconds: `[h]`, `[mm]`, `[ss]`
+    Elapsed(char, usize),
+    /// `AM/PM` or `A/P`, with the case as written
+    AmPm {
+        short: bool,
+        lower: bool,
+    },
+}
+
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+impl NumberFormat {
+    pub fn parse(code: &str) -> Self {
+        Self {
+            code: code.to_string(),
+            sections: split_sections(code).into_iter().map(tokenize).collect(),
+        }
+    }
+
+    /// Format code of one of Excel's built-in formats (ids 0-49). The locale-dependent
+    /// short date (14) and date-time (22) are shown in ISO order.
+    pub fn builtin(id: u32) -> Option<&'static str> {
+        Some(match id {
+            0 => "General",
+            1 => "0",
+            2 => "0.00",
+            3 => "#,##0",
+            4 => "#,##0.00",
+            5 => "\"$\"#,##0_);(\"$\"#,##0)",
+            6 => "\"$\"#,##0_);[Red](\"$\"#,##0)",
+            7 => "\"$\"#,##0.00_);(\"$\"#,##0.00)",
+            8 => "\"$\"#,##0.00_);[Red](\"$\"#,##0.00)",
+            9 => "0%",
+            10 => "0.00%",
+            11 => "0.00E+00",
+            12 => "# ?/?",
+            13 => "# ??/??",
+            14 => "yyyy-mm-dd",
+            15 => "d-mmm-yy",
+            16 => "d-mmm",
+            17 => "mmm-yy",
+            18 => "h:mm AM/PM",
+            19 => "h:mm:ss AM/PM",
+            20 => "h:mm",
+            21 => "h:mm:ss",
+            22 => "yyyy-mm-dd h:mm",
+            37 => "#,##0 ;(#,##0)",
+            38 => "#,##0 ;[Red](#,##0)",
+            39 => "#,##0.00;(#,##0.00)",
+            40 => "#,##0.00;[Red](#,##0.00)",
+            45 => "mm:ss",
+            46 => "[h]:mm:ss",
+            47 => "mm:ss.0",
+            48 => "##0.0E+0",
+            49 => "@",
+            _ => return None,
+        })
+    }
+
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    /// `General` (or an empty code), which leaves values as they are
+    pub fn is_general(&self) -> bool {
+        match self.sections.as_slice() {
+            [] => true,
+            [tokens] => matches!(tokens.as_slice(), [] | [Token::General]),
+            _ => false,
+        }
+    }
+
+    /// Renders a number (a date serial for date formats) as Excel would. Serials
+    /// count from 1904-01-01 in workbooks using the 1904 date system.
+    pub fn format(&self, value: f64, date1904: bool) -> String {
+        let section = |index: usize| self.sections.get(index).map_or(&[][..], Vec::as_slice);
+        let (tokens, value, minus) = match self.sections.len() {
+            0 | 1 => (section(0), value, value < 0.0 && !is_date(section(0))),
+            // The negative section writes its own sign (or parentheses)
+            _ if value < 0.0 => (section(1), -value, false),
+            n if value == 0.0 && n >= 3 => (section(2), value, false),
+            _ => (section(0), value, false),
+        };
+
+        let text = if is_date(tokens) {
+            format_date(tokens, value, date1904)
+        } else {
+            format_number(tokens, value.abs())
+        };
+        if minus && text.chars().any(|c| c.is_ascii_digit() && c != '0') {
+            format!("-{text}")
+        } else {
+            text
+        }
+    }
+}
+
+/// Splits a format code on `;`, except inside quotes, brackets or after `\`
+fn split_sections(code: &str) -> Vec<&str> {
+    let mut sections = Vec::new();
+    let (mut start, mut quoted, mut bracketed, mut escaped) = (0, false, false, false);
+    for (i, c) in code.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if !quoted => escaped = true,
+            '"' => quoted = !quoted,
+            '[' if !quoted => bracketed = true,
+            ']' if !quoted => bracketed = false,
+            ';' if !quoted && !bracketed => {
+                sections.push(&code[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    sections.push(&code[start..]);
+    sections
+}
+
+fn tokenize(section: &str) -> Vec<Token> {
+    let chars: Vec<char> = section.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    // Length of the run of `c` (case-insensitive) starting at `i`
+    let run = |i: usize, c: char| {
+        chars[i..]
+            .iter()
+            .take_while(|ch| ch.eq_ignore_ascii_case(&c))
+            .count()
+    };
+    let starts_with = |i: usize, text: &str| {
+        let text: Vec<char> = text.chars().collect();
+        chars.len() >= i + text.len()
+            && chars[i..i + text.len()]
+                .iter()
+                .zip(&text)
+                .all(|(a, b)| a.eq_ignore_ascii_case(b))
+    };
+
+    while i < chars.len() {
+        let c = chars[i];
+        let (token, len) = match c {
+            '"' => {
+                let text: String = chars[i + 1..].iter().take_while(|&&c| c != '"').collect();
+                let len = text.chars().count() + 2;
+                (Token::Literal(text), len)
+            }
+            '\\' => match chars.get(i + 1) {
+                Some(c) => (Token::Literal(c.to_string()), 2),
+                None => (Token::Literal(String::new()), 1),
+            },
+            // Padding as wide as the next character, e.g. `_)` to line up with negatives
+            '_' => (Token::Literal(" ".into()), 2),
+            // Repeat-to-fill: there's no column width to fill here
+            '*' => (Token::Literal(String::new()), 2),
+            '[' => {
+                let inner: String = chars[i + 1..].iter().take_while(|&&c| c != ']').collect();
+                let len = inner.chars().count() + 2;
+                (bracket_token(&inner), len)
+            }
+            '0' | '#' | '?' => (Token::Digit(c), 1),
+            '.' if tokens.iter().any(|t| matches!(t, Token::Second(_))) && run(i + 1, '0') > 0 => {
+                let digits = run(i + 1, '0');
+                (Token::SubSecond(digits), digits + 1)
+            }
+            '.' => (Token::Point, 1),
+            ',' => (Token::Comma, 1),
+            '%' => (Token::Percent, 1),
+            '@' => (Token::Text, 1),
+            'E' | 'e' if matches!(chars.get(i + 1), Some('+' | '-')) => (
+                Token::Exponent {
+                    always_sign: chars[i + 1] == '+',
+                },
+                2,
+            ),
+            _ if starts_with(i, "General") => (Token::General, 7),
+            _ if starts_with(i, "AM/PM") => (
+                Token::AmPm {
+                    short: false,
+                    lower: c.is_ascii_lowercase(),
+                },
+                5,
+            ),
+            _ if starts_with(i, "A/P") => (
+                Token::AmPm {
+                    short: true,
+                    lower: c.is_ascii_lowercase(),
+                },
+                3,
+            ),
+            'y' | 'Y' => (Token::Year(run(i, 'y')), run(i, 'y')),
+            'm' | 'M' => (Token::Month(run(i, 'm')), run(i, 'm')),
+            'd' | 'D' => (Token::Day(run(i, 'd')), run(i, 'd')),
+            'h' | 'H' => (Token::Hour(run(i, 'h')), run(i, 'h')),
+            's' | 'S' => (Token::Second(run(i, 's')), run(i, 's')),
+            _ => (Token::Literal(c.to_string()), 1),
+        };
+        tokens.push(token);
+        i += len;
+    }
+    resolve_minutes(&mut tokens);
+    tokens
+}
+
+/// `[Red]` and conditions like `[>=100]` are dropped; `[$€-407]` is a currency symbol
+fn bracket_token(inner: &str) -> Token {
+    let lower = inner.to_ascii_lowercase();
+    if let Some(unit @ ('h' | 'm' | 's')) = lower.chars().next()
+        && lower.chars().all(|c| c == unit)
+    {
+        return Token::Elapsed(unit, lower.len());
+    }
+    match inner.strip_prefix('$') {
+        Some(currency) => Token::Literal(currency.split('-').next().unwrap_or("").to_string()),
+        None => Token::Literal(String::new()),
+    }
+}
+
+/// `m`/`mm` means minutes right after hours or right before seconds
+fn resolve_minutes(tokens: &mut [Token]) {
+    let significant: Vec<usize> = (0..tokens.len())
+        .filter(|&i| !matches!(tokens[i], Token::Literal(_)))
+        .collect();
+    for (n, &i) in significant.iter().enumerate() {
+        let Token::Month(len @ (1 | 2)) = tokens[i] else {
+            continue;
+        };
+        let after_hours = n > 0
+            && matches!(
+                tokens[significant[n - 1]],
+                Token::Hour(_) | Token::Elapsed('h', _)
+            );
+        let before_seconds = significant
+            .get(n + 1)
+            .is_some_and(|&next| matches!(tokens[next], Token::Second(_) | Token::Elapsed('s', _)));
+        if after_hours || before_seconds {
+            tokens[i] = Token::Minute(len);
+        }
+    }
+}
+
+fn is_date(tokens: &[Token]) -> bool {
+    tokens.iter().any(|token| {
+        matches!(
+            token,
+            Token::Year(_)
+                | Token::Month(_)
+                | Token::Minute(_)
+                | Token::Day(_)
+                | Token::Hour(_)
+                | Token::Second(_)
+                | Token::Elapsed(..)
+        )
+    })
+}
+
+/// Excel's General format: integers as is, others with up to 10 significant digits
+pub fn format_general(value: f64) -> String {
+    let abs = value.abs();
+    if abs != 0.0 && !(1e-9..1e11).contains(&abs) {
+        let text = format!("{value:.5E}");
+        let (mantissa, exponent) = text.split_once('E').unwrap_or((&text, "0"));
+        let mantissa = mantissa.trim_end_matches('0').trim_end_matches('.');
+        let exponent: i32 = exponent.parse().unwrap_or(0);
+        let sign = if exponent < 0 { '-' } else { '+' };
+        return format!("{mantissa}E{sign}{:02}", exponent.abs());
+    }
+    let int_digits = if abs < 1.0 {
+        1
+    } else {
+        abs.log10().floor() as usize + 1
+    };
+    let decimals = 10usize.saturating_sub(int_digits);
+    let text = format!("{value:.decimals$}");
+    if text.contains('.') {
+        text.trim_end_matches('0').trim_end_matches('.').to_string()
+    } else {
+        text
+    }
+}
+
+fn format_number(tokens: &[Token], value: f64) -> String {
+    let point = tokens.iter().position(|t| *t == Token::Point);
+    let exponent = tokens
+        .iter()
+        .position(|t| matches!(t, Token::Exponent { .. }));
+    let mantissa_end = exponent.unwrap_or(tokens.len());
+    let int_end = point.unwrap_or(mantissa_end);
+    let digit_positions = |range: std::ops::Range<usize>| -> Vec<usize> {
+        range
+            .filter(|&i| matches!(tokens[i], Token::Digit(_)))
+            .collect()
+    };
+    let int_places = digit_positions(0..int_end);
+    let frac_places = digit_positions(int_end..mantissa_end);
+    let exp_places = digit_positions(mantissa_end..tokens.len());
+
+    if int_places.is_empty() && frac_places.is_empty() {
+        // Only text (and perhaps General): no digits to lay out
+        return tokens
+            .iter()
+            .map(|token| match token {
+                Token::Literal(text) => text.clone(),
+                Token::General | Token::Text => format_general(value),
+                Token::Percent => "%".into(),
+                _ => String::new(),
+            })
+            .collect();
+    }
+
+    // Commas right after the last integer digit divide by 1,000 each; commas
+    // between integer digits turn on thousands separators
+    let last_int = int_places.last().copied();
+    let scaling_commas = last_int.map_or(0, |last| {
+        tokens[last + 1..int_end]
+            .iter()
+            .take_while(|t| **t == Token::Comma)
+            .count()
+    });
+    let grouping = match (int_places.first(), last_int) {
+        (Some(&first), Some(last)) => tokens[first..last].contains(&Token::Comma),
+        _ => false,
+    };
+    let percents = tokens.iter().filter(|t| **t == Token::Percent).count();
+    let mut value = value * 100f64.powi(percents as i32) / 1000f64.powi(scaling_commas as i32);
+
+    let mut exp_value = 0i32;
+    if exponent.is_some() && value != 0.0 {
+        // `##0.0E+0` keeps exponents a multiple of the integer digit count (engineering notation)
+        let step = int_places.len().max(1) as i32;
+        exp_value = value.log10().floor() as i32;
+        if step > 1 {
+            exp_value -= exp_value.rem_euclid(step);
+        }
+        value /= 10f64.powi(exp_value);
+        if format!("{value:.*}", frac_places.len()).starts_with("10") && step == 1 {
+            value /= 10.0;
+            exp_value += 1;
+        }
+    }
+
+    let rounded = format!("{value:.*}", frac_places.len());
+    let (int_text, frac_text) = rounded.split_once('.').unwrap_or((&rounded, ""));
+    let min_int_digits = int_places
+        .iter()
+        .filter(|&&i| tokens[i] == Token::Digit('0'))
+        .count();
+    let int_digits = if int_text == "0" { "" } else { int_text };
+    let int_digits = format!("{int_digits:0>min_int_digits$}");
+
+    let mut out = String::new();
+    for (i, token) in tokens.iter().enumerate() {
+        match token {
+            Token::Literal(text) => out.push_str(text),
+            Token::Point => out.push('.'),
+            Token::Percent => out.push('%'),
+            Token::Exponent { always_sign } => {
+                out.push('E');
+                if exp_value < 0 {
+                    out.push('-');
+                } else if *always_sign {
+                    out.push('+');
+                }
+            }
+            Token::Digit(placeholder) => {
+                if let Some(n) = int_places.iter().position(|&p| p == i) {
+                    out.push_str(&integer_digit(
+                        &int_digits,
+                        n,
+                        int_places.len(),
+                        *placeholder,
+                        grouping,
+                    ));
+                } else if let Some(n) = frac_places.iter().position(|&p| p == i) {
+                    let trailing_zero = frac_text[n..].chars().all(|c| c == '0');
+                    match (frac_text.as_bytes().get(n), placeholder) {
+                        (Some(_), '#') if trailing_zero => {}
+                        (Some(_), '?') if trailing_zero => out.push(' '),
+                        (Some(&digit), _) => out.push(digit as char),
+                        (None, _) => {}
+                    }
+                } else if let Some(n) = exp_places.iter().position(|&p| p == i) {
+                    let digits = format!("{:0>width$}", exp_value.abs(), width = exp_places.len());
+                    if n == 0 {
+                        out.push_str(&digits[..=digits.len() - exp_places.len()]);
+                    } else {
+                        let index = digits.len() - exp_places.len() + n;
+                        out.push_str(&digits[index..=index]);
+                    }
+                }
+            }
+            Token::General | Token::Text => out.push_str(&format_general(value)),
+            _ => {}
+        }
+    }
+    out
+}
+
+/// Text for integer placeholder `n` of `count`: digits fill placeholders from the
+/// right and the first one takes any extra leading digits
+fn integer_digit(
+    digits: &str,
+    n: usize,
+    count: usize,
+    placeholder: char,
+    grouping: bool,
+) -> String {
+    if grouping {
+        // Separators make per-placeholder layout moot; the first placeholder shows it all
+        return if n == 0 {
+            group_thousands(digits)
+        } else {
+            String::new()
+        };
+    }
+    let from_right = count - 1 - n;
+    let len = digits.len();
+    if n == 0 && len > count {
+        return digits[..len - from_right].to_string();
+    }
+    match len.checked_sub(from_right + 1) {
+        Some(index) => digits[index..=index].to_string(),
+        None => match placeholder {
+            '0' => "0".into(),
+            '?' => " ".into(),
+            _ => String::new(),
+        },
+    }
+}
+
+fn group_thousands(digits: &str) -> String {
+    let mut out = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Days between day 0 of the 1900 date system and day 0 (1904-01-01) of the 1904 one
+pub const DATE1904_OFFSET: f64 = 1462.0;
+
+/// Date and time of an Excel serial in the 1900 date system
+pub fn serial_to_datetime(serial: f64) -> Option<NaiveDateTime> {
+    let days = serial.floor() as i64;
+    // Excel counts the phantom 1900-02-29 as day 60
+    let days = if days > 60 { days - 1 } else { days };
+    let date = NaiveDate::from_ymd_opt(1899, 12, 31)?.checked_add_signed(Duration::days(days))?;
+    let millis = ((serial - serial.floor()) * 86_400_000.0).round() as i64;
+    date.and_hms_opt(0, 0, 0)?
+        .checked_add_signed(Duration::milliseconds(millis))
+}
+
+fn format_date(tokens: &[Token], serial: f64, date1904: bool) -> String {
+    if serial < 0.0 {
+        // Excel can't show negative dates or times either
+        return "#".repeat(8);
+    }
+    // Round to the precision shown, so 59.6 seconds becomes the next minute
+    let sub_second_digits = tokens
+        .iter()
+        .find_map(|t| match t {
+            Token::SubSecond(digits) => Some((*digits).min(3)),
+            _ => None,
+        })
+        .unwrap_or(0);
+    let unit = 86_400.0 * 10f64.powi(sub_second_digits as i32);
+    let serial = (serial * unit).round() / unit;
+    let offset = if date1904 { DATE1904_OFFSET } else { 0.0 };
+    let Some(datetime) = serial_to_datetime(serial + offset) else {
+        return "#".repeat(8);
+    };
+    let twelve_hour = tokens.iter().any(|t| matches!(t, Token::AmPm { .. }));
+
+    let mut out = String::new();
+    for token in tokens {
+        match token {
+            Token::Literal(text) => out.push_str(text),
+            Token::Year(len) if *len <= 2 => out.push_str(&format!("{:02}", datetime.year() % 100)),
+            Token::Year(_) => out.push_str(&format!("{:04}", datetime.year())),
+            Token::Month(len) => {
+                let name = MONTHS[datetime.month0() as usize];
+                match len {
+                    1 => out.push_str(&datetime.month().to_string()),
+                    2 => out.push_str(&format!("{:02}", datetime.month())),
+                    3 => out.push_str(&name[..3]),
+                    5 => out.push_str(&name[..1]),
+                    _ => out.push_str(name),
+                }
+            }
+            Token::Day(len) => match len {
+                1 => out.push_str(&datetime.day().to_string()),
+                2 => out.push_str(&format!("{:02}", datetime.day())),
+                3 => out.push_str(&datetime.format("%a").to_string()),
+                _ => out.push_str(&datetime.format("%A").to_string()),
+            },
+            Token::Hour(len) => {
+                let hour = match datetime.hour() {
+                    h if !twelve_hour => h,
+                    0 => 12,
+                    h if h > 12 => h - 12,
+                    h => h,
+                };
+                out.push_str(&pad(hour as i64, *len));
+            }
+            Token::Minute(len) => out.push_str(&pad(datetime.minute() as i64, *len)),
+            Token::Second(len) => out.push_str(&pad(datetime.second() as i64, *len)),
+            Token::SubSecond(digits) => {
+                let millis = format!("{:03}", datetime.nanosecond() / 1_000_000);
+                out.push('.');
+                out.push_str(&format!("{millis:0<digits$}")[..*digits]);
+            }
+            Token::Elapsed(unit, len) => {
+                let per_day = match unit {
+                    'h' => 24.0,
+                    'm' => 1440.0,
+                    _ => 86_400.0,
+                };
+                out.push_str(&pad((serial * per_day).floor() as i64, *len));
+            }
+            Token::AmPm { short, lower } => {
+                let text = match (datetime.hour() < 12, short) {
+                    (true, false) => "AM",
+                    (false, false) => "PM",
+                    (true, true) => "A",
+                    (false, true) => "P",
+                };
+                out.push_str(&if *lower {
+                    text.to_ascii_lowercase()
+                } else {
+                    text.to_string()
+                });
+            }
+            Token::Point => out.push('.'),
+            Token::Comma => out.push(','),
+            Token::Digit('0') => out.push('0'),
+            _ => {}
+        }
+    }
+    out
+}
+
+fn pad(value: i64, len: usize) -> String {
+    if len >= 2 {
+        format!("{value:02}")
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fmt(code: &str, value: f64) -> String {
+        NumberFormat::parse(code).format(value, false)
+    }
+
+    #[test]
+    fn test_number_formats() {
+        assert_eq!(fmt("0.00", 1.23456), "1.23");
+        assert_eq!(fmt("0.0000", 0.07126), "0.0713");
+        assert_eq!(fmt("#,##0", 1234567.8), "1,234,568");
+        assert_eq!(fmt("#,##0.00", -1234.5), "-1,234.50");
+        assert_eq!(fmt("0%", 0.25), "25%");
+        assert_eq!(fmt("0.0%", 0.0126), "1.3%");
+        assert_eq!(fmt("0.00E+00", 12345.0), "1.23E+04");
+        assert_eq!(fmt("0.00E+00", 0.00012), "1.20E-04");
+        assert_eq!(fmt("##0.0E+0", 12345.0), "12.3E+3");
+        assert_eq!(fmt("#.##", 0.5), ".5");
+        assert_eq!(fmt("000-0000", 5551234.0), "555-1234");
+        assert_eq!(fmt("#,##0,\"K\"", 1234567.0), "1,235K");
+        assert_eq!(fmt("0.0?", 1.5), "1.5 ");
+        assert_eq!(fmt("General", 0.1 + 0.2), "0.3");
+        assert_eq!(fmt("@", 12.0), "12");
+    }
+
+    #[test]
+    fn test_currency_and_sections() {
+        assert_eq!(fmt("\"$\"#,##0.00", 1234.5), "$1,234.50");
+        assert_eq!(fmt("\"$\"#,##0.00", -5.0), "-$5.00");
+        assert_eq!(fmt("[$€-407] #,##0.00", 99.9), "€ 99.90");
+        assert_eq!(fmt("\\$#,##0_);[Red](\\$#,##0)", -1500.0), "($1,500)");
+        assert_eq!(fmt("\\$#,##0_);[Red](\\$#,##0)", 1500.0), "$1,500 ");
+        assert_eq!(fmt("0.00;-0.00;\"zero\"", 0.0), "zero");
+        assert_eq!(fmt("0;;", -3.0), "");
+        assert_eq!(NumberFormat::builtin(9), Some("0%"));
+        assert!(NumberFormat::parse("General").is_general());
+        assert!(!NumberFormat::parse("0.00").is_general());
+    }
+
+    #[test]
+    fn test_date_formats() {
+        // 2024-03-05 14:07:09
+        let serial = 45356.0 + (14.0 * 3600.0 + 7.0 * 60.0 + 9.0) / 86400.0;
+        assert!(is_date(&NumberFormat::parse("d-mmm-yy").sections[0]));
+        assert_eq!(fmt("yyyy-mm-dd", serial), "2024-03-05");
+        assert_eq!(fmt("d-mmm-yy", serial), "5-Mar-24");
+        assert_eq!(fmt("dddd, mmmm d, yyyy", serial), "Tuesday, March 5, 2024");
+        assert_eq!(fmt("mm/dd/yyyy hh:mm", serial), "03/05/2024 14:07");
+        assert_eq!(fmt("h:mm:ss AM/PM", serial), "2:07:09 PM");
+        assert_eq!(fmt("h:mm a/p", 0.25), "6:00 a");
+        assert_eq!(fmt("[h]:mm:ss", 1.5), "36:00:00");
+        assert_eq!(fmt("mm:ss.00", 1.5 / 86400.0), "00:01.50");
+        assert_eq!(fmt("yyyy-mm-dd", -1.0), "########");
+    }
+
+    #[test]
+    fn test_date1904_formats() {
+        let format = NumberFormat::parse("yyyy-mm-dd h:mm");
+        assert_eq!(format.format(0.0, true), "1904-01-01 0:00");
+        assert_eq!(format.format(43904.5, true), "2024-03-15 12:00");
+        assert_eq!(format.format(45366.5, false), "2024-03-15 12:00");
+        // Elapsed time doesn't depend on the epoch
+        assert_eq!(NumberFormat::parse("[h]:mm").format(1.5, true), "36:00");
+    }
+}
@@ -0,0 +1,196 @@
+use crate::xlsb::{self, Records};
+use crate::xml::attribute;
+use anyhow::Result;
+use quick_xml::events::Event;
+use std::collections::HashMap;
+use std::io::{BufReader, Read, Seek};
+use zip::ZipArchive;
+
+/// A relationship from one part of an xlsx/xlsb package to another, or to a URL
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Relationship {
+    /// Last segment of the relationship type, e.g. `worksheet`, `comments` or `hyperlink`
+    pub kind: String,
+    /// Package path of the target part, or the URL of an external target
+    pub target: String,
+    pub external: bool,
+}
+
+/// Relationships of `part` (e.g. `xl/worksheets/sheet1.xml`), keyed by id.
+/// A part without relationships has none.
+pub fn relationships<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    part: &str,
+) -> Result<HashMap<String, Relationship>> {
+    let (dir, file) = part.rsplit_once('/').unwrap_or(("", part));
+    let rels_path = if dir.is_empty() {
+        format!("_rels/{file}.rels")
+    } else {
+        format!("{dir}/_rels/{file}.rels")
+    };
+    let Ok(rels) = archive.by_name(&rels_path) else {
+        return Ok(HashMap::new());
+    };
+
+    let mut xml = quick_xml::Reader::from_reader(BufReader::new(rels));
+    let mut buf = Vec::new();
+    let mut relationships = HashMap::new();
+    loop {
+        buf.clear();
+        match xml.read_event_into(&mut buf)? {
+            Event::Start(e) | Event::Empty(e) if e.local_name().as_ref() == b"Relationship" => {
+                let (Some(id), Some(target)) = (attribute(&e, "Id")?, attribute(&e, "Target")?)
+                else {
+                    continue;
+                };
+                let external = attribute(&e, "TargetMode")?.as_deref() == Some("External");
+                let kind = attribute(&e, "Type")?
+                    .and_then(|kind| kind.rsplit('/').next().map(str::to_string))
+                    .unwrap_or_default();
+                let target = if external {
+                    target
+                } else {
+                    resolve(dir, &target)
+                };
+                relationships.insert(
+                    id,
+                    Relationship {
+                        kind,
+                        target,
+                        external,
+                    },
+                );
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(relationships)
+}
+
+/// Package path of `target`, relative to directory `dir` unless it starts with `/`
+fn resolve(dir: &str, target: &str) -> String {
+    let mut path: Vec<&str> = match target.strip_prefix('/') {
+        Some(_) => Vec::new(),
+        None => dir.split('/').filter(|s| !s.is_empty()).collect(),
+    };
+    for segment in target.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                path.pop();
+            }
+            segment => path.push(segment),
+        }
+    }
+    path.join("/")
+}
+
+/// Package path of each sheet of an xlsx or xlsb workbook, e.g. `Sales` → `xl/worksheets/sheet1.xml`
+pub fn sheet_paths<R: Read + Seek>(archive: &mut ZipArchive<R>) -> Result<HashMap<String, String>> {
+    let binary = archive.index_for_name("xl/workbook.xml").is_none();
+    let workbook_part = if binary {
+        "xl/workbook.bin"
+    } else {
+        "xl/workbook.xml"
+    };
+    let relationships = relationships(archive, workbook_part)?;
+    let sheets = if binary {
+        xlsb_sheets(archive.by_name(workbook_part)?)?
+    } else {
+        xlsx_sheets(archive.by_name(workbook_part)?)?
+    };
+    Ok(sheets
+        .into_iter()
+        .filter_map(|(name, id)| Some((name, relationships.get(&id)?.target.clone())))
+        .collect())
+}
+
+/// Whether an xlsx or xlsb workbook uses the 1904 date system, in which serials
+/// count from 1904-01-01
+pub fn date1904<R: Read + Seek>(archive: &mut ZipArchive<R>) -> Result<bool> {
+    if archive.index_for_name("xl/workbook.xml").is_none() {
+        let mut records = Records::new(BufReader::new(archive.by_name("xl/workbook.bin")?));
+        while let Some((typ, data)) = records.next_record()? {
+            if typ == xlsb::WORKBOOK_PROPERTIES {
+                // f1904 is the lowest bit of the flags
+                return Ok(xlsb::read_u32(data, 0).is_some_and(|flags| flags & 1 != 0));
+            }
+        }
+        return Ok(false);
+    }
+
+    let workbook = archive.by_name("xl/workbook.xml")?;
+    let mut xml = quick_xml::Reader::from_reader(BufReader::new(workbook));
+    let mut buf = Vec::new();
+    loop {
+        buf.clear();
+        match xml.read_event_into(&mut buf)? {
+            Event::Start(e) | Event::Empty(e) if e.local_name().as_ref() == b"workbookPr" => {
+                let date1904 = attribute(&e, "date1904")?;
+                return Ok(matches!(date1904.as_deref(), Some("1" | "true")));
+            }
+            Event::Eof => return Ok(false),
+            _ => {}
+        }
+    }
+}
+
+/// Sheet names and relationship ids from `xl/workbook.xml`
+fn xlsx_sheets(workbook: impl Read) -> Result<Vec<(String, String)>> {
+    let mut xml = quick_xml::Reader::from_reader(BufReader::new(workbook));
+    let mut buf = Vec::new();
+    let mut sheets = Vec::new();
+    loop {
+        buf.clear();
+        match xml.read_event_into(&mut buf)? {
+            Event::Start(e) | Event::Empty(e) if e.local_name().as_ref() == b"sheet" => {
+                if let (Some(name), Some(id)) = (attribute(&e, "name")?, attribute(&e, "r:id")?) {
+                    sheets.push((name, id));
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(sheets)
+}
+
+/// Sheet names and relationship ids from the BrtBundleSh records of `xl/workbook.bin`
+fn xlsb_sheets(workbook: impl Read) -> Result<Vec<(String, String)>> {
+    let mut records = Records::new(BufReader::new(workbook));
+    let mut sheets = Vec::new();
+    while let Some((typ, data)) = records.next_record()? {
+        if typ != xlsb::BUNDLE_SHEET {
+            continue;
+        }
+        // State and tab id come first
+        if let Some((id, end)) = xlsb::wide_string(data, 8)
+            && let Some((name, _)) = xlsb::wide_string(data, end)
+        {
+            sheets.push((name, id));
+        }
+    }
+    Ok(sheets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve() {
+        assert_eq!(
+            resolve("xl", "worksheets/sheet1.xml"),
+            "xl/worksheets/sheet1.xml"
+        );
+        assert_eq!(
+            resolve("xl/worksheets", "../comments1.xml"),
+            "xl/comments1.xml"
+        );
+        assert_eq!(
+            resolve("xl", "/xl/worksheets/sheet2.xml"),
+            "xl/worksheets/sheet2.xml"
+        );
+    }
+}
@@ -0,0 +1,233 @@
+use crate::arrow_convert::batch_to_rows;
+use crate::styles::CellFormats;
+use crate::workbook::{CellValue, HEADER_ORIGIN, SheetData};
+use anyhow::{Context, Result};
+use bytes::Bytes;
+use parquet::arrow::arrow_reader::{
+    ArrowReaderMetadata, ArrowReaderOptions, ParquetRecordBatchReaderBuilder,
+};
+use parquet::file::reader::ChunkReader;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+/// Leading (and trailing) bytes of every Parquet file
+pub const PARQUET_MAGIC: [u8; 4] = *b"PAR1";
+/// Upper bound on rows decoded per record batch
+const MAX_BATCH_ROWS: usize = 8192;
+
+/// Whether files with this extension are opened as Parquet
+pub fn is_parquet_extension(ext: &str) -> bool {
+    matches!(ext.to_ascii_lowercase().as_str(), "parquet" | "parq" | "pq")
+}
+
+/// Where the Parquet bytes come from
+enum ParquetInput {
+    File(PathBuf),
+    Memory(Bytes),
+}
+
+/// A Parquet file shown as a single sheet. Only the footer metadata is read up
+/// front; rows are decoded on demand from the row groups that cover them.
+pub struct ParquetFile {
+    name: String,
+    input: ParquetInput,
+    metadata: ArrowReaderMetadata,
+    pub headers: Vec<String>,
+}
+
+impl ParquetFile {
+    pub fn open(path: &Path, name: &str) -> Result<Self> {
+        let file = File::open(path)?;
+        let metadata = Self::load_metadata(&file)?;
+        Ok(Self::new(
+            name,
+            ParquetInput::File(path.to_path_buf()),
+            metadata,
+        ))
+    }
+
+    /// Parquet data already in memory (e.g. read from stdin)
+    pub fn from_bytes(bytes: Vec<u8>, name: &str) -> Result<Self> {
+        let bytes = Bytes::from(bytes);
+        let metadata = Self::load_metadata(&bytes)?;
+        Ok(Self::new(name, ParquetInput::Memory(bytes), metadata))
+    }
+
+    fn load_metadata(reader: &impl ChunkReader) -> Result<ArrowReaderMetadata> {
+        // The page index lets row offsets skip whole pages instead of decoding them
+        let options = ArrowReaderOptions::new().with_page_index(true);
+        ArrowReaderMetadata::load(reader, options).context("Failed to read Parquet metadata")
+    }
+
+    fn new(name: &str, input: ParquetInput, metadata: ArrowReaderMetadata) -> Self {
+        let headers = metadata
+            .schema()
+            .fields()
+            .iter()
+            .map(|field| field.name().clone())
+            .collect();
+        Self {
+            name: name.to_string(),
+            input,
+            metadata,
+            headers,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn width(&self) -> usize {
+        self.headers.len()
+    }
+
+    pub fn height(&self) -> usize {
+        self.metadata.metadata().file_metadata().num_rows().max(0) as usize
+    }
+
+    /// Zero-indexed rows, decoded from the row groups overlapping the range
+    pub fn read_rows(&self, start: usize, count: usize) -> Result<Vec<Vec<CellValue>>> {
+        let end = (start + count).min(self.height());
+        if start >= end {
+            return Ok(Vec::new());
+        }
+
+        let mut row_groups = Vec::new();
+        let mut offset = 0;
+        let mut group_start = 0;
+        for (i, group) in self.metadata.metadata().row_groups().iter().enumerate() {
+            let group_end = group_start + group.num_rows().max(0) as usize;
+            if group_end > start && group_start < end {
+                if row_groups.is_empty() {
+                    offset = start - group_start;
+                }
+                row_groups.push(i);
+            }
+            group_start = group_end;
+        }
+
+        match &self.input {
+            ParquetInput::File(path) => {
+                self.read_row_groups(File::open(path)?, row_groups, offset, end - start)
+            }
+            ParquetInput::Memory(bytes) => {
+                self.read_row_groups(bytes.clone(), row_groups, offset, end - start)
+            }
+        }
+    }
+
+    fn read_row_groups<T: ChunkReader + 'static>(
+        &self,
+        input: T,
+        row_groups: Vec<usize>,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<Vec<CellValue>>> {
+        let reader =
+            ParquetRecordBatchReaderBuilder::new_with_metadata(input, self.metadata.clone())
+                .with_row_groups(row_groups)
+                .with_offset(offset)
+                .with_limit(limit)
+                .with_batch_size(limit.clamp(1, MAX_BATCH_ROWS))
+                .build()?;
+
+        let mut rows = Vec::with_capacity(limit);
+        for batch in reader {
+            rows.extend(batch_to_rows(&batch?));
+        }
+        Ok(rows)
+    }
+
+    /// Decodes every row into memory
+    pub fn to_sheet_data(&self) -> Result<SheetData> {
+        let rows = self.read_rows(0, self.height())?;
+        let (width, height) = (self.width(), rows.len());
+        Ok(SheetData {
+            headers: self.headers.clone(),
+            rows,
+            formulas: vec![vec![None; width]; height],
+            merges: Vec::new(),
+            formats: CellFormats::default(),
+            origin: HEADER_ORIGIN,
+            width,
+            height,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::{
+        ArrayRef, BooleanArray, Decimal128Array, Int64Array, RecordBatch, StringArray,
+        TimestampMillisecondArray,
+    };
+    use parquet::arrow::ArrowWriter;
+    use parquet::file::properties::WriterProperties;
+    use std::sync::Arc;
+
+    /// 250 rows written in row groups of 100
+    fn sample_parquet() -> Vec<u8> {
+        let ids: ArrayRef = Arc::new(Int64Array::from_iter_values(0..250));
+        let names: ArrayRef = Arc::new(StringArray::from_iter(
+            (0..250).map(|i| (i % 10 != 0).then(|| format!("row {i}"))),
+        ));
+        let flags: ArrayRef = Arc::new(BooleanArray::from_iter((0..250).map(|i| Some(i % 2 == 0))));
+        let prices: ArrayRef = Arc::new(
+            Decimal128Array::from_iter_values((0..250).map(|i| i * 25))
+                .with_precision_and_scale(10, 2)
+                .unwrap(),
+        );
+        // 2024-01-15 12:00:00 UTC
+        let times: ArrayRef = Arc::new(TimestampMillisecondArray::from_iter_values(
+            std::iter::repeat_n(1_705_320_000_000, 250),
+        ));
+        let batch = RecordBatch::try_from_iter([
+            ("id", ids),
+            ("name", names),
+            ("flag", flags),
+            ("price", prices),
+            ("at", times),
+        ])
+        .unwrap();
+
+        let props = WriterProperties::builder()
+            .set_max_row_group_size(100)
+            .build();
+        let mut out = Vec::new();
+        let mut writer = ArrowWriter::try_new(&mut out, batch.schema(), Some(props)).unwrap();
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+        out
+    }
+
+    #[test]
+    fn test_parquet_metadata() {
+        let parquet = ParquetFile::from_bytes(sample_parquet(), "data").unwrap();
+        assert_eq!(parquet.headers, vec!["id", "name", "flag", "price", "at"]);
+        assert_eq!(parquet.height(), 250);
+        assert_eq!(parquet.metadata.metadata().num_row_groups(), 3);
+    }
+
+    #[test]
+    fn test_parquet_read_rows_across_row_groups() {
+        let parquet = ParquetFile::from_bytes(sample_parquet(), "data").unwrap();
+        let rows = parquet.read_rows(95, 10).unwrap();
+        assert_eq!(rows.len(), 10);
+        let ids: Vec<String> = rows.iter().map(|r| r[0].to_raw_string()).collect();
+        assert_eq!(ids.first().map(String::as_str), Some("95"));
+        assert_eq!(ids.last().map(String::as_str), Some("104"));
+
+        let row = &rows[5];
+        assert!(matches!(row[1], CellValue::Empty));
+        assert!(matches!(row[2], CellValue::Bool(true)));
+        assert!(matches!(row[3], CellValue::Float(f) if f == 25.0));
+        assert!(matches!(row[4], CellValue::DateTime(_)));
+        assert_eq!(row[4].to_raw_string(), "2024-01-15 12:00:00");
+
+        assert_eq!(parquet.read_rows(245, 10).unwrap().len(), 5);
+        assert!(parquet.read_rows(250, 10).unwrap().is_empty());
+        assert_eq!(parquet.to_sheet_data().unwrap().height, 250);
+    }
+}
@@ -0,0 +1,412 @@
+use crate::workbook::column_letter;
+use calamine::{Data, Range};
+use std::ops::Range as Span;
+
+/// Rows at the top of a region searched for its header
+const HEADER_SCAN_ROWS: usize = 10;
+
+/// A rectangular block of cells separated from the rest of the sheet by blank
+/// rows or columns. Coordinates are zero-based sheet positions, end inclusive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataRegion {
+    pub start: (u32, u32),
+    pub end: (u32, u32),
+    /// Sheet row guessed to hold the column names; rows above it are notes
+    pub header_row: u32,
+    /// Non-empty cells below the header row
+    pub data_cells: usize,
+}
+
+impl DataRegion {
+    /// A1-style address, e.g. `B4:F20`
+    pub fn address(&self) -> String {
+        format!(
+            "{}{}:{}{}",
+            column_letter(self.start.1 as usize),
+            self.start.0 + 1,
+            column_letter(self.end.1 as usize),
+            self.end.0 + 1
+        )
+    }
+}
+
+/// The part of a sheet shown after region detection, when that hides anything
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShownRegion {
+    /// Zero-based index among the sheet's regions
+    pub index: usize,
+    /// Number of regions found on the sheet
+    pub count: usize,
+    /// Rows at the top of the region skipped as notes above the header
+    pub note_rows: usize,
+}
+
+impl ShownRegion {
+    /// Tells what was left out, e.g. `region 2 of 3, 1 note row above the header
+    /// skipped (use --region/--list-regions, --header-row)`
+    pub fn note(&self) -> String {
+        let mut parts = Vec::new();
+        let mut hints = Vec::new();
+        if self.count > 1 {
+            parts.push(format!("region {} of {}", self.index + 1, self.count));
+            hints.push("--region/--list-regions");
+        }
+        if self.note_rows > 0 {
+            let plural = if self.note_rows == 1 { "" } else { "s" };
+            parts.push(format!(
+                "{} note row{plural} above the header skipped",
+                self.note_rows
+            ));
+            hints.push("--header-row");
+        }
+        format!("{} (use {})", parts.join(", "), hints.join(", "))
+    }
+}
+
+/// Index of the most table-like region: the one holding the most data
+pub fn best_region(regions: &[DataRegion]) -> Option<usize> {
+    regions
+        .iter()
+        .enumerate()
+        .rev()
+        .max_by_key(|(_, region)| region.data_cells)
+        .map(|(index, _)| index)
+}
+
+/// Splits the used part of a sheet into data regions, top to bottom, by cutting
+/// along fully blank rows and columns. Blocks on the same rows with a single
+/// blank spacer column between them stay one region.
+pub fn detect_regions(range: &Range<Data>) -> Vec<DataRegion> {
+    let (height, width) = range.get_size();
+    let grid = Grid {
+        filled: range.cells().map(|(_, _, cell)| !is_blank(cell)).collect(),
+        width,
+    };
+    let text: Vec<bool> = range
+        .cells()
+        .map(|(_, _, cell)| matches!(cell, Data::String(_)))
+        .collect();
+
+    let mut blocks = Vec::new();
+    grid.cut(0..height, 0..width, &mut blocks);
+    let mut blocks = join_side_by_side(blocks);
+    blocks.sort_by_key(|(rows, _)| rows.start);
+
+    // A blank spacer row inside a table shouldn't split it: a block sharing
+    // columns with the one above and no header of its own continues it
+    let mut merged: Vec<(Span<usize>, Span<usize>)> = Vec::new();
+    for (rows, cols) in blocks {
+        if let Some((prev_rows, prev_cols)) = merged.last_mut()
+            && overlaps(prev_cols, &cols)
+            && !grid.is_header(&text, rows.start, &cols)
+        {
+            prev_rows.end = rows.end;
+            *prev_cols = union(prev_cols, &cols);
+            continue;
+        }
+        merged.push((rows, cols));
+    }
+
+    let (first_row, first_col) = range.start().unwrap_or((0, 0));
+    merged
+        .into_iter()
+        .map(|(rows, cols)| {
+            let header = grid.find_header(&rows, &cols);
+            let data_cells = (header + 1..rows.end)
+                .map(|row| cols.clone().filter(|&col| grid.at(row, col)).count())
+                .sum();
+            DataRegion {
+                start: (first_row + rows.start as u32, first_col + cols.start as u32),
+                end: (
+                    first_row + rows.end as u32 - 1,
+                    first_col + cols.end as u32 - 1,
+                ),
+                header_row: first_row + header as u32,
+                data_cells,
+            }
+        })
+        .collect()
+}
+
+/// Joins blocks on the same rows that are only one blank column apart, which
+/// is a spacer inside a table rather than a gap between two tables
+fn join_side_by_side(
+    mut blocks: Vec<(Span<usize>, Span<usize>)>,
+) -> Vec<(Span<usize>, Span<usize>)> {
+    blocks.sort_by_key(|(rows, cols)| (rows.start, rows.end, cols.start));
+    let mut joined: Vec<(Span<usize>, Span<usize>)> = Vec::with_capacity(blocks.len());
+    for (rows, cols) in blocks {
+        if let Some((prev_rows, prev_cols)) = joined.last_mut()
+            && *prev_rows == rows
+            && cols.start == prev_cols.end + 1
+        {
+            prev_cols.end = cols.end;
+            continue;
+        }
+        joined.push((rows, cols));
+    }
+    joined
+}
+
+fn overlaps(a: &Span<usize>, b: &Span<usize>) -> bool {
+    a.start < b.end && b.start < a.end
+}
+
+fn union(a: &Span<usize>, b: &Span<usize>) -> Span<usize> {
+    a.start.min(b.start)..a.end.max(b.end)
+}
+
+fn is_blank(cell: &Data) -> bool {
+    match cell {
+        Data::Empty => true,
+        Data::String(s) => s.trim().is_empty(),
+        _ => false,
+    }
+}
+
+/// Which cells of the range hold a value, row-major
+struct Grid {
+    filled: Vec<bool>,
+    width: usize,
+}
+
+impl Grid {
+    fn at(&self, row: usize, col: usize) -> bool {
+        self.filled[row * self.width + col]
+    }
+
+    /// Recursive XY-cut: split on blank rows, then on blank columns, until
+    /// each block has neither
+    fn cut(&self, rows: Span<usize>, cols: Span<usize>, out: &mut Vec<(Span<usize>, Span<usize>)>) {
+        let bands = runs(
+            rows.clone()
+                .map(|row| cols.clone().any(|col| self.at(row, col))),
+            rows.start,
+        );
+        if bands.len() > 1 {
+            for band in bands {
+                self.cut(band, cols.clone(), out);
+            }
+            return;
+        }
+        let Some(band) = bands.into_iter().next() else {
+            return;
+        };
+
+        let blocks = runs(
+            cols.clone()
+                .map(|col| band.clone().any(|row| self.at(row, col))),
+            cols.start,
+        );
+        if blocks.len() > 1 {
+            for block in blocks {
+                self.cut(band.clone(), block, out);
+            }
+        } else if let Some(block) = blocks.into_iter().next() {
+            out.push((band, block));
+        }
+    }
+
+    /// The header row of a block: its first row, unless that is clearly a
+    /// title or note, filling no more than half of the columns. Then the first
+    /// row near the top that does fill more is taken.
+    fn find_header(&self, rows: &Span<usize>, cols: &Span<usize>) -> usize {
+        rows.clone()
+            .take(HEADER_SCAN_ROWS)
+            .find(|&row| self.filled_in(row, cols) * 2 > cols.len())
+            .unwrap_or(rows.start)
+    }
+
+    /// Whether a row reads as column names: text only, filling more than half
+    /// of the columns
+    fn is_header(&self, text: &[bool], row: usize, cols: &Span<usize>) -> bool {
+        self.filled_in(row, cols) * 2 > cols.len()
+            && cols
+                .clone()
+                .all(|col| !self.at(row, col) || text[row * self.width + col])
+    }
+
+    /// Number of filled cells of `row` within `cols`
+    fn filled_in(&self, row: usize, cols: &Span<usize>) -> usize {
+        cols.clone().filter(|&col| self.at(row, col)).count()
+    }
+}
+
+/// Maximal runs of `true`, as index spans offset by `offset`
+fn runs(used: impl Iterator<Item = bool>, offset: usize) -> Vec<Span<usize>> {
+    let mut spans: Vec<Span<usize>> = Vec::new();
+    for (i, used) in used.enumerate() {
+        if !used {
+            continue;
+        }
+        let index = offset + i;
+        match spans.last_mut() {
+            Some(span) if span.end == index => span.end += 1,
+            _ => spans.push(index..index + 1),
+        }
+    }
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Cells given as (row, col, value) in sheet coordinates
+    fn sheet(cells: &[(u32, u32, Data)]) -> Range<Data> {
+        let mut cells: Vec<calamine::Cell<Data>> = cells
+            .iter()
+            .map(|(row, col, value)| calamine::Cell::new((*row, *col), value.clone()))
+            .collect();
+        // `from_sparse` expects cells in row order
+        cells.sort_by_key(|cell| cell.get_position());
+        Range::from_sparse(cells)
+    }
+
+    fn text(s: &str) -> Data {
+        Data::String(s.to_string())
+    }
+
+    #[test]
+    fn test_detects_separate_blocks_and_headers() {
+        let mut cells = vec![
+            (0, 0, text("Sales report")),
+            (1, 0, text("Generated by finance")),
+            // Main table at B4:D7 with a title row directly above the header
+            (3, 1, text("Quarter totals")),
+            (4, 1, text("Region")),
+            (4, 2, text("Q1")),
+            (4, 3, text("Q2")),
+        ];
+        for (row, region) in [(5, "North"), (6, "South")] {
+            cells.push((row, 1, text(region)));
+            cells.push((row, 2, Data::Float(1.0)));
+            cells.push((row, 3, Data::Int(2)));
+        }
+        // A block at B10:C11 below a blank row
+        cells.push((9, 1, text("Key")));
+        cells.push((9, 2, text("Value")));
+        cells.push((10, 1, text("fx")));
+        cells.push((10, 2, Data::Float(1.1)));
+
+        let regions = detect_regions(&sheet(&cells));
+        let addresses: Vec<String> = regions.iter().map(DataRegion::address).collect();
+        assert_eq!(addresses, vec!["A1:A2", "B4:D7", "B10:C11"]);
+        assert_eq!(regions[1].header_row, 4);
+        assert_eq!(regions[1].data_cells, 6);
+        assert_eq!(regions[2].header_row, 9);
+        assert_eq!(best_region(&regions), Some(1));
+        let shown = ShownRegion {
+            index: 1,
+            count: regions.len(),
+            note_rows: (regions[1].header_row - regions[1].start.0) as usize,
+        };
+        assert_eq!(
+            shown.note(),
+            "region 2 of 3, 1 note row above the header skipped \
+             (use --region/--list-regions, --header-row)"
+        );
+    }
+
+    #[test]
+    fn test_spacer_column_does_not_split_table() {
+        // Column C is a spacer; the block at G2:G3 beyond blank column F has rows of its own
+        let mut cells = Vec::new();
+        for (col, name) in [(0, "id"), (1, "name"), (3, "qty"), (4, "price")] {
+            cells.push((0, col, text(name)));
+        }
+        for row in 1..4 {
+            for col in [0, 1, 3, 4] {
+                cells.push((row, col, Data::Int(row.into())));
+            }
+        }
+        cells.push((1, 6, text("Total")));
+        cells.push((2, 6, Data::Int(9)));
+        let regions = detect_regions(&sheet(&cells));
+        let addresses: Vec<String> = regions.iter().map(DataRegion::address).collect();
+        assert_eq!(addresses, vec!["A1:E4", "G2:G3"]);
+        assert_eq!(regions[0].header_row, 0);
+        assert_eq!(regions[0].data_cells, 12);
+    }
+
+    #[test]
+    fn test_side_by_side_tables_stay_apart() {
+        // Tables at A1:B3 and E1:F3, two blank columns apart
+        let mut cells = Vec::new();
+        for col in [0, 1, 4, 5] {
+            cells.push((0, col, text("name")));
+            cells.push((1, col, Data::Int(1)));
+            cells.push((2, col, Data::Int(2)));
+        }
+        let regions = detect_regions(&sheet(&cells));
+        let addresses: Vec<String> = regions.iter().map(DataRegion::address).collect();
+        assert_eq!(addresses, vec!["A1:B3", "E1:F3"]);
+    }
+
+    #[test]
+    fn test_header_with_numbers_is_kept() {
+        let mut cells = vec![
+            (0, 0, text("Sales by region")),
+            (1, 0, text("Region")),
+            (1, 1, Data::Int(2023)),
+            (1, 2, Data::Int(2024)),
+        ];
+        // An all-text data row right below the header
+        for (col, value) in ["North", "n/a", "n/a"].into_iter().enumerate() {
+            cells.push((2, col as u32, text(value)));
+        }
+        cells.push((3, 0, text("South")));
+        cells.push((3, 1, Data::Int(5)));
+        cells.push((3, 2, Data::Int(6)));
+        let regions = detect_regions(&sheet(&cells));
+        assert_eq!(regions.len(), 1);
+        assert_eq!(regions[0].header_row, 1);
+
+        // Without a title, the first row is the header even when all rows are text
+        let cells: Vec<(u32, u32, Data)> = (0..3)
+            .flat_map(|row| (0..2).map(move |col| (row, col, text("x"))))
+            .collect();
+        assert_eq!(detect_regions(&sheet(&cells))[0].header_row, 0);
+
+        let shown = ShownRegion {
+            index: 0,
+            count: 1,
+            note_rows: 1,
+        };
+        assert_eq!(
+            shown.note(),
+            "1 note row above the header skipped (use --header-row)"
+        );
+    }
+
+    #[test]
+    fn test_header_is_the_first_qualifying_row() {
+        // All-text table whose header has a blank cell; fuller rows follow it
+        let mut cells = vec![(0, 0, text("code")), (0, 1, text("name"))];
+        for row in 1..4 {
+            for col in 0..3 {
+                cells.push((row, col, text("x")));
+            }
+        }
+        let regions = detect_regions(&sheet(&cells));
+        assert_eq!(regions.len(), 1);
+        assert_eq!(regions[0].header_row, 0);
+    }
+
+    #[test]
+    fn test_spacer_row_does_not_split_table() {
+        let mut cells = vec![(0, 0, text("id")), (0, 1, text("name"))];
+        for row in [1, 2, 4, 5] {
+            cells.push((row, 0, Data::Int(row.into())));
+            cells.push((row, 1, text("x")));
+        }
+        // Below another blank row, a block whose first column is empty
+        cells.push((7, 1, text("y")));
+        cells.push((7, 2, Data::Int(8)));
+        let regions = detect_regions(&sheet(&cells));
+        assert_eq!(regions.len(), 1);
+        assert_eq!(regions[0].address(), "A1:C8");
+        assert_eq!(regions[0].header_row, 0);
+        assert!(detect_regions(&Range::empty()).is_empty());
+    }
+}
@@ -0,0 +1,372 @@
+use crate::styles::CellFormats;
+use crate::workbook::{CellValue, HEADER_ORIGIN, SheetData};
+use anyhow::{Context, Result, anyhow};
+use rusqlite::types::ValueRef;
+use rusqlite::{Connection, MAIN_DB, OpenFlags, OptionalExtension};
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// Header string at the start of every SQLite 3 database file
+pub const SQLITE_MAGIC: &[u8; 16] = b"SQLite format 3\0";
+/// Rows between remembered rowids when paging a rowid table
+const ROWID_STRIDE: usize = 256;
+/// Names SQLite accepts for the rowid, unless a column of the table uses them
+const ROWID_ALIASES: [&str; 3] = ["_rowid_", "rowid", "oid"];
+
+/// Whether files with this extension are opened as SQLite databases
+pub fn is_sqlite_extension(ext: &str) -> bool {
+    matches!(
+        ext.to_ascii_lowercase().as_str(),
+        "sqlite" | "sqlite3" | "db" | "db3"
+    )
+}
+
+/// A SQLite database whose tables and views are shown as sheets
+pub struct SqliteDatabase {
+    conn: Arc<Mutex<Connection>>,
+    /// Tables and views in schema order, internal `sqlite_` tables excluded
+    tables: Vec<String>,
+}
+
+impl SqliteDatabase {
+    /// Opens the database read-only; nothing is ever written back
+    pub fn open(path: &Path) -> Result<Self> {
+        let conn = Connection::open_with_flags(
+            path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )?;
+        Self::from_connection(conn)
+    }
+
+    /// A database image already in memory (e.g. read from stdin)
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Self> {
+        let mut conn = Connection::open_in_memory()?;
+        conn.deserialize_read_exact(MAIN_DB, bytes.as_slice(), bytes.len(), true)?;
+        Self::from_connection(conn)
+    }
+
+    fn from_connection(conn: Connection) -> Result<Self> {
+        let tables = conn
+            .prepare(
+                "SELECT name FROM sqlite_schema \
+                 WHERE type IN ('table', 'view') AND name NOT LIKE 'sqlite\\_%' ESCAPE '\\' \
+                 ORDER BY rowid",
+            )?
+            .query_map([], |row| row.get(0))?
+            .collect::<rusqlite::Result<Vec<String>>>()
+            .context("Not a readable SQLite database")?;
+        Ok(Self {
+            conn: Arc::new(Mutex::new(conn)),
+            tables,
+        })
+    }
+
+    pub fn table_names(&self) -> &[String] {
+        &self.tables
+    }
+
+    /// Reads the columns and row count of a table or view
+    pub fn table(&self, name: &str) -> Result<SqliteTable> {
+        if !self.tables.iter().any(|t| t == name) {
+            return Err(anyhow!("Sheet '{name}' not found in SQLite database."));
+        }
+        SqliteTable::open(Arc::clone(&self.conn), name)
+            .with_context(|| format!("Failed to read table '{name}'"))
+    }
+}
+
+/// How the rows of a table are paged
+enum Paging {
+    /// Rowid tables are read by rowid range. The rowid of every `ROWID_STRIDE`th
+    /// row is remembered as rows are requested, so no page scans earlier rows.
+    Rowid {
+        column: &'static str,
+        checkpoints: Mutex<Vec<i64>>,
+    },
+    /// Views and WITHOUT ROWID tables have no rowid and are paged with `LIMIT`/`OFFSET`
+    Offset,
+}
+
+/// One table or view, read a page at a time as rows are displayed
+pub struct SqliteTable {
+    conn: Arc<Mutex<Connection>>,
+    /// Quoted table name
+    table: String,
+    paging: Paging,
+    pub headers: Vec<String>,
+    height: usize,
+}
+
+impl SqliteTable {
+    fn open(conn: Arc<Mutex<Connection>>, name: &str) -> Result<Self> {
+        let guard = lock(&conn);
+        let table = quote_identifier(name);
+        let headers: Vec<String> = guard
+            .prepare(&format!("SELECT * FROM {table}"))?
+            .column_names()
+            .into_iter()
+            .map(str::to_string)
+            .collect();
+        // A column of the table may be named like the rowid and hide it
+        let rowid = ROWID_ALIASES.into_iter().find(|alias| {
+            !headers.iter().any(|h| h.eq_ignore_ascii_case(alias))
+                && guard
+                    .prepare(&format!("SELECT {alias} FROM {table} LIMIT 0"))
+                    .is_ok()
+        });
+        let paging = match rowid {
+            Some(column) => Paging::Rowid {
+                column,
+                checkpoints: Mutex::new(Vec::new()),
+            },
+            None => Paging::Offset,
+        };
+        let height: i64 = guard.query_row(&format!("SELECT COUNT(*) FROM {table}"), [], |row| {
+            row.get(0)
+        })?;
+        drop(guard);
+
+        Ok(Self {
+            conn,
+            table,
+            paging,
+            headers,
+            height: usize::try_from(height).unwrap_or_default(),
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.headers.len()
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Zero-indexed rows `start..start + count`
+    pub fn read_rows(&self, start: usize, count: usize) -> Result<Vec<Vec<CellValue>>> {
+        let conn = lock(&self.conn);
+        let table = &self.table;
+        let (mut stmt, params) = match &self.paging {
+            Paging::Rowid {
+                column,
+                checkpoints,
+            } => {
+                let Some(from) =
+                    rowid_checkpoint(&conn, table, column, checkpoints, start / ROWID_STRIDE)?
+                else {
+                    return Ok(Vec::new());
+                };
+                let stmt = conn.prepare_cached(&format!(
+                    "SELECT * FROM {table} WHERE {column} >= ?1 ORDER BY {column} LIMIT ?2 OFFSET ?3"
+                ))?;
+                (
+                    stmt,
+                    vec![from, count as i64, (start % ROWID_STRIDE) as i64],
+                )
+            }
+            Paging::Offset => {
+                let stmt =
+                    conn.prepare_cached(&format!("SELECT * FROM {table} LIMIT ?1 OFFSET ?2"))?;
+                (stmt, vec![count as i64, start as i64])
+            }
+        };
+        let width = self.width();
+        let rows = stmt
+            .query_map(rusqlite::params_from_iter(params), |row| {
+                (0..width)
+                    .map(|col| row.get_ref(col).map(sqlite_to_cellvalue))
+                    .collect()
+            })?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(rows)
+    }
+
+    /// Reads every row into memory
+    pub fn to_sheet_data(&self) -> Result<SheetData> {
+        let rows = self.read_rows(0, self.height)?;
+        let (width, height) = (self.width(), rows.len());
+        Ok(SheetData {
+            headers: self.headers.clone(),
+            rows,
+            formulas: vec![vec![None; width]; height],
+            merges: Vec::new(),
+            formats: CellFormats::default(),
+            origin: HEADER_ORIGIN,
+            width,
+            height,
+        })
+    }
+}
+
+/// The rowid of row `stride * ROWID_STRIDE`, stepping from the last known
+/// checkpoint a stride at a time; `None` past the last row
+fn rowid_checkpoint(
+    conn: &Connection,
+    table: &str,
+    column: &str,
+    checkpoints: &Mutex<Vec<i64>>,
+    stride: usize,
+) -> Result<Option<i64>> {
+    let mut checkpoints = lock(checkpoints);
+    if checkpoints.is_empty() {
+        let first: Option<i64> =
+            conn.query_row(&format!("SELECT MIN({column}) FROM {table}"), [], |row| {
+                row.get(0)
+            })?;
+        let Some(first) = first else {
+            return Ok(None);
+        };
+        checkpoints.push(first);
+    }
+    let mut next = conn.prepare_cached(&format!(
+        "SELECT {column} FROM {table} WHERE {column} >= ?1 ORDER BY {column} LIMIT 1 OFFSET ?2"
+    ))?;
+    while checkpoints.len() <= stride {
+        let last = checkpoints[checkpoints.len() - 1];
+        let Some(rowid) = next
+            .query_row([last, ROWID_STRIDE as i64], |row| row.get(0))
+            .optional()?
+        else {
+            return Ok(None);
+        };
+        checkpoints.push(rowid);
+    }
+    Ok(Some(checkpoints[stride]))
+}
+
+/// The connection is only used from one thread at a time, so a poisoned lock is still usable
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// SQLite values are dynamically typed, so each cell maps from its stored class
+fn sqlite_to_cellvalue(value: ValueRef<'_>) -> CellValue {
+    match value {
+        ValueRef::Null => CellValue::Empty,
+        ValueRef::Integer(i) => CellValue::Int(i),
+        ValueRef::Real(f) => CellValue::Float(f),
+        ValueRef::Text(text) => CellValue::String(String::from_utf8_lossy(text).into_owned()),
+        ValueRef::Blob(blob) => CellValue::String(format!("<BLOB {} bytes>", blob.len())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_database(path: &Path) {
+        let conn = Connection::open(path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE \"order items\" (id INTEGER PRIMARY KEY, name TEXT, price REAL, data BLOB);
+             CREATE TABLE tags (tag TEXT PRIMARY KEY, n INTEGER) WITHOUT ROWID;
+             CREATE VIEW cheap AS SELECT name FROM \"order items\" WHERE price < 5;
+             INSERT INTO tags VALUES ('b', 2), ('a', 1);",
+        )
+        .unwrap();
+        let mut insert = conn
+            .prepare("INSERT INTO \"order items\" (name, price, data) VALUES (?1, ?2, ?3)")
+            .unwrap();
+        for i in 0..25 {
+            let name = (i != 3).then(|| format!("item {i}"));
+            insert
+                .execute(rusqlite::params![name, f64::from(i), vec![0u8; 3]])
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn test_sqlite_tables_as_sheets() {
+        let path = std::env::temp_dir().join(format!("xleak-{}.sqlite", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        sample_database(&path);
+        let db = SqliteDatabase::open(&path).unwrap();
+        assert_eq!(db.table_names(), ["order items", "tags", "cheap"]);
+
+        let items = db.table("order items").unwrap();
+        assert_eq!(items.headers, vec!["id", "name", "price", "data"]);
+        assert_eq!(items.height(), 25);
+        let rows = items.read_rows(2, 3).unwrap();
+        assert_eq!(rows.len(), 3);
+        assert!(matches!(rows[0][0], CellValue::Int(3)));
+        assert!(matches!(rows[1][1], CellValue::Empty));
+        assert!(matches!(rows[2][2], CellValue::Float(f) if f == 4.0));
+        assert_eq!(rows[0][3].to_raw_string(), "<BLOB 3 bytes>");
+        assert_eq!(items.read_rows(24, 10).unwrap().len(), 1);
+
+        assert_eq!(
+            db.table("cheap").unwrap().to_sheet_data().unwrap().height,
+            5
+        );
+        assert!(db.table("missing").is_err());
+
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(bytes.starts_with(SQLITE_MAGIC));
+        let db = SqliteDatabase::from_bytes(bytes).unwrap();
+        let tags = db.table("tags").unwrap().to_sheet_data().unwrap();
+        assert_eq!(tags.rows[0][0].to_raw_string(), "a");
+    }
+
+    #[test]
+    fn test_sqlite_paging() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE shadowed (rowid INTEGER, n INTEGER);
+             CREATE TABLE hidden (rowid, _rowid_, oid, n INTEGER);
+             CREATE TABLE keyed (k INTEGER PRIMARY KEY, n INTEGER) WITHOUT ROWID;
+             CREATE VIEW odd AS SELECT n FROM shadowed WHERE n % 2 = 1;",
+        )
+        .unwrap();
+        // The `rowid` column counts down while the real rowid counts up
+        for n in 0..1000 {
+            conn.execute("INSERT INTO shadowed VALUES (?1, ?2)", [1000 - n, n])
+                .unwrap();
+            conn.execute("INSERT INTO hidden VALUES (0, 0, 0, ?1)", [n])
+                .unwrap();
+            conn.execute("INSERT INTO keyed VALUES (?1, ?1)", [n])
+                .unwrap();
+        }
+        // Gaps in the rowids don't shift rows
+        conn.execute("DELETE FROM shadowed WHERE n < 10", [])
+            .unwrap();
+        let db = SqliteDatabase::from_connection(conn).unwrap();
+
+        let n_values = |name: &str, start: usize, count: usize| -> Vec<String> {
+            db.table(name)
+                .unwrap()
+                .read_rows(start, count)
+                .unwrap()
+                .iter()
+                .map(|row| row.last().unwrap().to_raw_string())
+                .collect()
+        };
+        let shadowed = db.table("shadowed").unwrap();
+        assert!(matches!(
+            shadowed.paging,
+            Paging::Rowid {
+                column: "_rowid_",
+                ..
+            }
+        ));
+        assert_eq!(shadowed.height(), 990);
+        assert_eq!(n_values("shadowed", 0, 2), ["10", "11"]);
+        assert_eq!(n_values("shadowed", 600, 2), ["610", "611"]);
+        assert_eq!(n_values("shadowed", 255, 3), ["265", "266", "267"]);
+        assert_eq!(n_values("shadowed", 988, 5), ["998", "999"]);
+        assert!(n_values("shadowed", 990, 5).is_empty());
+
+        for name in ["hidden", "keyed", "odd"] {
+            assert!(matches!(db.table(name).unwrap().paging, Paging::Offset));
+        }
+        assert_eq!(n_values("hidden", 700, 2), ["700", "701"]);
+        assert_eq!(n_values("keyed", 998, 5), ["998", "999"]);
+        assert_eq!(n_values("odd", 10, 2), ["31", "33"]);
+    }
+}
@@ -0,0 +1,282 @@
+use crate::names;
+use crate::number_format::NumberFormat;
+use crate::package;
+use crate::xlsb::{self, Records};
+use crate::xml::attribute;
+use anyhow::Result;
+use quick_xml::events::Event;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Seek};
+use std::sync::Arc;
+use zip::ZipArchive;
+
+/// Number formats of a sheet's cells, keyed by zero-based sheet position.
+/// Cells in the General format are left out.
+#[derive(Debug, Clone, Default)]
+pub struct CellFormats {
+    cells: Arc<HashMap<(u32, u32), Arc<NumberFormat>>>,
+    date1904: bool,
+}
+
+impl CellFormats {
+    pub fn get(&self, row: usize, col: usize) -> Option<&NumberFormat> {
+        let key = (u32::try_from(row).ok()?, u32::try_from(col).ok()?);
+        self.cells.get(&key).map(Arc::as_ref)
+    }
+
+    /// Whether the workbook's date serials count from 1904-01-01
+    pub fn date1904(&self) -> bool {
+        self.date1904
+    }
+}
+
+/// Number format of each cell style of an xlsx or xlsb workbook, which calamine
+/// only uses to tell dates from numbers
+pub struct Styles {
+    /// By style (xf) index; `None` for General
+    formats: Vec<Option<Arc<NumberFormat>>>,
+    sheet_paths: HashMap<String, String>,
+    date1904: bool,
+}
+
+impl Styles {
+    pub fn read<R: Read + Seek>(archive: &mut ZipArchive<R>) -> Result<Self> {
+        let sheet_paths = package::sheet_paths(archive)?;
+        let date1904 = package::date1904(archive)?;
+        let (custom, style_formats) = if archive.index_for_name("xl/styles.xml").is_some() {
+            parse_xlsx_styles(BufReader::new(archive.by_name("xl/styles.xml")?))?
+        } else if archive.index_for_name("xl/styles.bin").is_some() {
+            read_xlsb_styles(archive.by_name("xl/styles.bin")?)?
+        } else {
+            Default::default()
+        };
+
+        let mut parsed: HashMap<u32, Option<Arc<NumberFormat>>> = HashMap::new();
+        let formats = style_formats
+            .into_iter()
+            .map(|id| {
+                parsed
+                    .entry(id)
+                    .or_insert_with(|| {
+                        let code = custom
+                            .get(&id)
+                            .map(String::as_str)
+                            .or_else(|| NumberFormat::builtin(id))?;
+                        let format = NumberFormat::parse(code);
+                        (!format.is_general()).then(|| Arc::new(format))
+                    })
+                    .clone()
+            })
+            .collect();
+        Ok(Self {
+            formats,
+            sheet_paths,
+            date1904,
+        })
+    }
+
+    /// Formats of the cells of `sheet` that aren't in General
+    pub fn cell_formats<R: Read + Seek>(
+        &self,
+        archive: &mut ZipArchive<R>,
+        sheet: &str,
+    ) -> Result<CellFormats> {
+        // Most workbooks only use General; don't read their sheets twice
+        let Some(path) = self
+            .sheet_paths
+            .get(sheet)
+            .filter(|_| self.formats.iter().any(Option::is_some))
+        else {
+            return Ok(CellFormats::default());
+        };
+        let part = archive.by_name(path)?;
+        let styles = if path.ends_with(".bin") {
+            read_xlsb_cell_styles(part)?
+        } else {
+            parse_xlsx_cell_styles(BufReader::new(part))?
+        };
+
+        let cells = styles
+            .into_iter()
+            .filter_map(|(cell, style)| Some((cell, self.formats.get(style)?.clone()?)))
+            .collect();
+        Ok(CellFormats {
+            cells: Arc::new(cells),
+            date1904: self.date1904,
+        })
+    }
+}
+
+/// Custom format codes by id, and the format id of each cell style, from `xl/styles.xml`
+fn parse_xlsx_styles(styles: impl BufRead) -> Result<(HashMap<u32, String>, Vec<u32>)> {
+    let mut xml = quick_xml::Reader::from_reader(styles);
+    let mut buf = Vec::new();
+    let mut custom = HashMap::new();
+    let mut style_formats = Vec::new();
+    // `dxfs` (conditional formats) also hold `numFmt` elements, and `cellStyleXfs`
+    // holds `xf` elements, but cells only refer to `numFmts` and `cellXfs`
+    let mut in_num_fmts = false;
+    let mut in_cell_xfs = false;
+
+    loop {
+        buf.clear();
+        let (e, empty) = match xml.read_event_into(&mut buf)? {
+            Event::Start(e) => (e, false),
+            Event::Empty(e) => (e, true),
+            Event::End(e) => {
+                match e.local_name().as_ref() {
+                    b"numFmts" => in_num_fmts = false,
+                    b"cellXfs" => in_cell_xfs = false,
+                    _ => {}
+                }
+                continue;
+            }
+            Event::Eof => break,
+            _ => continue,
+        };
+        match e.local_name().as_ref() {
+            // An empty `<cellXfs/>` has no children and no end tag
+            b"numFmts" => in_num_fmts = !empty,
+            b"cellXfs" => in_cell_xfs = !empty,
+            b"numFmt" if in_num_fmts => {
+                let id = attribute(&e, "numFmtId")?.and_then(|id| id.parse().ok());
+                if let (Some(id), Some(code)) = (id, attribute(&e, "formatCode")?) {
+                    custom.insert(id, code);
+                }
+            }
+            b"xf" if in_cell_xfs => {
+                let id = attribute(&e, "numFmtId")?.and_then(|id| id.parse().ok());
+                style_formats.push(id.unwrap_or(0));
+            }
+            _ => {}
+        }
+    }
+    Ok((custom, style_formats))
+}
+
+/// Like `parse_xlsx_styles`, from the BrtFmt and BrtXF records of `xl/styles.bin`
+fn read_xlsb_styles(styles: impl Read) -> Result<(HashMap<u32, String>, Vec<u32>)> {
+    let mut records = Records::new(BufReader::new(styles));
+    let mut custom = HashMap::new();
+    let mut style_formats = Vec::new();
+    let mut in_cell_styles = false;
+
+    while let Some((typ, data)) = records.next_record()? {
+        match typ {
+            xlsb::FORMAT => {
+                if let Some(id) = xlsb::read_u16(data, 0)
+                    && let Some((code, _)) = xlsb::wide_string(data, 2)
+                {
+                    custom.insert(u32::from(id), code);
+                }
+            }
+            xlsb::BEGIN_CELL_STYLES => in_cell_styles = true,
+            xlsb::END_CELL_STYLES => in_cell_styles = false,
+            xlsb::CELL_STYLE if in_cell_styles => {
+                style_formats.push(u32::from(xlsb::read_u16(data, 2).unwrap_or(0)));
+            }
+            _ => {}
+        }
+    }
+    Ok((custom, style_formats))
+}
+
+/// Style index of each cell that has one, from a worksheet's XML
+fn parse_xlsx_cell_styles(sheet: impl BufRead) -> Result<Vec<((u32, u32), usize)>> {
+    let mut xml = quick_xml::Reader::from_reader(sheet);
+    let mut buf = Vec::new();
+    let mut cells = Vec::new();
+    // Positions are usually explicit, but may be left out for the next row or cell
+    let (mut row, mut col, mut next_row) = (0u32, 0u32, 0u32);
+
+    loop {
+        buf.clear();
+        match xml.read_event_into(&mut buf)? {
+            Event::Start(e) | Event::Empty(e) => match e.local_name().as_ref() {
+                b"row" => {
+                    row = match attribute(&e, "r")?.and_then(|r| r.parse::<u32>().ok()) {
+                        Some(r) => r.saturating_sub(1),
+                        None => next_row,
+                    };
+                    (col, next_row) = (0, row + 1);
+                }
+                b"c" => {
+                    if let Some(reference) = attribute(&e, "r")?
+                        && let Some((Some(r), Some(c))) = names::parse_cell(&reference)
+                    {
+                        (row, col) = (r, c);
+                    }
+                    if let Some(style) = attribute(&e, "s")?.and_then(|s| s.parse().ok()) {
+                        cells.push(((row, col), style));
+                    }
+                    col += 1;
+                }
+                _ => {}
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(cells)
+}
+
+/// Like `parse_xlsx_cell_styles`, from the cell records of a binary worksheet
+fn read_xlsb_cell_styles(sheet: impl Read) -> Result<Vec<((u32, u32), usize)>> {
+    /// BrtCellBlank through BrtFmlaError, and BrtCellRString
+    fn is_cell(typ: u16) -> bool {
+        (0x0001..=0x000B).contains(&typ) || typ == 0x003E
+    }
+
+    let mut records = Records::new(BufReader::new(sheet));
+    let mut cells = Vec::new();
+    let mut row = 0;
+    while let Some((typ, data)) = records.next_record()? {
+        if typ == xlsb::ROW_HEADER {
+            row = xlsb::read_u32(data, 0).unwrap_or(row);
+        } else if is_cell(typ)
+            && let (Some(col), Some(style)) = (xlsb::read_u32(data, 0), xlsb::read_u32(data, 4))
+        {
+            // The style index takes the low 24 bits; flags follow
+            let style = (style & 0x00FF_FFFF) as usize;
+            if style != 0 {
+                cells.push(((row, col), style));
+            }
+        }
+    }
+    Ok(cells)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_xlsx_styles() {
+        let styles = r#"<styleSheet>
+            <numFmts count="1"><numFmt numFmtId="164" formatCode="0.0000"/></numFmts>
+            <cellStyleXfs count="1"><xf numFmtId="4"/></cellStyleXfs>
+            <cellXfs count="3"><xf numFmtId="0"/><xf numFmtId="164"/><xf numFmtId="9" applyNumberFormat="1"/></cellXfs>
+            </styleSheet>"#;
+        let (custom, style_formats) = parse_xlsx_styles(styles.as_bytes()).unwrap();
+        assert_eq!(custom[&164], "0.0000");
+        assert_eq!(style_formats, vec![0, 164, 9]);
+
+        // Conditional formats reuse ids, and an empty `cellXfs` has no styles
+        let styles = r#"<styleSheet>
+            <numFmts count="1"><numFmt numFmtId="164" formatCode="0.0%"/></numFmts>
+            <cellXfs count="0"/>
+            <cellStyleXfs count="1"><xf numFmtId="4"/></cellStyleXfs>
+            <dxfs count="1"><dxf><numFmt numFmtId="164" formatCode="[Red]0"/></dxf></dxfs>
+            </styleSheet>"#;
+        let (custom, style_formats) = parse_xlsx_styles(styles.as_bytes()).unwrap();
+        assert_eq!(custom, HashMap::from([(164, "0.0%".to_string())]));
+        assert!(style_formats.is_empty());
+
+        let sheet = r#"<worksheet><sheetData>
+            <row r="2"><c r="A2" s="1"><v>0.5</v></c><c s="2"><v>1</v></c><c r="D2"><v>2</v></c></row>
+            <row><c s="2"/></row>
+            </sheetData></worksheet>"#;
+        let cells = parse_xlsx_cell_styles(sheet.as_bytes()).unwrap();
+        assert_eq!(cells, vec![((1, 0), 1), ((1, 1), 2), ((2, 0), 2)]);
+    }
+}
@@ -1,4 +1,10 @@
-use crate::workbook::{CellValue, LazySheetData, SheetData, Workbook};
+use crate::comments::Comment;
+use crate::hyperlinks::{self, Hyperlink, LinkTarget};
+use crate::json_source;
+use crate::merges::{self, MergedCells};
+use crate::names;
+use crate::styles::CellFormats;
+use crate::workbook::{CellValue, LazySheetData, SheetData, Workbook, column_letter};
 use anyhow::{Context, Result};
 use arboard::Clipboard;
 use crossterm::{
@@ -9,12 +15,15 @@ use crossterm::{
 use ratatui::{
     Frame, Terminal,
     backend::CrosstermBackend,
-    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    buffer::Buffer,
+    layout::{Alignment, Constraint, Direction, Flex, Layout, Rect},
     style::{Color, Modifier, Style},
     widgets::{Block, Borders, Cell, Clear, Paragraph, Row, Table, Wrap},
 };
+use std::collections::HashMap;
 use std::io;
 use std::time::{Duration, Instant};
+use unicode_width::UnicodeWidthStr;
 
 /// Available themes
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -311,7 +320,9 @@ impl ColorScheme {
             CellValue::Int(_) | CellValue::Float(_) => self.number_fg,
             CellValue::Bool(_) => self.bool_fg,
             CellValue::Error(_) => self.error_fg,
-            CellValue::DateTime(_) => self.datetime_fg,
+            CellValue::DateTime(_) | CellValue::Time(_) | CellValue::Duration(_) => {
+                self.datetime_fg
+            }
         }
     }
 }
@@ -355,6 +366,29 @@ impl SheetDataSource {
         }
     }
 
+    fn origin(&self) -> (usize, usize) {
+        match self {
+            SheetDataSource::Eager(data) => data.origin,
+            SheetDataSource::Lazy { data, .. } => data.origin,
+        }
+    }
+
+    fn merges(&self) -> &[MergedCells] {
+        match self {
+            SheetDataSource::Eager(data) => &data.merges,
+            SheetDataSource::Lazy { data, .. } => &data.merges,
+        }
+    }
+
+    /// Number formats by sheet position; cheap to clone, so they can be read
+    /// while rows are borrowed
+    fn formats(&self) -> CellFormats {
+        match self {
+            SheetDataSource::Eager(data) => data.formats.clone(),
+            SheetDataSource::Lazy { data, .. } => data.formats.clone(),
+        }
+    }
+
     /// Fetches rows with automatic cache management
     fn get_rows(
         &mut self,
@@ -450,11 +484,7 @@ impl ProgressInfo {
     }
 
     fn percentage(&self) -> usize {
-        if self.total == 0 {
-            100
-        } else {
-            (self.current * 100) / self.total
-        }
+        (self.current * 100).checked_div(self.total).unwrap_or(100)
     }
 
     fn format(&self) -> String {
@@ -473,6 +503,12 @@ pub struct TuiState {
     sheet_names: Vec<String>,
     current_sheet_index: usize,
     sheet_data: SheetDataSource,
+    /// Whether the VBA modules are shown instead of a sheet
+    showing_macros: bool,
+    /// Comments of the current sheet, by sheet position
+    comments: HashMap<(u32, u32), Vec<Comment>>,
+    /// Hyperlinks of the current sheet, each on a cell or a range of cells
+    links: Vec<Hyperlink>,
     should_quit: bool,
     cursor_row: usize,               // Current row (0-indexed in data)
     cursor_col: usize,               // Current column (0-indexed)
@@ -534,14 +570,19 @@ impl TuiState {
             }
         } else {
             // Convert to eager loading for small files
-            SheetDataSource::Eager(lazy_data.to_sheet_data())
+            SheetDataSource::Eager(lazy_data.to_sheet_data()?)
         };
+        let comments = Self::sheet_comments(&workbook, &sheet_names[current_sheet_index]);
+        let links = Self::sheet_links(&workbook, &sheet_names[current_sheet_index]);
 
         let mut state = Self {
             workbook,
             sheet_names,
             current_sheet_index,
             sheet_data,
+            showing_macros: false,
+            comments,
+            links,
             should_quit: false,
             cursor_row: 0,
             cursor_col: 0,
@@ -636,8 +677,11 @@ impl TuiState {
             }
         } else {
             // Convert to eager loading for small files
-            SheetDataSource::Eager(lazy_data.to_sheet_data())
+            SheetDataSource::Eager(lazy_data.to_sheet_data()?)
         };
+        self.showing_macros = false;
+        self.comments = Self::sheet_comments(&self.workbook, &sheet_name);
+        self.links = Self::sheet_links(&self.workbook, &sheet_name);
 
         // Recalculate column widths if horizontal scrolling is enabled
         if self.horizontal_scroll_enabled {
@@ -647,6 +691,82 @@ impl TuiState {
         Ok(())
     }
 
+    /// Comments grouped by cell. Like merges they only affect presentation, so
+    /// unreadable ones are skipped.
+    fn sheet_comments(workbook: &Workbook, sheet: &str) -> HashMap<(u32, u32), Vec<Comment>> {
+        let mut comments: HashMap<(u32, u32), Vec<Comment>> = HashMap::new();
+        for comment in workbook.comments(sheet).unwrap_or_default() {
+            comments
+                .entry((comment.row, comment.col))
+                .or_default()
+                .push(comment);
+        }
+        comments
+    }
+
+    /// Shows the workbook's VBA modules as a sheet of code lines, so they can be
+    /// read and searched, or goes back to the current sheet
+    fn toggle_macros(&mut self) {
+        if self.showing_macros {
+            if let Err(e) = self.load_current_sheet() {
+                self.copy_feedback = Some((format!("Failed to load sheet: {e}"), Instant::now()));
+            }
+            self.reset_cursor();
+            self.clear_search();
+            return;
+        }
+        let project = match self.workbook.vba_project() {
+            Ok(Some(project)) => project,
+            Ok(None) => {
+                self.copy_feedback =
+                    Some(("No VBA macros in this workbook".to_string(), Instant::now()));
+                return;
+            }
+            Err(e) => {
+                self.copy_feedback = Some((format!("{e}"), Instant::now()));
+                return;
+            }
+        };
+        self.sheet_data = SheetDataSource::Eager(project.to_sheet_data());
+        self.showing_macros = true;
+        self.comments.clear();
+        self.links.clear();
+        if self.horizontal_scroll_enabled {
+            self.column_widths = self.calculate_column_widths();
+        }
+        self.reset_cursor();
+        self.clear_search();
+    }
+
+    /// Hyperlinks of a sheet; unreadable ones are skipped like comments
+    fn sheet_links(workbook: &Workbook, sheet: &str) -> Vec<Hyperlink> {
+        workbook.hyperlinks(sheet).unwrap_or_default()
+    }
+
+    /// Sheet position of the cell at data position (`row`, `col`), as map keys
+    fn sheet_key(&self, row: usize, col: usize) -> Option<(u32, u32)> {
+        let origin = self.sheet_data.origin();
+        Some((
+            u32::try_from(origin.0 + row).ok()?,
+            u32::try_from(origin.1 + col).ok()?,
+        ))
+    }
+
+    /// Comments on the cell at data position (`row`, `col`)
+    fn comments_at(&self, row: usize, col: usize) -> &[Comment] {
+        self.sheet_key(row, col)
+            .and_then(|key| self.comments.get(&key))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    fn link_at(&self, row: usize, col: usize) -> Option<&LinkTarget> {
+        let (row, col) = self.sheet_key(row, col)?;
+        self.links
+            .iter()
+            .find(|link| link.contains(row, col))
+            .map(|link| &link.target)
+    }
+
     fn reset_cursor(&mut self) {
         self.cursor_row = 0;
         self.cursor_col = 0;
@@ -674,6 +794,7 @@ impl TuiState {
 
         // Search through all cells (load in chunks for lazy data)
         const SEARCH_CHUNK_SIZE: usize = 500;
+        let (formats, origin) = (self.sheet_data.formats(), self.sheet_data.origin());
         for chunk_start in (0..total_height).step_by(SEARCH_CHUNK_SIZE) {
             let chunk_size = SEARCH_CHUNK_SIZE.min(total_height - chunk_start);
             let (rows, _formulas) = self.sheet_data.get_rows(chunk_start, chunk_size);
@@ -681,7 +802,10 @@ impl TuiState {
             for (chunk_idx, row) in rows.iter().enumerate() {
                 let row_idx = chunk_start + chunk_idx;
                 for (col_idx, cell) in row.iter().enumerate() {
-                    let cell_str = cell.to_string().to_lowercase();
+                    let format = formats.get(origin.0 + row_idx, origin.1 + col_idx);
+                    let cell_str = cell
+                        .to_formatted_string(format, formats.date1904())
+                        .to_lowercase();
                     if cell_str.contains(&query_lower) {
                         self.search_matches.push((row_idx, col_idx));
                     }
@@ -762,34 +886,45 @@ impl TuiState {
     }
 
     /// Parse jump input and navigate to that location
-    /// Supports formats: "100" (row), "A5" (cell address), "5,3" (row,col)
+    /// Supports formats: "100" (row), "A5" (cell address), "5,3" (row,col), "Revenue" (defined name)
     fn perform_jump(&mut self) {
         if self.jump_input.is_empty() {
             self.jump_mode = false;
             return;
         }
 
-        let input = self.jump_input.trim();
+        let input = self.jump_input.trim().to_string();
+        let input = input.as_str();
 
-        // Try to parse as row number (1-indexed)
+        // Try to parse as row number (1-indexed, as numbered in the sheet)
         if let Ok(row_num) = input.parse::<usize>() {
-            if row_num > 0 && row_num <= self.sheet_data.height() {
-                self.cursor_row = row_num - 1; // Convert to 0-indexed
-                self.copy_feedback = Some((format!("Jumped to row {}", row_num), Instant::now()));
-            } else {
-                self.copy_feedback = Some((
-                    format!(
-                        "Invalid row: {} (max: {})",
-                        row_num,
-                        self.sheet_data.height()
-                    ),
-                    Instant::now(),
-                ));
+            let first_col = self.sheet_data.origin().1;
+            match row_num
+                .checked_sub(1)
+                .and_then(|row| self.sheet_to_data(row, first_col))
+            {
+                Some((row, _)) => {
+                    self.cursor_row = row;
+                    self.copy_feedback =
+                        Some((format!("Jumped to row {}", row_num), Instant::now()));
+                }
+                None => {
+                    let first_row = self.sheet_data.origin().0 + 1;
+                    self.copy_feedback = Some((
+                        format!(
+                            "Invalid row: {} (rows {}-{})",
+                            row_num,
+                            first_row,
+                            first_row + self.sheet_data.height().saturating_sub(1)
+                        ),
+                        Instant::now(),
+                    ));
+                }
             }
         }
         // Try to parse as cell address like "A5" or "B10"
         else if let Some((col, row)) = Self::parse_cell_address(input) {
-            if row < self.sheet_data.height() && col < self.sheet_data.width() {
+            if let Some((row, col)) = self.sheet_to_data(row, col) {
                 self.cursor_row = row;
                 self.cursor_col = col;
                 self.copy_feedback = Some((
@@ -808,13 +943,13 @@ impl TuiState {
             if let (Ok(row_num), Ok(col_num)) =
                 (row.trim().parse::<usize>(), col.trim().parse::<usize>())
             {
-                if row_num > 0
-                    && row_num <= self.sheet_data.height()
-                    && col_num > 0
-                    && col_num <= self.sheet_data.width()
+                if let Some((row, col)) = row_num
+                    .checked_sub(1)
+                    .zip(col_num.checked_sub(1))
+                    .and_then(|(row, col)| self.sheet_to_data(row, col))
                 {
-                    self.cursor_row = row_num - 1;
-                    self.cursor_col = col_num - 1;
+                    self.cursor_row = row;
+                    self.cursor_col = col;
                     self.copy_feedback = Some((
                         format!("Jumped to row {}, col {}", row_num, col_num),
                         Instant::now(),
@@ -825,13 +960,15 @@ impl TuiState {
                 }
             } else {
                 self.copy_feedback = Some((
-                    "Invalid format. Use: row number, cell (A5), or row,col".to_string(),
+                    "Invalid format. Use: row number, cell (A5), row,col, or a name".to_string(),
                     Instant::now(),
                 ));
             }
-        } else {
+        }
+        // Try a defined name, which may be on another sheet
+        else if !self.jump_to_name(input) {
             self.copy_feedback = Some((
-                "Invalid format. Use: row number, cell (A5), or row,col".to_string(),
+                "Invalid format. Use: row number, cell (A5), row,col, or a name".to_string(),
                 Instant::now(),
             ));
         }
@@ -840,6 +977,99 @@ impl TuiState {
         self.jump_input.clear();
     }
 
+    /// Moves to the first cell of a defined name, switching sheets if needed.
+    /// Returns false if the workbook has no such name.
+    fn jump_to_name(&mut self, input: &str) -> bool {
+        let current_sheet = self.current_sheet_name().to_string();
+        let Some(defined) = self
+            .workbook
+            .defined_names()
+            .ok()
+            .and_then(|names| names::find(names, input, Some(&current_sheet)))
+            .cloned()
+        else {
+            return false;
+        };
+        let Some((sheet, area)) = defined.area() else {
+            self.copy_feedback = Some((
+                format!(
+                    "{} is {}, not a range of cells",
+                    defined.name, defined.refers_to
+                ),
+                Instant::now(),
+            ));
+            return true;
+        };
+
+        let start = (area.start.0 as usize, area.start.1 as usize);
+        let label = format!("{} ({})", defined.name, defined.refers_to);
+        self.go_to_cell(&sheet, start, &label);
+        true
+    }
+
+    /// Moves to the cell at sheet position `cell` of `sheet`, switching sheets if
+    /// needed, and reports it as `label`
+    fn go_to_cell(&mut self, sheet: &str, cell: (usize, usize), label: &str) {
+        let Some(index) = self
+            .sheet_names
+            .iter()
+            .position(|name| name.eq_ignore_ascii_case(sheet))
+        else {
+            let message = if self.workbook.is_hidden(sheet) {
+                format!("Sheet '{sheet}' is hidden; start with --include-hidden")
+            } else {
+                format!("Sheet '{sheet}' not found")
+            };
+            self.copy_feedback = Some((message, Instant::now()));
+            return;
+        };
+        if index != self.current_sheet_index {
+            self.current_sheet_index = index;
+            if let Err(e) = self.load_current_sheet() {
+                self.copy_feedback =
+                    Some((format!("Failed to load '{sheet}': {e}"), Instant::now()));
+                return;
+            }
+            self.reset_cursor();
+            self.clear_search();
+        }
+
+        let message = match self.sheet_to_data(cell.0, cell.1) {
+            Some((row, col)) => {
+                self.cursor_row = row;
+                self.cursor_col = col;
+                format!("Jumped to {label}")
+            }
+            None => format!("{label} is outside the data shown"),
+        };
+        self.copy_feedback = Some((message, Instant::now()));
+    }
+
+    /// Follows the hyperlink on the current cell: moves to its target within the
+    /// workbook, or shows the URL of an external one
+    fn follow_link(&mut self) {
+        let message = match self.link_at(self.cursor_row, self.cursor_col).cloned() {
+            Some(LinkTarget::Location(location)) => {
+                // `Sheet2!B5`, a cell or range on this sheet, or a defined name
+                let first_cell = location.split(':').next().unwrap_or_default();
+                if let Some((sheet, area)) = names::parse_area(&location) {
+                    let start = (area.start.0 as usize, area.start.1 as usize);
+                    self.go_to_cell(&sheet, start, &location);
+                } else if let Some((Some(row), Some(col))) = names::parse_cell(first_cell) {
+                    let sheet = self.current_sheet_name().to_string();
+                    self.go_to_cell(&sheet, (row as usize, col as usize), &location);
+                } else if !self.jump_to_name(&location) {
+                    self.copy_feedback =
+                        Some((format!("Link target not found: {location}"), Instant::now()));
+                }
+                return;
+            }
+            Some(LinkTarget::Url(url)) => format!("External link: {url}"),
+            None => "No link on this cell".to_string(),
+        };
+        self.copy_feedback = Some((message, Instant::now()));
+    }
+
     /// Parse cell address like "A5", "B10", "AA100" into (col, row) indices
     fn parse_cell_address(addr: &str) -> Option<(usize, usize)> {
         let addr = addr.to_uppercase();
@@ -914,7 +1144,7 @@ impl TuiState {
                     self.copy_feedback = Some((
                         format!(
                             "Copied row {} ({} cells)",
-                            self.cursor_row + 1,
+                            self.sheet_data.origin().0 + self.cursor_row + 1,
                             self.sheet_data.width()
                         ),
                         Instant::now(),
@@ -938,12 +1168,27 @@ impl TuiState {
     }
 
     fn move_down(&mut self) {
-        if self.cursor_row < self.sheet_data.height().saturating_sub(1) {
-            self.cursor_row += 1;
+        // A merged cell is one stop: continue below its last row
+        let row = self
+            .merge_at_cursor()
+            .map_or(self.cursor_row, |merge| merge.end.0);
+        if row < self.sheet_data.height().saturating_sub(1) {
+            self.cursor_row = row + 1;
             // Auto-scroll down will be handled in render based on viewport height
         }
     }
 
+    fn merge_at_cursor(&self) -> Option<MergedCells> {
+        merges::merge_at(self.sheet_data.merges(), self.cursor_row, self.cursor_col).copied()
+    }
+
+    /// Moves the cursor onto the top-left (value) cell of the merge it landed in
+    fn snap_to_merge(&mut self) {
+        if let Some(merge) = self.merge_at_cursor() {
+            (self.cursor_row, self.cursor_col) = merge.start;
+        }
+    }
+
     /// Update scroll offset to keep cursor visible
     fn update_scroll(&mut self, viewport_height: usize) {
         // Scroll down if cursor is below visible area
@@ -969,11 +1214,13 @@ impl TuiState {
 
         // Sample first 100 rows (or fewer if sheet is smaller)
         let sample_size = 100.min(self.sheet_data.height());
+        let (formats, origin) = (self.sheet_data.formats(), self.sheet_data.origin());
         let (sample_rows, _) = self.sheet_data.get_rows(0, sample_size);
 
-        for row in sample_rows.iter() {
+        for (row_idx, row) in sample_rows.iter().enumerate() {
             for (col_idx, cell) in row.iter().enumerate() {
-                let len = cell.to_string().len();
+                let format = formats.get(origin.0 + row_idx, origin.1 + col_idx);
+                let len = cell.to_formatted_string(format, formats.date1904()).len();
                 widths[col_idx] = widths[col_idx].max(len);
             }
         }
@@ -1022,8 +1269,11 @@ impl TuiState {
     }
 
     fn move_right(&mut self) {
-        if self.cursor_col < self.sheet_data.width().saturating_sub(1) {
-            self.cursor_col += 1;
+        let col = self
+            .merge_at_cursor()
+            .map_or(self.cursor_col, |merge| merge.end.1);
+        if col < self.sheet_data.width().saturating_sub(1) {
+            self.cursor_col = col + 1;
             // Auto-scroll right will be handled in render based on viewport width
         }
     }
@@ -1057,23 +1307,22 @@ impl TuiState {
         self.cursor_row = self.sheet_data.height().saturating_sub(1);
     }
 
-    fn col_to_letter(&self, col: usize) -> String {
-        let mut result = String::new();
-        let mut n = col + 1;
-        while n > 0 {
-            n -= 1;
-            result.push((b'A' + (n % 26) as u8) as char);
-            n /= 26;
-        }
-        result.chars().rev().collect()
+    /// Excel-style address of a data cell, offset by where the data starts in the sheet
+    fn cell_address(&self, row: usize, col: usize) -> String {
+        let (first_row, first_col) = self.sheet_data.origin();
+        format!("{}{}", column_letter(first_col + col), first_row + row + 1)
     }
 
     fn current_cell_address(&self) -> String {
-        format!(
-            "{}{}",
-            self.col_to_letter(self.cursor_col),
-            self.cursor_row + 1
-        )
+        self.cell_address(self.cursor_row, self.cursor_col)
+    }
+
+    /// Data position of a zero-based sheet position, if it lies within the data
+    fn sheet_to_data(&self, row: usize, col: usize) -> Option<(usize, usize)> {
+        let (first_row, first_col) = self.sheet_data.origin();
+        let row = row.checked_sub(first_row)?;
+        let col = col.checked_sub(first_col)?;
+        (row < self.sheet_data.height() && col < self.sheet_data.width()).then_some((row, col))
     }
 
     /// Check if a key press matches a configured action
@@ -1200,6 +1449,10 @@ impl TuiState {
                 self.copy_current_row();
             } else if self.key_matches(code, modifiers, "jump") {
                 self.enter_jump_mode();
+            } else if self.key_matches(code, modifiers, "show_macros") {
+                self.toggle_macros();
+            } else if self.key_matches(code, modifiers, "follow_link") {
+                self.follow_link();
             } else if self.key_matches(code, modifiers, "show_cell_detail") {
                 self.show_cell_detail = true;
                 self.cell_detail_scroll = 0;
@@ -1240,6 +1493,8 @@ impl TuiState {
     }
 
     fn render(&mut self, frame: &mut Frame) {
+        use ratatui::text::{Line, Span};
+
         let chunks = Layout::default()
             .direction(Direction::Vertical)
             .constraints([
@@ -1253,6 +1508,7 @@ impl TuiState {
         let viewport_width = chunks[0].width.saturating_sub(2) as usize; // Account for borders
 
         // Update scroll to keep cursor visible
+        self.snap_to_merge();
         self.update_scroll(table_height);
         self.update_horizontal_scroll(viewport_width);
 
@@ -1308,6 +1564,25 @@ impl TuiState {
 
         let header = Row::new(header_cells).height(1);
 
+        // A merged cell is drawn as one block: the cells it covers share its styling
+        let merges = self.sheet_data.merges().to_vec();
+        let cursor_merge = merges::merge_at(&merges, self.cursor_row, self.cursor_col).copied();
+        let (formats, origin) = (self.sheet_data.formats(), self.sheet_data.origin());
+        let comments = &self.comments;
+        // Links that reach the visible rows, looked up for each cell drawn
+        let sheet_row = |row: usize| u32::try_from(origin.0 + row).unwrap_or(u32::MAX);
+        let (first_row, last_row) = (
+            sheet_row(visible_start),
+            sheet_row(visible_start + table_height),
+        );
+        let links: Vec<&Hyperlink> = self
+            .links
+            .iter()
+            .filter(|link| link.first.0 <= last_row && link.last.0 >= first_row)
+            .collect();
+        // Drawn text of the cells with an external link: (row, column) on screen, URL and width
+        let mut url_cells: Vec<(usize, usize, &str, usize)> = Vec::new();
+
         // Get visible rows from data source (handles lazy loading if needed)
         let (visible_rows, _visible_formulas) =
             self.sheet_data.get_rows(visible_start, table_height);
@@ -1325,9 +1600,14 @@ impl TuiState {
                     .map(|(col_idx, cell)| {
                         // Start with cell type color
                         let mut style = Style::default().fg(colors.cell_color(cell));
+                        let merge = merges::merge_at(&merges, row_idx, col_idx);
+                        let is_cursor_cell = match cursor_merge {
+                            Some(cursor_merge) => cursor_merge.contains(row_idx, col_idx),
+                            None => row_idx == self.cursor_row && col_idx == self.cursor_col,
+                        };
 
                         // Add alternating row background (only if not the current row)
-                        let is_alternating_row = row_idx % 2 == 1;
+                        let is_alternating_row = merge.map_or(row_idx, |m| m.start.0) % 2 == 1;
                         if is_alternating_row && let Some(alt_bg) = colors.alternating_row_bg {
                             style = style.bg(alt_bg);
                         }
@@ -1348,7 +1628,7 @@ impl TuiState {
                                 .add_modifier(Modifier::BOLD);
                         }
                         // Highlight current cell
-                        else if row_idx == self.cursor_row && col_idx == self.cursor_col {
+                        else if is_cursor_cell {
                             style = style
                                 .bg(colors.current_cell_bg)
                                 .fg(colors.current_cell_fg)
@@ -1366,7 +1646,31 @@ impl TuiState {
                         else if col_idx == self.cursor_col {
                             style = style.fg(colors.current_col_fg);
                         }
-                        Cell::from(cell.to_string()).style(style)
+                        let format = formats.get(origin.0 + row_idx, origin.1 + col_idx);
+                        let text = cell.to_formatted_string(format, formats.date1904());
+                        let key = u32::try_from(origin.0 + row_idx)
+                            .ok()
+                            .zip(u32::try_from(origin.1 + col_idx).ok());
+                        let link = key.and_then(|(row, col)| {
+                            links.iter().find(|link| link.contains(row, col))
+                        });
+                        if let Some(link) = link {
+                            style = style.add_modifier(Modifier::UNDERLINED);
+                            if let Some(url) = link.url() {
+                                let col = col_idx - visible_col_start;
+                                url_cells.push((visible_idx, col, url, text.width()));
+                            }
+                        }
+                        let commented = key.is_some_and(|key| comments.contains_key(&key));
+                        if !commented {
+                            return Cell::from(text).style(style);
+                        }
+                        // Marks a comment like the red triangle in Excel's cell corner
+                        Cell::from(Line::from(vec![
+                            Span::raw(text),
+                            Span::styled("◥", Style::default().fg(Color::Red)),
+                        ]))
+                        .style(style)
                     })
                     .collect();
                 Row::new(cells).height(1)
@@ -1374,7 +1678,14 @@ impl TuiState {
             .collect();
 
         // Calculate column widths
-        let col_widths: Vec<Constraint> = if self.horizontal_scroll_enabled {
+        let col_widths: Vec<Constraint> = if self.showing_macros {
+            // Module and line number, then the rest of the width for the code
+            vec![
+                Constraint::Percentage(20),
+                Constraint::Length(6),
+                Constraint::Fill(1),
+            ]
+        } else if self.horizontal_scroll_enabled {
             // Use fixed widths based on content
             self.column_widths[visible_col_start..visible_col_end]
                 .iter()
@@ -1389,17 +1700,33 @@ impl TuiState {
                 .collect()
         };
 
-        let table_title = if self.sheet_names.len() > 1 {
+        let sheet_label = self.workbook.sheet_label(self.current_sheet_name());
+        let table_title = if self.showing_macros {
+            " VBA modules (m: back to sheets) ".to_string()
+        } else if self.sheet_names.len() > 1 {
             format!(
                 " {} (Sheet {}/{}) ",
-                self.current_sheet_name(),
+                sheet_label,
                 self.current_sheet_index + 1,
                 self.sheet_names.len()
             )
         } else {
-            format!(" {} ", self.current_sheet_name())
+            format!(" {} ", sheet_label)
+        };
+        let table_title = match self.workbook.shown_region(self.current_sheet_name()) {
+            Some(region) if !self.showing_macros => {
+                format!("{table_title}· {} ", region.note())
+            }
+            _ => table_title,
         };
 
+        // The table's inner area and its column layout, as Table computes them
+        let table_area = Block::default().borders(Borders::ALL).inner(chunks[0]);
+        let columns = Layout::horizontal(col_widths.clone())
+            .flex(Flex::Start)
+            .spacing(1)
+            .split(table_area);
+
         let table = Table::new(data_rows, col_widths).header(header).block(
             Block::default()
                 .borders(Borders::ALL)
@@ -1409,9 +1736,23 @@ impl TuiState {
 
         frame.render_widget(table, chunks[0]);
 
+        // Rows start below the header
+        for (row, col, url, width) in url_cells {
+            let y = table_area.y + 1 + row as u16;
+            if let Some(column) = columns.get(col)
+                && y < table_area.bottom()
+            {
+                let width = column.width.min(u16::try_from(width).unwrap_or(u16::MAX));
+                link_cells(frame.buffer_mut(), Rect::new(column.x, y, width, 1), url);
+            }
+        }
+
         // Status bar with current cell info
         let (cell, _) = self.sheet_data.get_cell(self.cursor_row, self.cursor_col);
-        let current_cell_value = cell.map(|v| v.to_string()).unwrap_or_default();
+        let format = formats.get(origin.0 + self.cursor_row, origin.1 + self.cursor_col);
+        let current_cell_value = cell
+            .map(|v| v.to_formatted_string(format, formats.date1904()))
+            .unwrap_or_default();
 
         // Format sheet dimensions with scroll indicator
         let sheet_dims = if self.horizontal_scroll_enabled && self.horizontal_scroll_offset > 0 {
@@ -1443,7 +1784,7 @@ impl TuiState {
             format!(" ⏳ {} ", progress.format())
         } else if self.jump_mode {
             format!(
-                " Jump to (row, cell like A5, or row,col): {} ",
+                " Jump to (row, cell like A5, row,col, or name): {} ",
                 self.jump_input
             )
         } else if self.search_mode {
@@ -1575,7 +1916,15 @@ impl TuiState {
             ]),
             Line::from(vec![
                 Span::styled("  Ctrl+G           ", Style::default().fg(Color::Green)),
-                Span::raw("Jump to row/cell (e.g., 100, A5, or 10,3)"),
+                Span::raw("Jump to row/cell/name (e.g., 100, A5, 10,3, or Revenue)"),
+            ]),
+            Line::from(vec![
+                Span::styled("  o                ", Style::default().fg(Color::Green)),
+                Span::raw("Follow the cell's hyperlink to another sheet or cell"),
+            ]),
+            Line::from(vec![
+                Span::styled("  m                ", Style::default().fg(Color::Green)),
+                Span::raw("Show the VBA macro modules (press again to go back)"),
             ]),
             Line::from(""),
             Line::from(Span::styled(
@@ -1639,7 +1988,7 @@ impl TuiState {
             )),
             Line::from(vec![
                 Span::styled("  Enter            ", Style::default().fg(Color::Green)),
-                Span::raw("Show cell details (type, formula, value)"),
+                Span::raw("Show cell details (type, formula, value, comments)"),
             ]),
             Line::from(vec![
                 Span::styled("  t                ", Style::default().fg(Color::Green)),
@@ -1706,6 +2055,7 @@ impl TuiState {
             Line::from("  Cell colors vary by type and current theme:"),
             Line::from("  • Numbers, strings, dates, booleans, errors each have distinct colors"),
             Line::from("  • Alternating row backgrounds improve readability"),
+            Line::from("  • A red ◥ marks a cell with comments; hyperlinks are underlined"),
             Line::from("  • Press 't' to cycle through 6 built-in themes"),
             Line::from(""),
             Line::from(Span::styled(
@@ -1820,8 +2170,30 @@ impl TuiState {
                 ),
                 Span::raw(header),
             ]),
-            Line::from(""),
         ];
+        if let Some(merge) = self.merge_at_cursor() {
+            detail_lines.push(Line::from(vec![
+                Span::styled(
+                    "Merged: ",
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(merges::describe(&merge, self.sheet_data.origin())),
+            ]));
+        }
+        if let Some(target) = self.link_at(self.cursor_row, self.cursor_col) {
+            detail_lines.push(Line::from(vec![
+                Span::styled(
+                    "Link: ",
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(target.to_string()),
+            ]));
+        }
+        detail_lines.push(Line::from(""));
 
         // Show formula first if it exists (more important than type)
         if let Some(ref formula) = cell_formula {
@@ -1852,6 +2224,8 @@ impl TuiState {
                 crate::workbook::CellValue::Bool(_) => "Boolean",
                 crate::workbook::CellValue::Error(_) => "Error",
                 crate::workbook::CellValue::DateTime(_) => "DateTime",
+                crate::workbook::CellValue::Time(_) => "Time",
+                crate::workbook::CellValue::Duration(_) => "Duration",
             };
 
             detail_lines.push(Line::from(vec![
@@ -1863,6 +2237,17 @@ impl TuiState {
                 ),
                 Span::styled(cell_type, Style::default().fg(Color::Green)),
             ]));
+            if let CellValue::Error(error) = cell {
+                detail_lines.push(Line::from(vec![
+                    Span::styled(
+                        "Meaning: ",
+                        Style::default()
+                            .fg(Color::Yellow)
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                    Span::raw(error.description()),
+                ]));
+            }
 
             // Raw value (unformatted)
             let raw_value = cell.to_raw_string();
@@ -1900,8 +2285,22 @@ impl TuiState {
                 ]));
             }
 
-            // Formatted display value
-            let display_value = cell.to_string();
+            // Formatted display value, under the cell's number format if it has one
+            let origin = self.sheet_data.origin();
+            let formats = self.sheet_data.formats();
+            let format = formats.get(origin.0 + self.cursor_row, origin.1 + self.cursor_col);
+            if let Some(format) = format {
+                detail_lines.push(Line::from(vec![
+                    Span::styled(
+                        "Format: ",
+                        Style::default()
+                            .fg(Color::Yellow)
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                    Span::raw(format.code().to_string()),
+                ]));
+            }
+            let display_value = cell.to_formatted_string(format, formats.date1904());
             if display_value != raw_value {
                 detail_lines.push(Line::from(vec![
                     Span::styled(
@@ -1923,8 +2322,15 @@ impl TuiState {
             )));
             detail_lines.push(Line::from(""));
 
+            // Nested JSON objects/arrays are expanded so they can be read
+            let full_content = match &cell {
+                CellValue::String(s) => json_source::pretty_print(s),
+                _ => None,
+            }
+            .unwrap_or(raw_value);
+
             // Split content by lines for multi-line display
-            for line in raw_value.lines() {
+            for line in full_content.lines() {
                 detail_lines.push(Line::from(Span::raw(line.to_string())));
             }
         } else {
@@ -1954,6 +2360,25 @@ impl TuiState {
             }
         }
 
+        // A thread's replies follow its first comment
+        for comment in self.comments_at(self.cursor_row, self.cursor_col) {
+            let author = if comment.author.is_empty() {
+                "Comment:".to_string()
+            } else {
+                format!("Comment by {}:", comment.author)
+            };
+            detail_lines.push(Line::from(""));
+            detail_lines.push(Line::from(Span::styled(
+                author,
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            )));
+            for line in comment.text.lines() {
+                detail_lines.push(Line::from(Span::raw(line.to_string())));
+            }
+        }
+
         detail_lines.push(Line::from(""));
         detail_lines.push(Line::from(vec![Span::styled(
             "↑↓ to scroll | Any other key to close",
@@ -2135,9 +2560,32 @@ fn run_event_loop(
     Ok(())
 }
 
+/// Makes the text drawn in `area` an OSC 8 link to `url`. The first cell gets
+/// the whole linked text and the cells it covers are skipped: ratatui counts the
+/// escape as part of a symbol's width, so a link can't span separate cells.
+fn link_cells(buf: &mut Buffer, area: Rect, url: &str) {
+    let mut text = String::new();
+    let mut x = area.x;
+    while x < area.right() {
+        let symbol = buf[(x, area.y)].symbol();
+        text.push_str(symbol);
+        // The cells behind a wide character are blanks
+        x += symbol.width().max(1) as u16;
+    }
+    if text.trim().is_empty() {
+        return;
+    }
+    buf[(area.x, area.y)].set_symbol(&hyperlinks::terminal_link(url, &text));
+    for x in area.x + 1..area.right() {
+        buf[(x, area.y)].set_skip(true);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::workbook::HeaderOptions;
+    use calamine::{Data, Range};
 
     #[test]
     fn test_parse_cell_address_basic() {
@@ -2180,6 +2628,156 @@ mod tests {
         assert_eq!(TuiState::parse_cell_address("ZZ1"), Some((701, 0)));
     }
 
+    /// TUI state showing `data` in place of the sheet it was opened with
+    fn state_showing(data: SheetDataSource) -> TuiState {
+        let workbook = Workbook::from_reader(
+            "id\n1\n".as_bytes(),
+            "stdin",
+            &crate::workbook::LoadOptions::default(),
+        )
+        .unwrap();
+        let mut state =
+            TuiState::new(workbook, "stdin", &crate::config::Config::default(), false).unwrap();
+        state.sheet_data = data;
+        state
+    }
+
+    /// Used range B2:C6: a title on row 2, the header on row 4 and data on rows 5-6
+    fn titled_range() -> Range<Data> {
+        let mut range = Range::new((1, 1), (5, 2));
+        range.set_value((1, 1), Data::String("Report".to_string()));
+        range.set_value((3, 1), Data::String("item".to_string()));
+        range.set_value((3, 2), Data::String("total".to_string()));
+        for (row, total) in [(4, 3.0), (5, 4.0)] {
+            range.set_value((row, 1), Data::String(format!("row {row}")));
+            range.set_value((row, 2), Data::Float(total));
+        }
+        range
+    }
+
+    fn jump(state: &mut TuiState, input: &str) -> (usize, usize) {
+        state.jump_input = input.to_string();
+        state.perform_jump();
+        (state.cursor_row, state.cursor_col)
+    }
+
+    #[test]
+    fn test_sheet_positions_with_offset_origin() {
+        let header = HeaderOptions {
+            skip_rows: 3,
+            header_rows: 1,
+        };
+        let eager = SheetData::from_range_with_formulas(titled_range(), None, &[], header);
+        let lazy = LazySheetData::from_excel(titled_range(), None, &[], header);
+        for data in [
+            SheetDataSource::Eager(eager),
+            SheetDataSource::Lazy {
+                data: lazy,
+                cache: None,
+                cache_size: TuiState::ROW_CACHE_SIZE,
+            },
+        ] {
+            let mut state = state_showing(data);
+            // The first data cell is B5
+            assert_eq!(state.cell_address(0, 0), "B5");
+            assert_eq!(state.cell_address(1, 1), "C6");
+            assert_eq!(state.sheet_to_data(4, 1), Some((0, 0)));
+            assert_eq!(state.sheet_to_data(5, 2), Some((1, 1)));
+            // The header row, column A and cells past the data aren't data cells
+            assert_eq!(state.sheet_to_data(3, 1), None);
+            assert_eq!(state.sheet_to_data(4, 0), None);
+            assert_eq!(state.sheet_to_data(6, 1), None);
+            assert_eq!(state.sheet_to_data(4, 3), None);
+
+            assert_eq!(jump(&mut state, "C6"), (1, 1));
+            assert_eq!(jump(&mut state, "B4"), (1, 1));
+            assert_eq!(jump(&mut state, "5"), (0, 1));
+            assert_eq!(jump(&mut state, "6,2"), (1, 0));
+            assert_eq!(state.current_cell_address(), "B6");
+        }
+    }
+
+    #[test]
+    fn test_sheet_positions_with_header_rows_and_from_a1() {
+        // Two stacked header rows (3 and 4) after skipping the title row
+        let header = HeaderOptions {
+            skip_rows: 2,
+            header_rows: 2,
+        };
+        let data = SheetData::from_range_with_formulas(titled_range(), None, &[], header);
+        let state = state_showing(SheetDataSource::Eager(data));
+        assert_eq!(state.cell_address(0, 1), "C5");
+        assert_eq!(state.sheet_to_data(5, 1), Some((1, 0)));
+        assert_eq!(state.sheet_to_data(2, 1), None);
+
+        // --from-a1 pads the range out to A1 and names the columns by letter
+        let range = titled_range();
+        let padded = range.range((0, 0), range.end().unwrap());
+        let header = HeaderOptions {
+            skip_rows: 0,
+            header_rows: 0,
+        };
+        let data = SheetData::from_range_with_formulas(padded, None, &[], header);
+        let mut state = state_showing(SheetDataSource::Eager(data));
+        assert_eq!(state.cell_address(1, 1), "B2");
+        assert_eq!(state.sheet_to_data(1, 1), Some((1, 1)));
+        assert_eq!(state.sheet_to_data(0, 0), Some((0, 0)));
+        assert_eq!(jump(&mut state, "B2"), (1, 1));
+        assert_eq!(
+            state
+                .sheet_data
+                .get_cell(1, 1)
+                .0
+                .map(|cell| cell.to_raw_string()),
+            Some("Report".to_string())
+        );
+    }
+
+    #[test]
+    fn test_render_terminal_links() {
+        use ratatui::backend::TestBackend;
+
+        let header = HeaderOptions {
+            skip_rows: 3,
+            header_rows: 1,
+        };
+        let data = SheetData::from_range_with_formulas(titled_range(), None, &[], header);
+        let mut state = state_showing(SheetDataSource::Eager(data));
+        // An external link on B6 and an internal one on C5
+        state.links = vec![
+            Hyperlink {
+                first: (5, 1),
+                last: (5, 1),
+                target: LinkTarget::Url("https://example.com/\x1b".to_string()),
+            },
+            Hyperlink {
+                first: (4, 2),
+                last: (4, 2),
+                target: LinkTarget::Location("Sheet2!A1".to_string()),
+            },
+        ];
+        let mut terminal = Terminal::new(TestBackend::new(40, 10)).unwrap();
+        let frame = terminal.draw(|frame| state.render(frame)).unwrap();
+        let buffer = frame.buffer;
+
+        // Inside the border the header is on the first line and B5 is on the next,
+        // so B6's text starts at (1, 3); the link covers the text, not the padding
+        assert_eq!(
+            buffer[(1, 3)].symbol(),
+            "\x1b]8;;https://example.com/%1B\x1b\\row 5\x1b]8;;\x1b\\"
+        );
+        assert!((2..6).all(|x| buffer[(x, 3)].skip));
+        assert!(!buffer[(6, 3)].skip);
+        assert_eq!(buffer[(1, 2)].symbol(), "r");
+        // Only external links become terminal links
+        let links = buffer
+            .content()
+            .iter()
+            .filter(|cell| cell.symbol().contains("\x1b]8"))
+            .count();
+        assert_eq!(links, 1);
+    }
+
     #[test]
     fn test_column_to_letter() {
         // Test helper function for column letters
@@ -0,0 +1,144 @@
+use crate::styles::CellFormats;
+use crate::workbook::{CellValue, HEADER_ORIGIN, SheetData};
+use anyhow::{Context, Result};
+
+/// The VBA project of a macro-enabled workbook, with its source code decoded
+#[derive(Debug, Clone, Default)]
+pub struct VbaProject {
+    /// Sorted by name
+    pub modules: Vec<VbaModule>,
+    pub references: Vec<VbaReference>,
+}
+
+/// A standard, class or document (sheet and workbook) module
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VbaModule {
+    pub name: String,
+    pub code: String,
+}
+
+impl VbaModule {
+    pub fn line_count(&self) -> usize {
+        self.code.lines().count()
+    }
+}
+
+/// A type library or project the VBA code refers to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VbaReference {
+    pub name: String,
+    pub description: String,
+    /// Where the library was on the machine that last saved the workbook
+    pub path: String,
+}
+
+impl VbaProject {
+    pub fn from_calamine(project: &calamine::vba::VbaProject) -> Result<Self> {
+        // calamine keeps modules in a hash map
+        let mut names = project.get_module_names();
+        names.sort_unstable_by_key(|name| name.to_lowercase());
+        let modules = names
+            .into_iter()
+            .map(|name| {
+                let code = project
+                    .get_module(name)
+                    .with_context(|| format!("Failed to read VBA module '{name}'"))?;
+                Ok(VbaModule {
+                    name: name.to_string(),
+                    code,
+                })
+            })
+            .collect::<Result<_>>()?;
+        let references = project
+            .get_references()
+            .iter()
+            .map(|reference| VbaReference {
+                name: reference.name.clone(),
+                description: reference.description.clone(),
+                path: reference.path.display().to_string(),
+            })
+            .collect();
+        Ok(Self {
+            modules,
+            references,
+        })
+    }
+
+    /// Looks a module up case-insensitively, as VBA does
+    pub fn module(&self, name: &str) -> Option<&VbaModule> {
+        self.modules
+            .iter()
+            .find(|module| module.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Every line of code as a row of Module, Line and Code cells, so the project
+    /// can be browsed and searched like a sheet
+    pub fn to_sheet_data(&self) -> SheetData {
+        let rows: Vec<Vec<CellValue>> = self
+            .modules
+            .iter()
+            .flat_map(|module| {
+                module.code.lines().enumerate().map(|(index, line)| {
+                    vec![
+                        CellValue::String(module.name.clone()),
+                        CellValue::Int(index as i64 + 1),
+                        CellValue::String(line.to_string()),
+                    ]
+                })
+            })
+            .collect();
+        let (width, height) = (3, rows.len());
+        SheetData {
+            headers: vec!["Module".into(), "Line".into(), "Code".into()],
+            rows,
+            formulas: vec![vec![None; width]; height],
+            merges: Vec::new(),
+            formats: CellFormats::default(),
+            origin: HEADER_ORIGIN,
+            width,
+            height,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vba_sheet_data() {
+        let project = VbaProject {
+            modules: vec![
+                VbaModule {
+                    name: "Module1".to_string(),
+                    code: "Sub Hello()\r\n    MsgBox \"Hi\"\r\nEnd Sub\r\n".to_string(),
+                },
+                VbaModule {
+                    name: "ThisWorkbook".to_string(),
+                    code: "Private Sub Workbook_Open()\r\nEnd Sub".to_string(),
+                },
+            ],
+            references: Vec::new(),
+        };
+        assert_eq!(
+            project.module("module1").map(VbaModule::line_count),
+            Some(3)
+        );
+        assert!(project.module("Sheet1").is_none());
+
+        let data = project.to_sheet_data();
+        assert_eq!((data.width, data.height), (3, 5));
+        assert_eq!(
+            data.rows[1],
+            vec![
+                CellValue::String("Module1".to_string()),
+                CellValue::Int(2),
+                CellValue::String("    MsgBox \"Hi\"".to_string()),
+            ]
+        );
+        assert_eq!(
+            data.rows[3][0],
+            CellValue::String("ThisWorkbook".to_string())
+        );
+    }
+}
@@ -1,65 +1,149 @@
+use crate::arrow_source::{self, ARROW_FILE_MAGIC, ARROW_STREAM_MAGIC, ArrowFile};
+use crate::cell_errors::{self, CellError, ErrorCell};
+use crate::comments::{self, Comment};
+use crate::compression::{self, Compression};
+use crate::csv_source::{self, CsvOptions, IndexedCsv, load_csv_data};
+use crate::hyperlinks::{self, Hyperlink};
+use crate::json_source::{self, load_json_data};
+use crate::merges::{self, MergedCells};
+use crate::names::{self, DefinedName};
+use crate::number_format::{self, NumberFormat};
+use crate::parquet_source::{self, PARQUET_MAGIC, ParquetFile};
+use crate::regions::{self, DataRegion, ShownRegion};
+use crate::sqlite_source::{self, SQLITE_MAGIC, SqliteDatabase, SqliteTable};
+use crate::styles::{CellFormats, Styles};
+use crate::vba::VbaProject;
 use anyhow::{Context, Result, anyhow};
-use calamine::{Data, Range, Reader, Sheets, Table, open_workbook_auto};
-use chrono::{Duration, NaiveDate};
-use std::path::Path;
-
-/// Attempts to parse a string into a numeric CellValue, otherwise returns it as a String.
-fn parse_string_to_cellvalue(s: &str) -> CellValue {
-    if s.is_empty() {
-        return CellValue::Empty;
-    }
-    // Try parsing as an integer first
-    if let Ok(i) = s.parse::<i64>() {
-        return CellValue::Int(i);
-    }
-    // Then try as a float
-    if let Ok(f) = s.parse::<f64>() {
-        return CellValue::Float(f);
-    }
-    // Default to a string
-    CellValue::String(s.to_string())
-}
-
-/// Loads a CSV file into a CsvData object.
-fn load_csv_data(path: &Path) -> Result<CsvData> {
-    let mut reader = csv::ReaderBuilder::new()
-        .has_headers(true)
-        .from_path(path)?;
-
-    let headers = reader
-        .headers()?
-        .iter()
-        .map(String::from)
-        .collect::<Vec<String>>();
-    let width = headers.len();
-
-    let mut rows = Vec::new();
-    for result in reader.records() {
-        let record = result?;
-        let row: Vec<CellValue> = record.iter().map(parse_string_to_cellvalue).collect();
-        rows.push(row);
-    }
-
-    let height = rows.len();
-
-    let sheet_data = SheetData {
-        headers,
-        rows,
-        formulas: vec![vec![None; width]; height], // CSVs don't have formulas
-        width,
-        height,
-    };
+use calamine::{
+    Data, Dimensions, ExcelDateTime, ExcelDateTimeType, Ods, Range, Reader, SheetVisible, Sheets,
+    Table, Xls, Xlsb, Xlsx,
+};
+use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, Cursor, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// Sheet name used for a file-backed CSV (the file stem)
+fn csv_sheet_name(path: &Path) -> &str {
+    path.file_stem().and_then(|s| s.to_str()).unwrap_or("data")
+}
 
-    let name = path
-        .file_stem()
-        .and_then(|s| s.to_str())
-        .unwrap_or("data")
-        .to_string();
+const CFB_MAGIC: [u8; 8] = [0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1];
+const ZIP_MAGIC: [u8; 4] = [b'P', b'K', 0x03, 0x04];
 
-    Ok(CsvData {
-        name,
-        data: sheet_data,
-    })
+/// Spreadsheet container formats read through calamine
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ExcelFormat {
+    Xls,
+    Xlsx,
+    Xlsb,
+    Ods,
+}
+
+impl ExcelFormat {
+    const ALL: [ExcelFormat; 4] = [Self::Xls, Self::Xlsx, Self::Xlsb, Self::Ods];
+    const ZIP_BASED: [ExcelFormat; 3] = [Self::Xlsx, Self::Xlsb, Self::Ods];
+
+    /// Candidate formats for a file extension, if it is a known spreadsheet extension
+    fn from_extension(ext: &str) -> Option<&'static [ExcelFormat]> {
+        match ext.to_ascii_lowercase().as_str() {
+            "xls" | "xla" => Some(&[Self::Xls]),
+            "xlsx" | "xlsm" | "xlam" => Some(&[Self::Xlsx]),
+            "xlsb" => Some(&[Self::Xlsb]),
+            "ods" => Some(&[Self::Ods]),
+            _ => None,
+        }
+    }
+
+    /// Candidate formats based on the leading magic bytes. Zip-based formats
+    /// can't be told apart from the header alone, so all of them are returned.
+    fn sniff(bytes: &[u8]) -> Option<&'static [ExcelFormat]> {
+        if bytes.starts_with(&CFB_MAGIC) {
+            Some(&[Self::Xls])
+        } else if bytes.starts_with(&ZIP_MAGIC) {
+            Some(&Self::ZIP_BASED)
+        } else {
+            None
+        }
+    }
+
+    fn open(self, reader: ExcelReader) -> Result<Sheets<ExcelReader>> {
+        Ok(match self {
+            Self::Xls => Sheets::Xls(Xls::new(reader)?),
+            Self::Xlsx => Sheets::Xlsx(Xlsx::new(reader)?),
+            Self::Xlsb => Sheets::Xlsb(Xlsb::new(reader)?),
+            Self::Ods => Sheets::Ods(Ods::new(reader)?),
+        })
+    }
+}
+
+/// Tries each candidate format in order, reopening the input for every attempt
+fn open_excel(
+    mut open_reader: impl FnMut() -> Result<ExcelReader>,
+    candidates: &[ExcelFormat],
+) -> Result<Sheets<ExcelReader>> {
+    let mut last_err = anyhow!("Cannot detect file format");
+    for format in candidates {
+        match format.open(open_reader()?) {
+            Ok(sheets) => return Ok(sheets),
+            Err(e) => last_err = e,
+        }
+    }
+    Err(last_err)
+}
+
+/// Byte source behind an Excel workbook: a file on disk or bytes read from stdin
+pub enum ExcelReader {
+    File(BufReader<File>),
+    Memory(Cursor<Arc<[u8]>>),
+}
+
+impl Read for ExcelReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self {
+            ExcelReader::File(r) => r.read(buf),
+            ExcelReader::Memory(r) => r.read(buf),
+        }
+    }
+}
+
+impl Seek for ExcelReader {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        match self {
+            ExcelReader::File(r) => r.seek(pos),
+            ExcelReader::Memory(r) => r.seek(pos),
+        }
+    }
+}
+
+/// A spreadsheet's cells with the parts read alongside them
+struct ExcelSheet {
+    range: Range<Data>,
+    formula_range: Option<Range<String>>,
+    /// Merged cells in sheet coordinates
+    merged: Vec<Dimensions>,
+    formats: CellFormats,
+    header: HeaderOptions,
+}
+
+/// Where a spreadsheet was opened from, so the parts calamine doesn't expose
+/// (merged cells in .ods files, the scope of defined names) can be read again
+#[derive(Clone)]
+enum ExcelInput {
+    File(PathBuf),
+    Memory(Arc<[u8]>),
+}
+
+impl ExcelInput {
+    fn reader(&self) -> Result<ExcelReader> {
+        Ok(match self {
+            Self::File(path) => ExcelReader::File(BufReader::new(File::open(path)?)),
+            Self::Memory(bytes) => ExcelReader::Memory(Cursor::new(Arc::clone(bytes))),
+        })
+    }
 }
 
 // +++++ Refactored Workbook and Data Structures +++++
@@ -70,45 +154,413 @@ pub struct CsvData {
     pub data: SheetData,
 }
 
+/// JSON records flattened into a single sheet
+#[derive(Debug, Clone)]
+pub struct JsonData {
+    pub name: String,
+    pub data: SheetData,
+}
+
 pub enum DataSource {
-    Excel(Sheets<std::io::BufReader<std::fs::File>>),
+    Excel(Sheets<ExcelReader>),
     Csv(CsvData),
+    /// Delimited file on disk, read on demand
+    IndexedCsv(Arc<IndexedCsv>),
+    Parquet(Arc<ParquetFile>),
+    Arrow(Arc<ArrowFile>),
+    Json(JsonData),
+    /// Each table and view is a sheet
+    Sqlite(SqliteDatabase),
+}
+
+/// Parsing options applied when a workbook is opened
+#[derive(Debug, Clone, Default)]
+pub struct LoadOptions {
+    pub csv: CsvOptions,
+    pub header: HeaderOptions,
+    /// Show only the most table-like data region of each spreadsheet, with its guessed header
+    pub detect_regions: bool,
+    /// Start spreadsheets at A1, keeping empty rows and columns before the used range
+    pub from_a1: bool,
+    /// List hidden and very hidden sheets alongside the visible ones
+    pub include_hidden: bool,
+}
+
+impl LoadOptions {
+    /// CSV options for a file with extension `ext`: the delimiter implied by the
+    /// extension (unless one was given) and the shared header settings
+    fn csv_options(&self, ext: Option<&str>) -> CsvOptions {
+        let mut csv_options = self.csv.clone();
+        if csv_options.delimiter.is_none() {
+            csv_options.delimiter = ext.and_then(csv_source::extension_delimiter);
+        }
+        csv_options.header = self.header;
+        csv_options
+    }
+}
+
+/// Where the column names are. Rows are counted from the top of the sheet
+/// (not from the first used cell), so row numbers match what a spreadsheet shows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeaderOptions {
+    /// Rows dropped before the header rows (or before the data without a header)
+    pub skip_rows: usize,
+    /// Stacked rows holding column names; with 0, columns are named A, B, C, ...
+    pub header_rows: usize,
+}
+
+impl Default for HeaderOptions {
+    fn default() -> Self {
+        Self {
+            skip_rows: 0,
+            header_rows: 1,
+        }
+    }
+}
+
+impl HeaderOptions {
+    pub fn has_header(&self) -> bool {
+        self.header_rows > 0
+    }
+
+    /// Leading rows to drop from a block of `height` rows starting at zero-based
+    /// sheet row `first_row`, as `(skipped rows, header rows)`
+    pub fn leading_rows(&self, first_row: usize, height: usize) -> (usize, usize) {
+        let skipped = self.skip_rows.saturating_sub(first_row).min(height);
+        let header = self.header_rows.min(height - skipped);
+        (skipped, header)
+    }
+}
+
+/// Combines stacked header rows into one name per column, e.g. `2024 / Q1`.
+/// Blank cells in the upper rows (typically merged cells) take the value to
+/// their left, as long as the cell above belongs to the same group.
+pub fn flatten_header_rows(rows: Vec<Vec<String>>) -> Vec<String> {
+    let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let last = rows.len().saturating_sub(1);
+    let mut filled: Vec<Vec<String>> = Vec::with_capacity(rows.len());
+    for (level, mut row) in rows.into_iter().enumerate() {
+        row.resize(width, String::new());
+        if level < last {
+            for col in 1..width {
+                let same_group = filled
+                    .last()
+                    .is_none_or(|parent| parent[col] == parent[col - 1]);
+                if row[col].trim().is_empty() && same_group {
+                    row[col] = row[col - 1].clone();
+                }
+            }
+        }
+        filled.push(row);
+    }
+
+    (0..width)
+        .map(|col| {
+            let mut parts: Vec<&str> = Vec::new();
+            for row in &filled {
+                let part = row[col].as_str();
+                // Vertically merged cells repeat the same name; keep it once
+                if !part.trim().is_empty() && parts.last() != Some(&part) {
+                    parts.push(part);
+                }
+            }
+            parts.join(" / ")
+        })
+        .collect()
+}
+
+/// Spreadsheet column name for a zero-based column index (0 = A, 26 = AA)
+pub fn column_letter(col: usize) -> String {
+    let mut result = String::new();
+    let mut n = col + 1;
+    while n > 0 {
+        n -= 1;
+        result.push((b'A' + (n % 26) as u8) as char);
+        n /= 26;
+    }
+    result.chars().rev().collect()
+}
+
+/// Header names for a sheet without a header row, starting at sheet column `first_col`
+pub fn column_letter_headers(first_col: usize, width: usize) -> Vec<String> {
+    (first_col..first_col + width).map(column_letter).collect()
 }
 
 pub struct Workbook {
     pub source: DataSource,
+    header: HeaderOptions,
+    detect_regions: bool,
+    from_a1: bool,
+    include_hidden: bool,
+    /// Data region chosen per sheet (zero-based); other sheets use the best one
+    selected_regions: HashMap<String, usize>,
+    /// Region last shown of each sheet where detection left something out
+    shown_regions: HashMap<String, ShownRegion>,
+    /// Merged cells of each sheet of an .ods file, read when it is opened
+    ods_merges: HashMap<String, Vec<Dimensions>>,
+    excel_input: Option<ExcelInput>,
+    /// Defined names, read on first use
+    defined_names: Option<Vec<DefinedName>>,
+    /// Number formats of the cell styles, read on first use
+    styles: Option<Styles>,
 }
 
 impl Workbook {
-    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+    fn new(source: DataSource, options: &LoadOptions) -> Self {
+        Self {
+            source,
+            header: options.header,
+            detect_regions: options.detect_regions,
+            from_a1: options.from_a1,
+            include_hidden: options.include_hidden,
+            selected_regions: HashMap::new(),
+            shown_regions: HashMap::new(),
+            ods_merges: HashMap::new(),
+            excel_input: None,
+            defined_names: None,
+            styles: None,
+        }
+    }
+
+    pub fn open(path: impl AsRef<Path>, options: &LoadOptions) -> Result<Self> {
         let path = path.as_ref();
-        let source = if path.extension().and_then(|s| s.to_str()) == Some("csv") {
-            let csv_data = load_csv_data(path).with_context(|| "Failed to load CSV file")?;
-            DataSource::Csv(csv_data)
+        let ext = path.extension().and_then(|s| s.to_str());
+        let magic_len = compression::MAGIC_LEN.max(SQLITE_MAGIC.len());
+        let mut magic = Vec::with_capacity(magic_len);
+        File::open(path)?
+            .take(magic_len as u64)
+            .read_to_end(&mut magic)?;
+
+        if let Some(compression) = ext
+            .and_then(Compression::from_extension)
+            .or_else(|| Compression::sniff(&magic))
+        {
+            return Self::open_compressed(path, compression, options);
+        }
+
+        let mut excel_input = None;
+        let source = if ext.is_some_and(parquet_source::is_parquet_extension)
+            || magic.starts_with(&PARQUET_MAGIC)
+        {
+            let parquet = ParquetFile::open(path, csv_sheet_name(path))
+                .context("Failed to open Parquet file")?;
+            DataSource::Parquet(Arc::new(parquet))
+        } else if ext.is_some_and(arrow_source::is_arrow_extension)
+            || magic.starts_with(&ARROW_FILE_MAGIC)
+        {
+            let arrow = ArrowFile::open(path, csv_sheet_name(path))
+                .context("Failed to open Arrow IPC file")?;
+            DataSource::Arrow(Arc::new(arrow))
+        } else if ext.is_some_and(sqlite_source::is_sqlite_extension)
+            || magic.starts_with(SQLITE_MAGIC)
+        {
+            let db = SqliteDatabase::open(path).context("Failed to open SQLite database")?;
+            DataSource::Sqlite(db)
+        } else if ext.is_some_and(json_source::is_json_extension) {
+            let json = load_json_data(BufReader::new(File::open(path)?), csv_sheet_name(path))
+                .context("Failed to load JSON file")?;
+            DataSource::Json(json)
+        } else if let Some(ext) = ext.filter(|e| csv_source::is_delimited_extension(e)) {
+            let csv_options = options.csv_options(Some(ext));
+            let name = csv_sheet_name(path);
+            if csv_source::supports_indexing(path, &csv_options)? {
+                let csv = IndexedCsv::open(path, name, &csv_options)
+                    .context("Failed to load CSV file")?;
+                DataSource::IndexedCsv(Arc::new(csv))
+            } else {
+                // UTF-16 text can't be split on raw bytes, so it is transcoded in memory
+                let csv = load_csv_data(File::open(path)?, name, &csv_options)
+                    .context("Failed to load CSV file")?;
+                DataSource::Csv(csv)
+            }
         } else {
-            let sheets = open_workbook_auto(path).context("Failed to open workbook")?;
+            let candidates = ext
+                .and_then(ExcelFormat::from_extension)
+                .or_else(|| ExcelFormat::sniff(&magic))
+                .unwrap_or(&ExcelFormat::ALL);
+            let input = ExcelInput::File(path.to_path_buf());
+            let sheets =
+                open_excel(|| input.reader(), candidates).context("Failed to open workbook")?;
+            excel_input = Some(input);
             DataSource::Excel(sheets)
         };
 
-        Ok(Self { source })
+        Ok(Self::with_excel_input(
+            Self::new(source, options),
+            excel_input,
+        ))
+    }
+
+    /// Opens `data.csv.gz`-style files. Delimited text streams from the decoder
+    /// straight into the CSV loader; anything else is decompressed into memory and sniffed.
+    fn open_compressed(
+        path: &Path,
+        compression: Compression,
+        options: &LoadOptions,
+    ) -> Result<Self> {
+        let decoder = compression.decoder(BufReader::new(File::open(path)?))?;
+        // `data.csv.gz` describes `data.csv`; a file recognized by its magic bytes keeps its name
+        let inner = match path.extension().and_then(|s| s.to_str()) {
+            Some(ext) if Compression::from_extension(ext).is_some() => {
+                Path::new(path.file_stem().unwrap_or_default())
+            }
+            _ => path,
+        };
+        let name = csv_sheet_name(inner);
+
+        let source = match inner.extension().and_then(|s| s.to_str()) {
+            Some(ext) if csv_source::is_delimited_extension(ext) => {
+                let csv_options = options.csv_options(Some(ext));
+                let csv = load_csv_data(decoder, name, &csv_options)
+                    .context("Failed to load compressed CSV file")?;
+                DataSource::Csv(csv)
+            }
+            Some(ext) if json_source::is_json_extension(ext) => {
+                let json =
+                    load_json_data(decoder, name).context("Failed to load compressed JSON file")?;
+                DataSource::Json(json)
+            }
+            _ => return Self::from_reader(decoder, name, options),
+        };
+        Ok(Self::new(source, options))
+    }
+
+    /// Reads all of `input` into memory (e.g. stdin) and sniffs whether it is a
+    /// spreadsheet container or CSV text. CSV data is exposed as sheet `name`.
+    pub fn from_reader(mut input: impl Read, name: &str, options: &LoadOptions) -> Result<Self> {
+        let mut bytes = Vec::new();
+        input
+            .read_to_end(&mut bytes)
+            .context("Failed to read input")?;
+
+        if let Some(compression) = Compression::sniff(&bytes) {
+            let decoder = compression.decoder(bytes.as_slice())?;
+            return Self::from_reader(decoder, name, options);
+        }
+
+        if bytes.starts_with(&PARQUET_MAGIC) {
+            let parquet =
+                ParquetFile::from_bytes(bytes, name).context("Failed to read Parquet data")?;
+            return Ok(Self::new(DataSource::Parquet(Arc::new(parquet)), options));
+        }
+
+        if bytes.starts_with(&ARROW_FILE_MAGIC) || bytes.starts_with(&ARROW_STREAM_MAGIC) {
+            let arrow = ArrowFile::from_bytes(bytes, name).context("Failed to read Arrow data")?;
+            return Ok(Self::new(DataSource::Arrow(Arc::new(arrow)), options));
+        }
+
+        if bytes.starts_with(SQLITE_MAGIC) {
+            let db = SqliteDatabase::from_bytes(bytes).context("Failed to read SQLite data")?;
+            return Ok(Self::new(DataSource::Sqlite(db), options));
+        }
+
+        let mut excel_input = None;
+        let source = match ExcelFormat::sniff(&bytes) {
+            Some(candidates) => {
+                let input = ExcelInput::Memory(bytes.into());
+                let sheets =
+                    open_excel(|| input.reader(), candidates).context("Failed to open workbook")?;
+                excel_input = Some(input);
+                DataSource::Excel(sheets)
+            }
+            None if json_source::looks_like_json(&bytes) => DataSource::Json(
+                load_json_data(bytes.as_slice(), name).context("Failed to load JSON data")?,
+            ),
+            None => DataSource::Csv(
+                load_csv_data(bytes.as_slice(), name, &options.csv_options(None))
+                    .context("Failed to load CSV data")?,
+            ),
+        };
+
+        Ok(Self::with_excel_input(
+            Self::new(source, options),
+            excel_input,
+        ))
     }
 
+    /// Keeps the spreadsheet's input around and reads what calamine doesn't expose
+    fn with_excel_input(mut self, input: Option<ExcelInput>) -> Self {
+        if let (Some(input), DataSource::Excel(Sheets::Ods(_))) = (&input, &self.source) {
+            // Merges only affect presentation, so an unreadable file still opens
+            self.ods_merges = input
+                .reader()
+                .and_then(merges::read_ods_merges)
+                .unwrap_or_default();
+        }
+        self.excel_input = input;
+        self
+    }
+
+    /// Sheets in workbook order; hidden ones only with `include_hidden`
     pub fn sheet_names(&self) -> Vec<String> {
         match &self.source {
-            DataSource::Excel(sheets) => sheets.sheet_names(),
+            DataSource::Excel(sheets) => sheets
+                .sheets_metadata()
+                .iter()
+                .filter(|sheet| self.include_hidden || sheet.visible == SheetVisible::Visible)
+                .map(|sheet| sheet.name.clone())
+                .collect(),
             DataSource::Csv(csv_data) => vec![csv_data.name.clone()],
+            DataSource::IndexedCsv(csv) => vec![csv.name().to_string()],
+            DataSource::Parquet(parquet) => vec![parquet.name().to_string()],
+            DataSource::Arrow(arrow) => vec![arrow.name().to_string()],
+            DataSource::Json(json) => vec![json.name.clone()],
+            DataSource::Sqlite(db) => db.table_names().to_vec(),
+        }
+    }
+
+    /// Every sheet in workbook order, hidden and very hidden ones included
+    pub fn all_sheet_names(&self) -> Vec<String> {
+        match &self.source {
+            DataSource::Excel(sheets) => sheets
+                .sheets_metadata()
+                .iter()
+                .map(|sheet| sheet.name.clone())
+                .collect(),
+            _ => self.sheet_names(),
+        }
+    }
+
+    /// Whether a spreadsheet sheet is hidden from Excel's sheet tabs (including
+    /// "very hidden" sheets, which can only be unhidden from VBA)
+    pub fn is_hidden(&self, name: &str) -> bool {
+        self.visibility(name) != SheetVisible::Visible
+    }
+
+    /// Sheet name as listed to the user, e.g. `Lookup (hidden)`
+    pub fn sheet_label(&self, name: &str) -> String {
+        match self.visibility(name) {
+            SheetVisible::Visible => name.to_string(),
+            SheetVisible::Hidden => format!("{name} (hidden)"),
+            SheetVisible::VeryHidden => format!("{name} (very hidden)"),
+        }
+    }
+
+    fn visibility(&self, name: &str) -> SheetVisible {
+        match &self.source {
+            DataSource::Excel(sheets) => sheets
+                .sheets_metadata()
+                .iter()
+                .find(|sheet| sheet.name == name)
+                .map_or(SheetVisible::Visible, |sheet| sheet.visible),
+            _ => SheetVisible::Visible,
         }
     }
 
     /// Loads all rows eagerly into memory
     pub fn load_sheet(&mut self, name: &str) -> Result<SheetData> {
         match &mut self.source {
-            DataSource::Excel(sheets) => {
-                let range = sheets
-                    .worksheet_range(name)
-                    .with_context(|| format!("Sheet '{name}' not found"))?;
-                let formula_range = sheets.worksheet_formula(name).ok();
-                Ok(SheetData::from_range_with_formulas(range, formula_range))
+            DataSource::Excel(_) => {
+                let sheet = self.excel_sheet(name)?;
+                Ok(SheetData {
+                    formats: sheet.formats,
+                    ..SheetData::from_range_with_formulas(
+                        sheet.range,
+                        sheet.formula_range,
+                        &sheet.merged,
+                        sheet.header,
+                    )
+                })
             }
             DataSource::Csv(csv_data) => {
                 if csv_data.name == name {
@@ -117,18 +569,69 @@ impl Workbook {
                     Err(anyhow!("Sheet '{name}' not found in CSV."))
                 }
             }
+            DataSource::IndexedCsv(csv) => {
+                if csv.name() == name {
+                    csv.to_sheet_data().context("Failed to read CSV rows")
+                } else {
+                    Err(anyhow!("Sheet '{name}' not found in CSV."))
+                }
+            }
+            DataSource::Parquet(parquet) => {
+                if parquet.name() == name {
+                    parquet
+                        .to_sheet_data()
+                        .context("Failed to read Parquet rows")
+                } else {
+                    Err(anyhow!("Sheet '{name}' not found in Parquet file."))
+                }
+            }
+            DataSource::Arrow(arrow) => {
+                if arrow.name() == name {
+                    arrow.to_sheet_data().context("Failed to read Arrow rows")
+                } else {
+                    Err(anyhow!("Sheet '{name}' not found in Arrow file."))
+                }
+            }
+            DataSource::Json(json) => {
+                if json.name == name {
+                    Ok(json.data.clone())
+                } else {
+                    Err(anyhow!("Sheet '{name}' not found in JSON file."))
+                }
+            }
+            DataSource::Sqlite(db) => db.table(name)?.to_sheet_data(),
         }
     }
 
     /// Loads only headers; rows fetched on demand
+    /// The first `max_rows` data rows of a sheet (all of them when 0), and how many
+    /// the sheet has. Indexed CSV files only read the rows returned.
+    pub fn load_sheet_head(&mut self, name: &str, max_rows: usize) -> Result<(SheetData, usize)> {
+        if let DataSource::IndexedCsv(csv) = &self.source
+            && csv.name() == name
+            && max_rows > 0
+        {
+            let data = csv.head(max_rows).context("Failed to read CSV rows")?;
+            return Ok((data, csv.height()));
+        }
+        let data = self.load_sheet(name)?;
+        let height = data.height;
+        Ok((data, height))
+    }
+
     pub fn load_sheet_lazy(&mut self, name: &str) -> Result<LazySheetData> {
         match &mut self.source {
-            DataSource::Excel(sheets) => {
-                let range = sheets
-                    .worksheet_range(name)
-                    .with_context(|| format!("Sheet '{name}' not found"))?;
-                let formula_range = sheets.worksheet_formula(name).ok();
-                Ok(LazySheetData::from_excel(range, formula_range))
+            DataSource::Excel(_) => {
+                let sheet = self.excel_sheet(name)?;
+                Ok(LazySheetData {
+                    formats: sheet.formats,
+                    ..LazySheetData::from_excel(
+                        sheet.range,
+                        sheet.formula_range,
+                        &sheet.merged,
+                        sheet.header,
+                    )
+                })
             }
             DataSource::Csv(csv_data) => {
                 if csv_data.name == name {
@@ -137,6 +640,294 @@ impl Workbook {
                     Err(anyhow!("Sheet '{name}' not found in CSV."))
                 }
             }
+            DataSource::IndexedCsv(csv) => {
+                if csv.name() == name {
+                    Ok(LazySheetData::from_indexed_csv(Arc::clone(csv)))
+                } else {
+                    Err(anyhow!("Sheet '{name}' not found in CSV."))
+                }
+            }
+            DataSource::Parquet(parquet) => {
+                if parquet.name() == name {
+                    Ok(LazySheetData::from_parquet(Arc::clone(parquet)))
+                } else {
+                    Err(anyhow!("Sheet '{name}' not found in Parquet file."))
+                }
+            }
+            DataSource::Arrow(arrow) => {
+                if arrow.name() == name {
+                    Ok(LazySheetData::from_arrow(Arc::clone(arrow)))
+                } else {
+                    Err(anyhow!("Sheet '{name}' not found in Arrow file."))
+                }
+            }
+            DataSource::Json(json) => {
+                if json.name == name {
+                    Ok(LazySheetData::from_csv(json.data.clone()))
+                } else {
+                    Err(anyhow!("Sheet '{name}' not found in JSON file."))
+                }
+            }
+            DataSource::Sqlite(db) => Ok(LazySheetData::from_sqlite(Arc::new(db.table(name)?))),
+        }
+    }
+
+    /// A spreadsheet's cells, formulas, merged cells and number formats, cut down to the
+    /// selected data region (with its guessed header row) when region detection is on
+    fn excel_sheet(&mut self, name: &str) -> Result<ExcelSheet> {
+        let merged = self.merged_regions(name);
+        let formats = self.cell_formats(name);
+        let DataSource::Excel(sheets) = &mut self.source else {
+            return Err(anyhow!("Sheet '{name}' is not a spreadsheet"));
+        };
+        let range = sheets
+            .worksheet_range(name)
+            .with_context(|| format!("Sheet '{name}' not found"))?;
+        let formula_range = sheets.worksheet_formula(name).ok();
+
+        let selected = self.selected_regions.get(name).copied();
+        if !self.detect_regions && selected.is_none() {
+            let range = match range.end() {
+                Some(end) if self.from_a1 => range.range((0, 0), end),
+                _ => range,
+            };
+            return Ok(ExcelSheet {
+                range,
+                formula_range,
+                merged,
+                formats,
+                header: self.header,
+            });
+        }
+        let regions = regions::detect_regions(&range);
+        let index = match selected {
+            Some(index) if index >= regions.len() => {
+                return Err(anyhow!(
+                    "Sheet '{name}' has {} data region(s); see --list-regions",
+                    regions.len()
+                ));
+            }
+            Some(index) => index,
+            None => match regions::best_region(&regions) {
+                Some(index) => index,
+                None => {
+                    return Ok(ExcelSheet {
+                        range,
+                        formula_range,
+                        merged,
+                        formats,
+                        header: self.header,
+                    });
+                }
+            },
+        };
+        let region = &regions[index];
+        let shown = ShownRegion {
+            index,
+            count: regions.len(),
+            note_rows: (region.header_row - region.start.0) as usize,
+        };
+        if shown.count > 1 || shown.note_rows > 0 {
+            self.shown_regions.insert(name.to_string(), shown);
+        }
+        let header = HeaderOptions {
+            skip_rows: region.header_row as usize,
+            header_rows: 1,
+        };
+        Ok(ExcelSheet {
+            range: range.range(region.start, region.end),
+            formula_range,
+            merged,
+            formats,
+            header,
+        })
+    }
+
+    /// Merged cells of a spreadsheet in sheet coordinates; none for xlsb and
+    /// other formats. They only affect presentation, so unreadable ones are skipped.
+    fn merged_regions(&mut self, sheet: &str) -> Vec<Dimensions> {
+        match &mut self.source {
+            DataSource::Excel(Sheets::Xlsx(xlsx)) => {
+                if xlsx.load_merged_regions().is_err() {
+                    return Vec::new();
+                }
+                xlsx.merged_regions_by_sheet(sheet)
+                    .into_iter()
+                    .map(|(_, _, merge)| *merge)
+                    .collect()
+            }
+            DataSource::Excel(Sheets::Xls(xls)) => {
+                xls.worksheet_merge_cells(sheet).unwrap_or_default()
+            }
+            DataSource::Excel(Sheets::Ods(_)) => {
+                self.ods_merges.get(sheet).cloned().unwrap_or_default()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Number formats of a sheet's cells (xlsx and xlsb only). Like merges they only
+    /// affect presentation, so unreadable ones are skipped.
+    fn cell_formats(&mut self, sheet: &str) -> CellFormats {
+        self.read_cell_formats(sheet).unwrap_or_default()
+    }
+
+    fn read_cell_formats(&mut self, sheet: &str) -> Result<CellFormats> {
+        let (DataSource::Excel(Sheets::Xlsx(_) | Sheets::Xlsb(_)), Some(input)) =
+            (&self.source, &self.excel_input)
+        else {
+            return Ok(CellFormats::default());
+        };
+        let mut archive = zip::ZipArchive::new(input.reader()?)?;
+        if self.styles.is_none() {
+            self.styles = Some(Styles::read(&mut archive)?);
+        }
+        match &self.styles {
+            Some(styles) => styles.cell_formats(&mut archive, sheet),
+            None => Ok(CellFormats::default()),
+        }
+    }
+
+    /// Data regions detected in a spreadsheet, top to bottom
+    pub fn data_regions(&mut self, sheet: &str) -> Result<Vec<DataRegion>> {
+        match &mut self.source {
+            DataSource::Excel(sheets) => {
+                let range = sheets
+                    .worksheet_range(sheet)
+                    .with_context(|| format!("Sheet '{sheet}' not found"))?;
+                Ok(regions::detect_regions(&range))
+            }
+            _ => Err(anyhow!("Data regions are only detected in spreadsheets")),
+        }
+    }
+
+    /// Cells of a whole spreadsheet that hold an error value, with the formulas that
+    /// produced them; other formats have none
+    pub fn error_cells(&mut self, sheet: &str) -> Result<Vec<ErrorCell>> {
+        let DataSource::Excel(sheets) = &mut self.source else {
+            return Ok(Vec::new());
+        };
+        let range = sheets
+            .worksheet_range(sheet)
+            .with_context(|| format!("Sheet '{sheet}' not found"))?;
+        let formulas = sheets.worksheet_formula(sheet).ok();
+        Ok(cell_errors::find_errors(&range, formulas.as_ref()))
+    }
+
+    /// Comments and notes on a sheet's cells, in sheet coordinates; none for
+    /// formats other than xlsx and ods
+    pub fn comments(&self, sheet: &str) -> Result<Vec<Comment>> {
+        match (&self.source, &self.excel_input) {
+            (DataSource::Excel(Sheets::Xlsx(_)), Some(input)) => {
+                comments::read_xlsx_comments(input.reader()?, sheet)
+            }
+            (DataSource::Excel(Sheets::Ods(_)), Some(input)) => {
+                Ok(comments::read_ods_comments(input.reader()?)?
+                    .remove(sheet)
+                    .unwrap_or_default())
+            }
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    /// Hyperlinks on a sheet's cells, in sheet coordinates (xlsx only)
+    pub fn hyperlinks(&self, sheet: &str) -> Result<Vec<Hyperlink>> {
+        match (&self.source, &self.excel_input) {
+            (DataSource::Excel(Sheets::Xlsx(_)), Some(input)) => {
+                hyperlinks::read_xlsx_hyperlinks(input.reader()?, sheet)
+            }
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    /// Shows data region `index` (zero-based, as listed by `data_regions`) for `sheet`
+    pub fn select_region(&mut self, sheet: &str, index: usize) {
+        self.selected_regions.insert(sheet.to_string(), index);
+    }
+
+    /// The data region a loaded sheet was cut down to, when region detection
+    /// left out other regions or note rows above the header
+    pub fn shown_region(&self, sheet: &str) -> Option<ShownRegion> {
+        self.shown_regions.get(sheet).copied()
+    }
+
+    // ===== Defined names =====
+
+    /// Named ranges, constants and formulas of a spreadsheet. Only xlsx files
+    /// record which sheet a name is local to; other formats list them all as workbook-level.
+    pub fn defined_names(&mut self) -> Result<&[DefinedName]> {
+        if self.defined_names.is_none() {
+            let DataSource::Excel(sheets) = &self.source else {
+                return Err(anyhow!("Defined names are only supported in spreadsheets"));
+            };
+            let xlsx_names = match (sheets, &self.excel_input) {
+                (Sheets::Xlsx(_), Some(input)) => {
+                    input.reader().and_then(names::read_xlsx_names).ok()
+                }
+                _ => None,
+            };
+            self.defined_names =
+                Some(xlsx_names.unwrap_or_else(|| names::from_calamine(sheets.defined_names())));
+        }
+        Ok(self.defined_names.as_deref().unwrap_or_default())
+    }
+
+    /// The cells a defined name refers to, e.g. `Revenue` for `Sheet1!$B$2:$E$14`.
+    /// The first row holds the column names unless the header options say otherwise.
+    pub fn load_name(&mut self, name: &str) -> Result<(DefinedName, SheetData)> {
+        let defined = names::find(self.defined_names()?, name, None)
+            .cloned()
+            .ok_or_else(|| anyhow!("Name '{name}' not found; see --list-names"))?;
+        let (sheet, area) = defined.area().ok_or_else(|| {
+            anyhow!(
+                "Name '{}' refers to {}, not a range of cells",
+                defined.name,
+                defined.refers_to
+            )
+        })?;
+
+        let merged = self.merged_regions(&sheet);
+        let formats = self.cell_formats(&sheet);
+        let DataSource::Excel(sheets) = &mut self.source else {
+            return Err(anyhow!("Defined names are only supported in spreadsheets"));
+        };
+        let range = sheets
+            .worksheet_range(&sheet)
+            .with_context(|| format!("Sheet '{sheet}' not found"))?;
+        let formula_range = sheets.worksheet_formula(&sheet).ok();
+        // Whole-column references run to the bottom of the sheet; stop at the used range
+        let used_end = range.end().unwrap_or((0, 0));
+        let end = (
+            area.end.0.min(used_end.0).max(area.start.0),
+            area.end.1.min(used_end.1).max(area.start.1),
+        );
+        let data = SheetData {
+            formats,
+            ..SheetData::from_range_with_formulas(
+                range.range(area.start, end),
+                formula_range,
+                &merged,
+                self.header,
+            )
+        };
+        Ok((defined, data))
+    }
+
+    // ===== VBA macros =====
+
+    /// The VBA project of a macro-enabled workbook (.xlsm, .xlsb or .xls); `None`
+    /// if it has no macros
+    pub fn vba_project(&mut self) -> Result<Option<VbaProject>> {
+        let DataSource::Excel(sheets) = &mut self.source else {
+            return Err(anyhow!("VBA macros are only found in spreadsheets"));
+        };
+        match sheets.vba_project() {
+            None => Ok(None),
+            Some(project) => {
+                let project =
+                    project.map_err(|e| anyhow!("Failed to read the VBA project: {e}"))?;
+                VbaProject::from_calamine(&project).map(Some)
+            }
         }
     }
 
@@ -178,19 +969,28 @@ impl Workbook {
                 let table = xlsx
                     .table_by_name(table_name)
                     .map_err(|e| anyhow!("Table '{table_name}' not found: {e}"))?;
-                Ok(TableData::from_calamine_table(table))
+                Ok(TableData::from_calamine_table(table, self.header))
             }
             _ => Err(anyhow!("Tables are only supported in .xlsx files")),
         }
     }
 }
 
+/// `origin` of data below a one-line header, as in CSV and columnar files: cell A2
+pub const HEADER_ORIGIN: (usize, usize) = (1, 0);
+
 /// Eagerly-loaded sheet data (loads all rows immediately)
 #[derive(Debug, Clone)]
 pub struct SheetData {
     pub headers: Vec<String>,
     pub rows: Vec<Vec<CellValue>>,
     pub formulas: Vec<Vec<Option<String>>>, // Parallel structure to rows with formulas
+    /// Merged cells within the data rows (spreadsheets only)
+    pub merges: Vec<MergedCells>,
+    /// Number formats of the cells, by sheet position (xlsx and xlsb only)
+    pub formats: CellFormats,
+    /// Sheet position (zero-based row, column) of `rows[0][0]`, so addresses match Excel
+    pub origin: (usize, usize),
     pub width: usize,
     pub height: usize,
 }
@@ -199,42 +999,67 @@ enum LazyDataSource {
     Excel {
         range: Range<Data>,
         formula_range: Option<Range<String>>,
+        header: HeaderOptions,
+        /// Rows of `range` above the first data row
+        data_start: usize,
     },
     Csv {
         data: SheetData,
     },
+    IndexedCsv {
+        csv: Arc<IndexedCsv>,
+    },
+    Parquet {
+        parquet: Arc<ParquetFile>,
+    },
+    Arrow {
+        arrow: Arc<ArrowFile>,
+    },
+    Sqlite {
+        table: Arc<SqliteTable>,
+    },
 }
 
 /// Lazy-loaded sheet data (loads rows on demand)
 pub struct LazySheetData {
     source: LazyDataSource,
     pub headers: Vec<String>,
+    pub merges: Vec<MergedCells>,
+    /// Number formats of the cells, by sheet position (xlsx and xlsb only)
+    pub formats: CellFormats,
+    /// Sheet position of the first data cell, as in `SheetData`
+    pub origin: (usize, usize),
     pub width: usize,
     pub height: usize,
 }
 
 impl LazySheetData {
     /// Create lazy data from an Excel range
-    pub fn from_excel(range: Range<Data>, formula_range: Option<Range<String>>) -> Self {
-        let (height, width) = range.get_size();
-        let headers = if height > 0 {
-            range
-                .rows()
-                .next()
-                .map(|row| row.iter().map(SheetData::cell_to_string).collect())
-                .unwrap_or_default()
-        } else {
-            vec![]
-        };
+    pub fn from_excel(
+        range: Range<Data>,
+        formula_range: Option<Range<String>>,
+        merged: &[Dimensions],
+        header: HeaderOptions,
+    ) -> Self {
+        let width = range.width();
+        let (headers, data_start, height) = SheetData::excel_layout(&range, header);
+        let (first_row, first_col) = range.start().unwrap_or((0, 0));
+        let origin = (first_row as usize + data_start, first_col as usize);
+        let merges = MergedCells::from_sheet(merged, origin, height, width);
 
         Self {
+            origin,
             source: LazyDataSource::Excel {
                 range,
                 formula_range,
+                header,
+                data_start,
             },
             headers,
+            merges,
+            formats: CellFormats::default(),
             width,
-            height: height.saturating_sub(1),
+            height,
         }
     }
 
@@ -242,12 +1067,67 @@ impl LazySheetData {
     pub fn from_csv(data: SheetData) -> Self {
         Self {
             headers: data.headers.clone(),
+            merges: data.merges.clone(),
+            formats: data.formats.clone(),
+            origin: data.origin,
             width: data.width,
             height: data.height,
             source: LazyDataSource::Csv { data },
         }
     }
 
+    /// Create lazy data backed by an on-disk CSV row index
+    pub fn from_indexed_csv(csv: Arc<IndexedCsv>) -> Self {
+        Self {
+            headers: csv.headers.clone(),
+            merges: Vec::new(),
+            formats: CellFormats::default(),
+            origin: (csv.first_row(), 0),
+            width: csv.width(),
+            height: csv.height(),
+            source: LazyDataSource::IndexedCsv { csv },
+        }
+    }
+
+    /// Create lazy data that decodes Parquet row groups on demand
+    pub fn from_parquet(parquet: Arc<ParquetFile>) -> Self {
+        Self {
+            headers: parquet.headers.clone(),
+            merges: Vec::new(),
+            formats: CellFormats::default(),
+            origin: HEADER_ORIGIN,
+            width: parquet.width(),
+            height: parquet.height(),
+            source: LazyDataSource::Parquet { parquet },
+        }
+    }
+
+    /// Create lazy data that converts Arrow record batches on demand
+    pub fn from_arrow(arrow: Arc<ArrowFile>) -> Self {
+        Self {
+            headers: arrow.headers.clone(),
+            merges: Vec::new(),
+            formats: CellFormats::default(),
+            origin: HEADER_ORIGIN,
+            width: arrow.width(),
+            height: arrow.height(),
+            source: LazyDataSource::Arrow { arrow },
+        }
+    }
+
+    /// Create lazy data that pages through a SQLite table or view
+    pub fn from_sqlite(table: Arc<SqliteTable>) -> Self {
+        Self {
+            headers: table.headers.clone(),
+            merges: Vec::new(),
+            formats: CellFormats::default(),
+            origin: HEADER_ORIGIN,
+            width: table.width(),
+            height: table.height(),
+            source: LazyDataSource::Sqlite { table },
+        }
+    }
+
     /// Zero-indexed row range; header excluded
     pub fn get_rows(
         &self,
@@ -258,8 +1138,31 @@ impl LazySheetData {
             LazyDataSource::Excel {
                 range,
                 formula_range,
-            } => self.get_excel_rows(start, count, range, formula_range),
+                data_start,
+                ..
+            } => self.get_excel_rows(start, count, range, formula_range, *data_start),
             LazyDataSource::Csv { data } => self.get_csv_rows(start, count, data),
+            LazyDataSource::IndexedCsv { csv } => {
+                // A read failure (e.g. the file changed underneath us) shows as missing rows
+                let rows = csv.read_rows(start, count).unwrap_or_default();
+                let formulas = vec![vec![None; self.width]; rows.len()];
+                (rows, formulas)
+            }
+            LazyDataSource::Parquet { parquet } => {
+                let rows = parquet.read_rows(start, count).unwrap_or_default();
+                let formulas = vec![vec![None; self.width]; rows.len()];
+                (rows, formulas)
+            }
+            LazyDataSource::Arrow { arrow } => {
+                let rows = arrow.read_rows(start, count).unwrap_or_default();
+                let formulas = vec![vec![None; self.width]; rows.len()];
+                (rows, formulas)
+            }
+            LazyDataSource::Sqlite { table } => {
+                let rows = table.read_rows(start, count).unwrap_or_default();
+                let formulas = vec![vec![None; self.width]; rows.len()];
+                (rows, formulas)
+            }
         }
     }
 
@@ -281,81 +1184,58 @@ impl LazySheetData {
         count: usize,
         range: &Range<Data>,
         formula_range: &Option<Range<String>>,
+        data_start: usize,
     ) -> (Vec<Vec<CellValue>>, Vec<Vec<Option<String>>>) {
         let end = (start + count).min(self.height);
 
         let rows: Vec<Vec<CellValue>> = range
             .rows()
-            .skip(1 + start)
+            .skip(data_start + start)
             .take(end - start)
             .map(|row| row.iter().map(SheetData::datatype_to_cellvalue).collect())
             .collect();
 
-        let formulas = self.get_formulas_for_range(start, end, formula_range);
+        let (first_row, first_col) = range.start().unwrap_or((0, 0));
+        let origin = (first_row as usize + data_start, first_col as usize);
+        let formulas = formula_grid(formula_range, origin, start, end, self.width);
 
         (rows, formulas)
     }
 
-    fn get_formulas_for_range(
-        &self,
-        start: usize,
-        end: usize,
-        formula_range: &Option<Range<String>>,
-    ) -> Vec<Vec<Option<String>>> {
-        if let Some(formula_range) = formula_range {
-            let formula_start = formula_range.start().unwrap_or((0, 0));
-            let total_height = self.height + 1;
-
-            let mut formula_grid: Vec<Vec<Option<String>>> =
-                vec![vec![None; self.width]; end - start];
-
-            for (row_offset, formula_row) in formula_range.rows().enumerate() {
-                let absolute_row = formula_start.0 as usize + row_offset;
-
-                if absolute_row > 0 && absolute_row <= total_height {
-                    let data_row_idx = absolute_row - 1;
-
-                    if data_row_idx >= start && data_row_idx < end {
-                        let result_idx = data_row_idx - start;
-
-                        for (col_offset, formula_str) in formula_row.iter().enumerate() {
-                            let absolute_col = formula_start.1 as usize + col_offset;
-                            if absolute_col < self.width && !formula_str.is_empty() {
-                                formula_grid[result_idx][absolute_col] = Some(formula_str.clone());
-                            }
-                        }
-                    }
-                }
-            }
-
-            formula_grid
-        } else {
-            vec![vec![None; self.width]; end - start]
-        }
-    }
-
     /// Consumes lazy data and loads all rows into memory
     #[allow(clippy::wrong_self_convention)]
-    pub fn to_sheet_data(self) -> SheetData {
+    pub fn to_sheet_data(self) -> Result<SheetData> {
         match self.source {
             LazyDataSource::Excel {
                 range,
                 formula_range,
-            } => SheetData::from_range_with_formulas(range, formula_range),
-            LazyDataSource::Csv { data } => data,
+                header,
+                ..
+            } => Ok(SheetData {
+                merges: self.merges,
+                formats: self.formats,
+                ..SheetData::from_range_with_formulas(range, formula_range, &[], header)
+            }),
+            LazyDataSource::Csv { data } => Ok(data),
+            LazyDataSource::IndexedCsv { csv } => csv.to_sheet_data(),
+            LazyDataSource::Parquet { parquet } => parquet.to_sheet_data(),
+            LazyDataSource::Arrow { arrow } => arrow.to_sheet_data(),
+            LazyDataSource::Sqlite { table } => table.to_sheet_data(),
         }
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum CellValue {
     Empty,
     String(String),
     Int(i64),
     Float(f64),
     Bool(bool),
-    Error(String),
-    DateTime(f64), // Excel datetime as float
+    Error(CellError),
+    DateTime(f64), // Excel datetime serial, always in the 1900 date system
+    Time(f64),     // Time of day as a fraction of a day
+    Duration(f64), // Elapsed time in days, e.g. 1.5 for 36 hours
 }
 
 impl CellValue {
@@ -369,6 +1249,66 @@ impl CellValue {
         matches!(self, CellValue::Int(_) | CellValue::Float(_))
     }
 
+    /// Builds a DateTime cell from a calendar timestamp, using the same serial
+    /// numbering as Excel's 1900 date system. Returns `None` before 1900.
+    pub fn from_naive_datetime(dt: NaiveDateTime) -> Option<CellValue> {
+        let date = dt.date();
+        if date < NaiveDate::from_ymd_opt(1900, 1, 1)? {
+            return None;
+        }
+        // Serials past Excel's phantom 1900-02-29 are offset by one day
+        let epoch = if date >= NaiveDate::from_ymd_opt(1900, 3, 1)? {
+            NaiveDate::from_ymd_opt(1899, 12, 30)?
+        } else {
+            NaiveDate::from_ymd_opt(1899, 12, 31)?
+        };
+        let days = (date - epoch).num_days() as f64;
+        let seconds = (dt - date.and_hms_opt(0, 0, 0)?).num_milliseconds() as f64 / 1000.0;
+        Some(CellValue::DateTime(days + seconds / 86400.0))
+    }
+
+    pub fn from_naive_time(time: NaiveTime) -> CellValue {
+        let seconds = time.num_seconds_from_midnight() as f64 + time.nanosecond() as f64 / 1e9;
+        CellValue::Time(seconds / 86400.0)
+    }
+
+    /// ISO 8601 text of a date, time or duration (`2024-03-15`, `2024-03-15T09:30:00`,
+    /// `09:30:00`, `PT36H`), for exports; `None` for other values
+    pub fn to_iso_string(&self) -> Option<String> {
+        match self {
+            CellValue::DateTime(serial) => {
+                let datetime = number_format::serial_to_datetime(*serial)?;
+                Some(if datetime.time() == NaiveTime::MIN {
+                    datetime.format("%Y-%m-%d").to_string()
+                } else {
+                    datetime.format("%Y-%m-%dT%H:%M:%S%.f").to_string()
+                })
+            }
+            CellValue::Time(fraction) => {
+                let millis = (fraction.rem_euclid(1.0) * 86_400_000.0).round() as i64 % 86_400_000;
+                let time = NaiveTime::MIN + Duration::milliseconds(millis);
+                Some(time.format("%H:%M:%S%.f").to_string())
+            }
+            CellValue::Duration(days) => Some(iso_duration(*days)),
+            _ => None,
+        }
+    }
+
+    /// Display text under the cell's number format; numbers and dates without
+    /// one fall back to the default display. `date1904` tells how plain numbers
+    /// under a date format count days; dates are already in the 1900 system.
+    pub fn to_formatted_string(&self, format: Option<&NumberFormat>, date1904: bool) -> String {
+        match (self, format) {
+            (CellValue::Int(i), Some(format)) => format.format(*i as f64, date1904),
+            (CellValue::Float(f), Some(format)) => format.format(*f, date1904),
+            (
+                CellValue::DateTime(f) | CellValue::Time(f) | CellValue::Duration(f),
+                Some(format),
+            ) => format.format(*f, false),
+            _ => self.to_string(),
+        }
+    }
+
     /// Returns unformatted value (for export/clipboard)
     pub fn to_raw_string(&self) -> String {
         match self {
@@ -383,34 +1323,86 @@ impl CellValue {
                 }
             }
             CellValue::Bool(b) => b.to_string(),
-            CellValue::Error(e) => format!("#{e}"),
-            CellValue::DateTime(dt) => {
-                let days = dt.floor() as i64;
-                let epoch = NaiveDate::from_ymd_opt(1899, 12, 31).unwrap();
-                let adjusted_days = if days > 60 { days - 1 } else { days };
-                let date = epoch + Duration::days(adjusted_days);
-                let time_fraction = dt.fract();
-                let total_seconds = (time_fraction * 86400.0).round() as i64;
-                let hours = total_seconds / 3600;
-                let minutes = (total_seconds % 3600) / 60;
-                let seconds = total_seconds % 60;
-
-                if time_fraction.abs() < 0.0000001 {
-                    format!("{}", date.format("%Y-%m-%d"))
-                } else {
-                    format!(
-                        "{} {:02}:{:02}:{:02}",
-                        date.format("%Y-%m-%d"),
-                        hours,
-                        minutes,
-                        seconds
-                    )
-                }
+            CellValue::Error(e) => e.to_string(),
+            CellValue::DateTime(_) | CellValue::Time(_) | CellValue::Duration(_) => {
+                self.to_string()
             }
         }
     }
 }
 
+/// Date and time of a serial, rounded to the second as shown
+fn serial_datetime_text(serial: f64) -> Option<String> {
+    let datetime = number_format::serial_to_datetime((serial * 86400.0).round() / 86400.0)?;
+    Some(if datetime.time() == NaiveTime::MIN {
+        datetime.format("%Y-%m-%d").to_string()
+    } else {
+        datetime.format("%Y-%m-%d %H:%M:%S").to_string()
+    })
+}
+
+/// Whole seconds of a time or duration given in days, split into hours, minutes and
+/// seconds, with whether it is negative
+fn hms(days: f64) -> (bool, i64, i64, i64) {
+    let seconds = (days * 86400.0).round() as i64;
+    let abs = seconds.abs();
+    (seconds < 0, abs / 3600, abs % 3600 / 60, abs % 60)
+}
+
+/// ISO 8601 duration such as `PT36H`, `PT1H30M` or `-PT45.5S`
+fn iso_duration(days: f64) -> String {
+    let millis = (days * 86_400_000.0).round() as i64;
+    let abs = millis.abs();
+    let (hours, minutes, seconds, millis_part) = (
+        abs / 3_600_000,
+        abs % 3_600_000 / 60_000,
+        abs % 60_000 / 1000,
+        abs % 1000,
+    );
+    let mut text = String::from(if millis < 0 { "-PT" } else { "PT" });
+    if hours > 0 {
+        text.push_str(&format!("{hours}H"));
+    }
+    if minutes > 0 {
+        text.push_str(&format!("{minutes}M"));
+    }
+    if millis_part > 0 {
+        let fraction = format!("{millis_part:03}");
+        text.push_str(&format!("{seconds}.{}S", fraction.trim_end_matches('0')));
+    } else if seconds > 0 || abs == 0 {
+        text.push_str(&format!("{seconds}S"));
+    }
+    text
+}
+
+/// Parses an ISO 8601 duration such as OpenDocument's `PT36H00M00S`, or `P1DT2H`.
+/// Years and months have no fixed length and aren't accepted. Returns days.
+fn parse_iso_duration(text: &str) -> Option<f64> {
+    let (negative, text) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text),
+    };
+    let text = text.strip_prefix('P')?;
+    let (date_part, time_part) = text.split_once('T').unwrap_or((text, ""));
+    let mut seconds = 0.0;
+    for (part, units) in [
+        (date_part, &[('W', 604_800.0), ('D', 86_400.0)][..]),
+        (time_part, &[('H', 3600.0), ('M', 60.0), ('S', 1.0)][..]),
+    ] {
+        let mut rest = part;
+        while !rest.is_empty() {
+            let end = rest.find(|c: char| c.is_ascii_alphabetic())?;
+            let value: f64 = rest[..end].replace(',', ".").parse().ok()?;
+            let unit = rest[end..].chars().next()?;
+            let (_, scale) = units.iter().find(|(u, _)| *u == unit)?;
+            seconds += value * scale;
+            rest = &rest[end + 1..];
+        }
+    }
+    let days = seconds / 86400.0;
+    Some(if negative { -days } else { days })
+}
+
 /// Excel Table data
 #[derive(Debug, Clone)]
 pub struct TableData {
@@ -421,16 +1413,38 @@ pub struct TableData {
 }
 
 impl TableData {
-    pub fn from_calamine_table(table: Table<Data>) -> Self {
+    /// Header options count sheet rows just like for whole sheets, with the
+    /// table's header row directly above its data
+    pub fn from_calamine_table(table: Table<Data>, header: HeaderOptions) -> Self {
         let name = table.name().to_string();
         let sheet_name = table.sheet_name().to_string();
-        let headers = table.columns().to_vec();
+        let data = table.data();
+        let (first_row, first_col) = data.start().unwrap_or((0, 0));
 
-        let rows: Vec<Vec<CellValue>> = table
-            .data()
-            .rows()
-            .map(|row| row.iter().map(SheetData::datatype_to_cellvalue).collect())
+        let header_row = table
+            .columns()
+            .iter()
+            .map(|column| CellValue::String(column.clone()))
             .collect();
+        let mut rows: Vec<Vec<CellValue>> = std::iter::once(header_row)
+            .chain(
+                data.rows()
+                    .map(|row| row.iter().map(SheetData::datatype_to_cellvalue).collect()),
+            )
+            .collect();
+
+        let (skipped, header_rows) =
+            header.leading_rows((first_row as usize).saturating_sub(1), rows.len());
+        let mut rows = rows.split_off(skipped);
+        let headers = if header.has_header() {
+            flatten_header_rows(
+                rows.drain(..header_rows)
+                    .map(|row| row.iter().map(CellValue::to_raw_string).collect())
+                    .collect(),
+            )
+        } else {
+            column_letter_headers(first_col as usize, table.columns().len())
+        };
 
         Self {
             name,
@@ -492,88 +1506,207 @@ impl std::fmt::Display for CellValue {
             CellValue::Bool(b) => {
                 write!(f, "{}", if *b { "true" } else { "false" })
             }
-            CellValue::Error(e) => write!(f, "ERROR: {e}"),
-            CellValue::DateTime(d) => {
-                let days = d.floor() as i64;
-                let excel_epoch = NaiveDate::from_ymd_opt(1899, 12, 31).unwrap();
-                let adjusted_days = if days > 60 { days - 1 } else { days };
-
-                if let Some(date) = excel_epoch.checked_add_signed(Duration::days(adjusted_days)) {
-                    let frac = d.fract();
-                    if frac.abs() > 0.000001 {
-                        let total_seconds = (frac * 86400.0).round() as u32;
-                        let hours = total_seconds / 3600;
-                        let minutes = (total_seconds % 3600) / 60;
-                        let seconds = total_seconds % 60;
-                        write!(f, "{} {:02}:{:02}:{:02}", date, hours, minutes, seconds)
-                    } else {
-                        write!(f, "{}", date)
-                    }
-                } else {
-                    write!(f, "Date[{days}]")
-                }
+            CellValue::Error(e) => write!(f, "{e}"),
+            CellValue::DateTime(d) => match serial_datetime_text(*d) {
+                Some(text) => write!(f, "{text}"),
+                None => write!(f, "Date[{}]", d.floor() as i64),
+            },
+            CellValue::Time(t) => {
+                // A whole day rounds back to midnight
+                let (_, hours, minutes, seconds) = hms(t.rem_euclid(1.0));
+                write!(f, "{:02}:{minutes:02}:{seconds:02}", hours % 24)
+            }
+            CellValue::Duration(d) => {
+                // Elapsed hours aren't wrapped at 24, like Excel's [h]:mm:ss
+                let (negative, hours, minutes, seconds) = hms(*d);
+                let sign = if negative { "-" } else { "" };
+                write!(f, "{sign}{hours}:{minutes:02}:{seconds:02}")
+            }
+        }
+    }
+}
+
+/// Orders cells for sorting a column: numbers by value, then dates, times and
+/// durations chronologically, then text, booleans and errors, with empty cells last
+impl PartialOrd for CellValue {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        fn rank(cell: &CellValue) -> u8 {
+            match cell {
+                CellValue::Int(_) | CellValue::Float(_) => 0,
+                CellValue::DateTime(_) => 1,
+                CellValue::Time(_) => 2,
+                CellValue::Duration(_) => 3,
+                CellValue::String(_) => 4,
+                CellValue::Bool(_) => 5,
+                CellValue::Error(_) => 6,
+                CellValue::Empty => 7,
+            }
+        }
+        match (self, other) {
+            (CellValue::Int(a), CellValue::Int(b)) => a.partial_cmp(b),
+            (CellValue::Int(a), CellValue::Float(b)) => (*a as f64).partial_cmp(b),
+            (CellValue::Float(a), CellValue::Int(b)) => a.partial_cmp(&(*b as f64)),
+            (CellValue::Float(a), CellValue::Float(b))
+            | (CellValue::DateTime(a), CellValue::DateTime(b))
+            | (CellValue::Time(a), CellValue::Time(b))
+            | (CellValue::Duration(a), CellValue::Duration(b)) => a.partial_cmp(b),
+            (CellValue::String(a), CellValue::String(b)) => a.partial_cmp(b),
+            (CellValue::Error(a), CellValue::Error(b)) => a.partial_cmp(b),
+            (CellValue::Bool(a), CellValue::Bool(b)) => a.partial_cmp(b),
+            _ => rank(self).partial_cmp(&rank(other)),
+        }
+    }
+}
+
+/// Formulas for data rows `start..end`. `origin` is the zero-based sheet
+/// position of the first data cell; formula cells outside the data are ignored.
+fn formula_grid(
+    formula_range: &Option<Range<String>>,
+    origin: (usize, usize),
+    start: usize,
+    end: usize,
+    width: usize,
+) -> Vec<Vec<Option<String>>> {
+    let mut grid = vec![vec![None; width]; end - start];
+    let Some(formula_range) = formula_range else {
+        return grid;
+    };
+    let Some((formula_row, formula_col)) = formula_range.start() else {
+        return grid;
+    };
+
+    for (row_offset, formulas) in formula_range.rows().enumerate() {
+        let Some(row) = (formula_row as usize + row_offset).checked_sub(origin.0) else {
+            continue;
+        };
+        if row < start || row >= end {
+            continue;
+        }
+        for (col_offset, formula) in formulas.iter().enumerate() {
+            if let Some(col) = (formula_col as usize + col_offset).checked_sub(origin.1)
+                && col < width
+                && !formula.is_empty()
+            {
+                grid[row - start][col] = Some(formula.clone());
             }
         }
     }
+    grid
 }
 
 impl SheetData {
     pub fn from_range_with_formulas(
         range: Range<Data>,
         formula_range: Option<Range<String>>,
+        merged: &[Dimensions],
+        header: HeaderOptions,
     ) -> Self {
-        let (height, width) = range.get_size();
-
-        let headers = if height > 0 {
-            range
-                .rows()
-                .next()
-                .map(|row| row.iter().map(Self::cell_to_string).collect())
-                .unwrap_or_default()
-        } else {
-            vec![]
-        };
+        let width = range.width();
+        let (headers, data_start, height) = Self::excel_layout(&range, header);
 
         let rows: Vec<Vec<CellValue>> = range
             .rows()
-            .skip(1)
+            .skip(data_start)
             .map(|row| row.iter().map(Self::datatype_to_cellvalue).collect())
             .collect();
 
-        let formulas: Vec<Vec<Option<String>>> = if let Some(formula_range) = formula_range {
-            let formula_start = formula_range.start().unwrap_or((0, 0));
-            let mut formula_grid: Vec<Vec<Option<String>>> = vec![vec![None; width]; height];
-
-            for (row_offset, formula_row) in formula_range.rows().enumerate() {
-                let absolute_row = formula_start.0 as usize + row_offset;
-                if absolute_row > 0 && absolute_row <= height {
-                    let data_row_idx = absolute_row - 1;
-                    for (col_offset, formula_str) in formula_row.iter().enumerate() {
-                        let absolute_col = formula_start.1 as usize + col_offset;
-                        if absolute_col < width && !formula_str.is_empty() {
-                            formula_grid[data_row_idx][absolute_col] = Some(formula_str.clone());
-                        }
-                    }
-                }
-            }
-
-            formula_grid
-                .into_iter()
-                .take(height.saturating_sub(1))
-                .collect()
-        } else {
-            vec![vec![None; width]; height.saturating_sub(1)]
-        };
+        let (first_row, first_col) = range.start().unwrap_or((0, 0));
+        let origin = (first_row as usize + data_start, first_col as usize);
+        let formulas = formula_grid(&formula_range, origin, 0, height, width);
+        let merges = MergedCells::from_sheet(merged, origin, height, width);
 
         Self {
             headers,
             rows,
             formulas,
+            merges,
+            formats: CellFormats::default(),
+            origin,
             width,
-            height: height.saturating_sub(1),
+            height,
+        }
+    }
+
+    /// Copies each merged cell's value into the cells it covers, so every row
+    /// of a merged label carries it (e.g. for filtering an export)
+    pub fn fill_merged(&mut self) {
+        merges::fill_merged(&mut self.rows, &self.merges);
+    }
+
+    /// Data position of the cell at sheet position (`row`, `col`), if it is in the data rows
+    pub fn data_position(&self, row: u32, col: u32) -> Option<(usize, usize)> {
+        let row = (row as usize).checked_sub(self.origin.0)?;
+        let col = (col as usize).checked_sub(self.origin.1)?;
+        (row < self.height && col < self.width).then_some((row, col))
+    }
+
+    /// Sheet positions of the first and last data cells; `None` without data
+    pub fn data_extent(&self) -> Option<((u32, u32), (u32, u32))> {
+        let first = (
+            u32::try_from(self.origin.0).ok()?,
+            u32::try_from(self.origin.1).ok()?,
+        );
+        let last = (
+            u32::try_from(self.origin.0 + self.height.checked_sub(1)?).ok()?,
+            u32::try_from(self.origin.1 + self.width.checked_sub(1)?).ok()?,
+        );
+        Some((first, last))
+    }
+
+    /// Number format of the cell at data position (`row`, `col`), if not General
+    pub fn number_format(&self, row: usize, col: usize) -> Option<&NumberFormat> {
+        self.formats.get(self.origin.0 + row, self.origin.1 + col)
+    }
+
+    /// Replaces numbers and dates that have a number format with their formatted
+    /// text, e.g. `0.25` in a percent cell becomes `25%`
+    pub fn apply_number_formats(&mut self) {
+        for (row_idx, row) in self.rows.iter_mut().enumerate() {
+            for (col_idx, cell) in row.iter_mut().enumerate() {
+                let format = self
+                    .formats
+                    .get(self.origin.0 + row_idx, self.origin.1 + col_idx);
+                if format.is_some()
+                    && matches!(
+                        cell,
+                        CellValue::Int(_)
+                            | CellValue::Float(_)
+                            | CellValue::DateTime(_)
+                            | CellValue::Time(_)
+                            | CellValue::Duration(_)
+                    )
+                {
+                    *cell = CellValue::String(
+                        cell.to_formatted_string(format, self.formats.date1904()),
+                    );
+                }
+            }
         }
     }
 
+    /// Column names, the number of leading rows of `range` that aren't data,
+    /// and the number of data rows
+    fn excel_layout(range: &Range<Data>, header: HeaderOptions) -> (Vec<String>, usize, usize) {
+        let (height, width) = range.get_size();
+        let (first_row, first_col) = range.start().unwrap_or((0, 0));
+        let (skipped, header_rows) = header.leading_rows(first_row as usize, height);
+
+        let headers = if header.has_header() {
+            flatten_header_rows(
+                range
+                    .rows()
+                    .skip(skipped)
+                    .take(header_rows)
+                    .map(|row| row.iter().map(Self::cell_to_string).collect())
+                    .collect(),
+            )
+        } else {
+            column_letter_headers(first_col as usize, width)
+        };
+        let data_start = skipped + header_rows;
+        (headers, data_start, height - data_start)
+    }
+
     fn cell_to_string(cell: &Data) -> String {
         match cell {
             Data::Empty => String::new(),
@@ -587,13 +1720,26 @@ impl SheetData {
                 }
             }
             Data::Bool(b) => b.to_string(),
-            Data::Error(e) => format!("ERROR: {e:?}"),
+            Data::Error(e) => CellError::from(e).to_string(),
             Data::DateTime(d) => format!("Date({})", d.as_f64()),
             Data::DateTimeIso(s) => s.clone(),
             Data::DurationIso(s) => s.clone(),
         }
     }
 
+    /// An ISO 8601 date, date-time or time, as OpenDocument and xlsx `t="d"` cells store them
+    fn iso_to_cellvalue(text: &str) -> Option<CellValue> {
+        if let Ok(datetime) = NaiveDateTime::from_str(text) {
+            CellValue::from_naive_datetime(datetime)
+        } else if let Ok(date) = NaiveDate::from_str(text) {
+            CellValue::from_naive_datetime(date.and_time(NaiveTime::MIN))
+        } else {
+            NaiveTime::from_str(text)
+                .ok()
+                .map(CellValue::from_naive_time)
+        }
+    }
+
     fn datatype_to_cellvalue(cell: &Data) -> CellValue {
         match cell {
             Data::Empty => CellValue::Empty,
@@ -601,14 +1747,42 @@ impl SheetData {
             Data::Int(i) => CellValue::Int(*i),
             Data::Float(f) => CellValue::Float(*f),
             Data::Bool(b) => CellValue::Bool(*b),
-            Data::Error(e) => CellValue::Error(format!("{e:?}")),
-            Data::DateTime(d) => CellValue::DateTime(d.as_f64()),
-            Data::DateTimeIso(s) => CellValue::String(s.clone()),
-            Data::DurationIso(s) => CellValue::String(s.clone()),
+            Data::Error(e) => CellValue::Error(e.into()),
+            Data::DateTime(d) if d.is_duration() => CellValue::Duration(d.as_f64()),
+            // Serials before day 1 only carry a time, as in a cell formatted `h:mm`.
+            // Day 0 of the 1904 date system is a real date, 1904-01-01.
+            Data::DateTime(d) if (0.0..1.0).contains(&d.as_f64()) && !is_date1904(d) => {
+                CellValue::Time(d.as_f64())
+            }
+            // Workbooks in the 1904 date system count from 1904-01-01; calamine knows
+            // which system the workbook uses, so go through the calendar date
+            Data::DateTime(d) => d
+                .as_datetime()
+                .and_then(CellValue::from_naive_datetime)
+                .unwrap_or(CellValue::DateTime(d.as_f64())),
+            Data::DateTimeIso(s) => {
+                Self::iso_to_cellvalue(s).unwrap_or_else(|| CellValue::String(s.clone()))
+            }
+            Data::DurationIso(s) => parse_iso_duration(s)
+                .map(|days| {
+                    // OpenDocument stores times of day as durations too
+                    if (0.0..1.0).contains(&days) {
+                        CellValue::Time(days)
+                    } else {
+                        CellValue::Duration(days)
+                    }
+                })
+                .unwrap_or_else(|| CellValue::String(s.clone())),
         }
     }
 }
 
+/// Whether calamine reads `d` in the 1904 date system, which it doesn't expose
+fn is_date1904(d: &ExcelDateTime) -> bool {
+    let as_1900 = ExcelDateTime::new(d.as_f64(), ExcelDateTimeType::DateTime, false);
+    d.as_datetime() != as_1900.as_datetime()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -657,8 +1831,9 @@ mod tests {
 
     #[test]
     fn test_cellvalue_display_error() {
-        let val = CellValue::Error("DIV/0!".to_string());
-        assert_eq!(val.to_string(), "ERROR: DIV/0!");
+        let val = CellValue::Error(CellError::Div0);
+        assert_eq!(val.to_string(), "#DIV/0!");
+        assert_eq!(CellValue::Error(CellError::NA).to_raw_string(), "#N/A");
     }
 
     #[test]
@@ -703,9 +1878,114 @@ mod tests {
         assert!(display.len() > 10);
     }
 
+    #[test]
+    fn test_time_and_duration_cells() {
+        let excel = |value, typ| {
+            SheetData::datatype_to_cellvalue(&Data::DateTime(ExcelDateTime::new(value, typ, false)))
+        };
+        let date = |value, is_1904| {
+            SheetData::datatype_to_cellvalue(&Data::DateTime(ExcelDateTime::new(
+                value,
+                ExcelDateTimeType::DateTime,
+                is_1904,
+            )))
+        };
+        assert_eq!(date(45366.5, false), CellValue::DateTime(45366.5));
+        // The same day in the 1904 date system, which starts 1,462 days later
+        assert_eq!(date(43904.5, true), CellValue::DateTime(45366.5));
+        assert_eq!(date(1.0, true).to_string(), "1904-01-02");
+        assert_eq!(date(0.5, true).to_string(), "1904-01-01 12:00:00");
+
+        let time = excel(0.5, ExcelDateTimeType::DateTime);
+        assert_eq!(time, CellValue::Time(0.5));
+        assert_eq!(time.to_string(), "12:00:00");
+        assert_eq!(time.to_iso_string().as_deref(), Some("12:00:00"));
+        let duration = excel(1.5, ExcelDateTimeType::TimeDelta);
+        assert_eq!(duration.to_string(), "36:00:00");
+        assert_eq!(duration.to_iso_string().as_deref(), Some("PT36H"));
+        assert_eq!(CellValue::Duration(-1.0 / 96.0).to_string(), "-0:15:00");
+        assert_eq!(
+            CellValue::Duration(0.5 / 86400.0)
+                .to_iso_string()
+                .as_deref(),
+            Some("PT0.5S")
+        );
+        // 23:59:59.7 rounds to the next day rather than showing 24:00:00
+        let late = CellValue::DateTime(45366.0 + 86399.7 / 86400.0);
+        assert_eq!(late.to_string(), "2024-03-16");
+
+        let ods = |text: &str| SheetData::datatype_to_cellvalue(&Data::DurationIso(text.into()));
+        assert_eq!(ods("PT36H00M00S").to_string(), "36:00:00");
+        assert_eq!(ods("PT10H30M00S"), CellValue::Time(10.5 / 24.0));
+        assert_eq!(ods("P1DT2H").to_iso_string().as_deref(), Some("PT26H"));
+        assert_eq!(ods("later"), CellValue::String("later".into()));
+
+        let iso = |text: &str| SheetData::datatype_to_cellvalue(&Data::DateTimeIso(text.into()));
+        assert_eq!(
+            iso("2024-03-15").to_iso_string().as_deref(),
+            Some("2024-03-15")
+        );
+        assert_eq!(iso("2024-03-15").to_string(), "2024-03-15");
+        assert_eq!(
+            iso("2024-03-15T09:30:00.250").to_iso_string().as_deref(),
+            Some("2024-03-15T09:30:00.250")
+        );
+        assert_eq!(iso("09:30:00").to_string(), "09:30:00");
+        assert_eq!(iso("1850-01-01"), CellValue::String("1850-01-01".into()));
+    }
+
+    #[test]
+    fn test_cellvalue_sort_order() {
+        let mut cells = vec![
+            CellValue::Empty,
+            CellValue::String("b".into()),
+            CellValue::Duration(2.0),
+            CellValue::DateTime(45366.0),
+            CellValue::Float(2.5),
+            CellValue::Time(0.25),
+            CellValue::DateTime(45000.5),
+            CellValue::Int(3),
+            CellValue::String("a".into()),
+            CellValue::Duration(0.5),
+        ];
+        cells.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let texts: Vec<String> = cells.iter().map(CellValue::to_raw_string).collect();
+        assert_eq!(
+            texts,
+            vec![
+                "2.5",
+                "3",
+                "2023-03-15 12:00:00",
+                "2024-03-15",
+                "06:00:00",
+                "12:00:00",
+                "48:00:00",
+                "a",
+                "b",
+                ""
+            ]
+        );
+    }
+
+    #[test]
+    fn test_datetime_from_naive_round_trip() {
+        for text in [
+            "1900-01-15 00:00:00",
+            "2024-03-05 13:45:30",
+            "1900-03-01 06:00:00",
+        ] {
+            let dt = NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S").unwrap();
+            let cell = CellValue::from_naive_datetime(dt).unwrap();
+            let expected = text.trim_end_matches(" 00:00:00");
+            assert_eq!(cell.to_raw_string(), expected);
+        }
+        let early = NaiveDateTime::parse_from_str("1899-12-31 00:00", "%Y-%m-%d %H:%M").unwrap();
+        assert!(CellValue::from_naive_datetime(early).is_none());
+    }
+
     #[test]
     fn test_workbook_open_real_file() {
-        if let Ok(wb) = Workbook::open("tests/fixtures/test_data.xlsx") {
+        if let Ok(wb) = Workbook::open("tests/fixtures/test_data.xlsx", &LoadOptions::default()) {
             let sheet_names = wb.sheet_names();
             assert!(!sheet_names.is_empty(), "Should have at least one sheet");
         }
@@ -720,6 +2000,9 @@ mod tests {
                 vec![CellValue::String("Bob".to_string()), CellValue::Int(25)],
             ],
             formulas: vec![vec![None, None], vec![None, None]],
+            merges: Vec::new(),
+            formats: CellFormats::default(),
+            origin: HEADER_ORIGIN,
             width: 2,
             height: 2,
         };
@@ -729,4 +2012,313 @@ mod tests {
         assert_eq!(sheet.headers.len(), 2);
         assert_eq!(sheet.rows.len(), 2);
     }
+
+    /// A sheet whose used range starts at B2: a title on row 2, header on row 4
+    fn titled_range() -> (Range<Data>, Range<String>) {
+        let mut range = Range::new((1, 1), (5, 2));
+        range.set_value((1, 1), Data::String("Report".to_string()));
+        range.set_value((3, 1), Data::String("item".to_string()));
+        range.set_value((3, 2), Data::String("total".to_string()));
+        for (row, total) in [(4, 3.0), (5, 4.0)] {
+            range.set_value((row, 1), Data::String(format!("row {row}")));
+            range.set_value((row, 2), Data::Float(total));
+        }
+        let mut formulas = Range::new((5, 2), (5, 2));
+        formulas.set_value((5, 2), "C5+1".to_string());
+        (range, formulas)
+    }
+
+    #[test]
+    fn test_header_row_excel_range() {
+        let (range, formulas) = titled_range();
+        let header = HeaderOptions {
+            skip_rows: 3,
+            header_rows: 1,
+        };
+        let data =
+            SheetData::from_range_with_formulas(range.clone(), Some(formulas.clone()), &[], header);
+        assert_eq!(data.headers, vec!["item", "total"]);
+        assert_eq!(data.height, 2);
+        assert_eq!(data.rows[1][0].to_raw_string(), "row 5");
+        assert_eq!(data.formulas[1][1].as_deref(), Some("C5+1"));
+        assert_eq!(data.formulas[0][1], None);
+        // The first data cell is B5
+        assert_eq!(data.origin, (4, 1));
+
+        let lazy = LazySheetData::from_excel(range.clone(), Some(formulas), &[], header);
+        assert_eq!(lazy.headers, data.headers);
+        assert_eq!(lazy.origin, data.origin);
+        let (rows, lazy_formulas) = lazy.get_rows(1, 5);
+        assert_eq!(rows.len(), 1);
+        assert_eq!(lazy_formulas[0][1].as_deref(), Some("C5+1"));
+
+        let header = HeaderOptions {
+            skip_rows: 4,
+            header_rows: 0,
+        };
+        let data = SheetData::from_range_with_formulas(range.clone(), None, &[], header);
+        assert_eq!(data.headers, vec!["B", "C"]);
+        assert_eq!(data.height, 2);
+        assert_eq!(column_letter(27), "AB");
+
+        // Padded out to A1, as with --from-a1
+        let end = range.end().unwrap();
+        let header = HeaderOptions {
+            skip_rows: 0,
+            header_rows: 0,
+        };
+        let data = SheetData::from_range_with_formulas(range.range((0, 0), end), None, &[], header);
+        assert_eq!(data.headers, vec!["A", "B", "C"]);
+        assert_eq!(data.height, 6);
+        assert_eq!(data.origin, (0, 0));
+        assert_eq!(data.rows[1][1].to_raw_string(), "Report");
+    }
+
+    #[test]
+    fn test_flatten_header_rows() {
+        let rows = |cells: &[&[&str]]| -> Vec<Vec<String>> {
+            cells
+                .iter()
+                .map(|row| row.iter().map(|c| c.to_string()).collect())
+                .collect()
+        };
+        // "Region" is merged vertically; the years are merged across their quarters
+        let headers = flatten_header_rows(rows(&[
+            &["Region", "2024", "", "2025", ""],
+            &["Region", "Q1", "Q2", "Q1", ""],
+        ]));
+        assert_eq!(
+            headers,
+            vec!["Region", "2024 / Q1", "2024 / Q2", "2025 / Q1", "2025"]
+        );
+
+        // A blank middle cell only inherits from its own parent group
+        let headers = flatten_header_rows(rows(&[
+            &["Sales", "", "", "Costs"],
+            &["EU", "", "US", ""],
+            &["Units", "Value", "Units", "Total"],
+        ]));
+        assert_eq!(
+            headers,
+            vec![
+                "Sales / EU / Units",
+                "Sales / EU / Value",
+                "Sales / US / Units",
+                "Costs / Total"
+            ]
+        );
+        assert_eq!(
+            flatten_header_rows(rows(&[&[" id ", ""]])),
+            vec![" id ", ""]
+        );
+    }
+
+    #[test]
+    fn test_excel_format_sniff() {
+        assert_eq!(
+            ExcelFormat::sniff(&CFB_MAGIC),
+            Some(&[ExcelFormat::Xls][..])
+        );
+        assert_eq!(
+            ExcelFormat::sniff(b"PK\x03\x04rest-of-zip"),
+            Some(&ExcelFormat::ZIP_BASED[..])
+        );
+        assert_eq!(ExcelFormat::sniff(b"name,age\nAlice,30\n"), None);
+        assert_eq!(ExcelFormat::sniff(b""), None);
+    }
+
+    #[test]
+    fn test_from_reader_csv() {
+        let input = "Name,Age\nAlice,30\nBob,25\n";
+        let mut wb =
+            Workbook::from_reader(input.as_bytes(), "stdin", &LoadOptions::default()).unwrap();
+        assert_eq!(wb.sheet_names(), vec!["stdin".to_string()]);
+
+        let data = wb.load_sheet("stdin").unwrap();
+        assert_eq!(data.headers, vec!["Name", "Age"]);
+        assert_eq!(data.height, 2);
+        assert!(matches!(data.rows[0][1], CellValue::Int(30)));
+    }
+
+    #[test]
+    fn test_open_compressed_csv() {
+        use std::io::Write;
+
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+        encoder.write_all(b"Name\tAge\nAlice\t30\n").unwrap();
+        let gz = encoder.finish().unwrap();
+
+        let path = std::env::temp_dir().join(format!("xleak-{}.tsv.gz", std::process::id()));
+        std::fs::write(&path, &gz).unwrap();
+        let mut wb = Workbook::open(&path, &LoadOptions::default()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let name = format!("xleak-{}", std::process::id());
+        assert_eq!(wb.sheet_names(), vec![name.clone()]);
+        let data = wb.load_sheet(&name).unwrap();
+        assert_eq!(data.headers, vec!["Name", "Age"]);
+        assert!(matches!(data.rows[0][1], CellValue::Int(30)));
+
+        let zst = zstd::encode_all(&b"Name,Age\nBob,25\n"[..], 0).unwrap();
+        let mut wb =
+            Workbook::from_reader(zst.as_slice(), "stdin", &LoadOptions::default()).unwrap();
+        let data = wb.load_sheet("stdin").unwrap();
+        assert!(matches!(data.rows[0][1], CellValue::Int(25)));
+    }
+
+    #[test]
+    fn test_from_reader_truncated_zip_fails() {
+        let result = Workbook::from_reader(
+            &b"PK\x03\x04not really a workbook"[..],
+            "stdin",
+            &LoadOptions::default(),
+        );
+        assert!(result.is_err());
+    }
+
+    /// A minimal xlsx file: each sheet is `(name, state, sheetData contents)`
+    fn xlsx_bytes(sheets: &[(&str, Option<&str>, &str)]) -> Vec<u8> {
+        xlsx_bytes_with(sheets, "")
+    }
+
+    /// A minimal xlsx file with `workbook_pr` (e.g. `<workbookPr date1904="1"/>`)
+    /// before its sheet list
+    fn xlsx_bytes_with(sheets: &[(&str, Option<&str>, &str)], workbook_pr: &str) -> Vec<u8> {
+        use std::io::Write;
+        use zip::write::SimpleFileOptions;
+
+        let mut workbook = format!(r#"<workbook xmlns:r="r">{workbook_pr}<sheets>"#);
+        let mut rels = String::from("<Relationships>");
+        let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for (i, (name, state, sheet_data)) in sheets.iter().enumerate() {
+            let state = state.map_or(String::new(), |state| format!(r#" state="{state}""#));
+            workbook.push_str(&format!(
+                r#"<sheet name="{name}" sheetId="{}"{state} r:id="rId{i}"/>"#,
+                i + 1
+            ));
+            rels.push_str(&format!(
+                r#"<Relationship Id="rId{i}" Type="worksheet" Target="worksheets/sheet{i}.xml"/>"#
+            ));
+            zip.start_file(
+                format!("xl/worksheets/sheet{i}.xml"),
+                SimpleFileOptions::default(),
+            )
+            .unwrap();
+            write!(
+                zip,
+                "<worksheet><sheetData>{sheet_data}</sheetData></worksheet>"
+            )
+            .unwrap();
+        }
+        workbook.push_str("</sheets></workbook>");
+        rels.push_str("</Relationships>");
+        // Cell styles 1 to 3 are a percentage, a custom currency format and a date and time
+        let styles = r#"<styleSheet><numFmts count="1"><numFmt numFmtId="164" formatCode="&quot;$&quot;#,##0.00"/></numFmts><cellXfs count="4"><xf numFmtId="0"/><xf numFmtId="10"/><xf numFmtId="164"/><xf numFmtId="22"/></cellXfs></styleSheet>"#;
+        for (path, contents) in [
+            ("xl/workbook.xml", workbook),
+            ("xl/_rels/workbook.xml.rels", rels),
+            ("xl/styles.xml", styles.to_string()),
+        ] {
+            zip.start_file(path, SimpleFileOptions::default()).unwrap();
+            zip.write_all(contents.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_hidden_sheets() {
+        let bytes = xlsx_bytes(&[
+            ("Data", None, ""),
+            (
+                "Lookup",
+                Some("hidden"),
+                r#"<row r="1"><c r="A1" t="e"><v>#REF!</v></c></row>"#,
+            ),
+            ("Macros", Some("veryHidden"), ""),
+        ]);
+        let mut wb =
+            Workbook::from_reader(bytes.as_slice(), "stdin", &LoadOptions::default()).unwrap();
+        assert_eq!(wb.sheet_names(), vec!["Data"]);
+        // --errors still scans hidden sheets
+        assert_eq!(wb.all_sheet_names(), vec!["Data", "Lookup", "Macros"]);
+        let errors = wb.error_cells("Lookup").unwrap();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].address(), "A1");
+        assert!(wb.is_hidden("Lookup"));
+        assert!(!wb.is_hidden("Data"));
+
+        let options = LoadOptions {
+            include_hidden: true,
+            ..Default::default()
+        };
+        let wb = Workbook::from_reader(bytes.as_slice(), "stdin", &options).unwrap();
+        assert_eq!(wb.sheet_names(), vec!["Data", "Lookup", "Macros"]);
+        assert_eq!(wb.sheet_label("Data"), "Data");
+        assert_eq!(wb.sheet_label("Lookup"), "Lookup (hidden)");
+        assert_eq!(wb.sheet_label("Macros"), "Macros (very hidden)");
+    }
+
+    #[test]
+    fn test_number_formats() {
+        let bytes = xlsx_bytes(&[(
+            "Data",
+            None,
+            r#"<row r="1"><c r="A1" t="inlineStr"><is><t>Rate</t></is></c><c r="B1" t="inlineStr"><is><t>Price</t></is></c></row>
+            <row r="2"><c r="A2" s="1"><v>0.0725</v></c><c r="B2" s="2"><v>1234.5</v></c><c r="C2"><v>0.5</v></c></row>"#,
+        )]);
+        let mut wb =
+            Workbook::from_reader(bytes.as_slice(), "stdin", &LoadOptions::default()).unwrap();
+        let mut data = wb.load_sheet("Data").unwrap();
+        assert_eq!(
+            data.number_format(0, 0).map(NumberFormat::code),
+            Some("0.00%")
+        );
+        assert!(data.number_format(0, 2).is_none());
+        let cell = &data.rows[0][1];
+        assert_eq!(
+            cell.to_formatted_string(data.number_format(0, 1), false),
+            "$1,234.50"
+        );
+
+        data.apply_number_formats();
+        let texts: Vec<String> = data.rows[0].iter().map(CellValue::to_raw_string).collect();
+        assert_eq!(texts, vec!["7.25%", "$1,234.50", "0.5"]);
+    }
+
+    #[test]
+    fn test_date1904_workbook() {
+        let bytes = xlsx_bytes_with(
+            &[(
+                "Data",
+                None,
+                r#"<row r="1"><c r="A1" t="inlineStr"><is><t>Start</t></is></c><c r="B1" t="inlineStr"><is><t>End</t></is></c></row>
+                <row r="2"><c r="A2" s="3"><v>0</v></c><c r="B2" s="3"><v>43904.5</v></c></row>"#,
+            )],
+            r#"<workbookPr date1904="1"/>"#,
+        );
+        let mut wb =
+            Workbook::from_reader(bytes.as_slice(), "stdin", &LoadOptions::default()).unwrap();
+        let mut data = wb.load_sheet("Data").unwrap();
+        assert!(data.formats.date1904());
+
+        // Day 0 is 1904-01-01, not a time of day
+        let (start, end) = (&data.rows[0][0], &data.rows[0][1]);
+        assert_eq!(start.to_string(), "1904-01-01");
+        assert_eq!(end.to_string(), "2024-03-15 12:00:00");
+        assert_eq!(start.to_iso_string().as_deref(), Some("1904-01-01"));
+        assert_eq!(end.to_iso_string().as_deref(), Some("2024-03-15T12:00:00"));
+
+        let format = data.number_format(0, 0).cloned();
+        assert_eq!(
+            start.to_formatted_string(format.as_ref(), true),
+            "1904-01-01 0:00"
+        );
+        // Plain numbers under a date format count from the workbook's epoch
+        assert_eq!(
+            CellValue::Float(1.25).to_formatted_string(format.as_ref(), true),
+            "1904-01-02 6:00"
+        );
+        data.apply_number_formats();
+        let texts: Vec<String> = data.rows[0].iter().map(CellValue::to_raw_string).collect();
+        assert_eq!(texts, vec!["1904-01-01 0:00", "2024-03-15 12:00"]);
+    }
 }
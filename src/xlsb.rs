@@ -0,0 +1,132 @@
+use anyhow::{Result, anyhow};
+use std::io::{ErrorKind, Read};
+
+/// BrtRowHdr: starts a row of cells
+pub const ROW_HEADER: u16 = 0x0000;
+/// BrtBundleSh: a sheet of the workbook, in `xl/workbook.bin`
+pub const BUNDLE_SHEET: u16 = 0x009C;
+/// BrtWbProp: workbook properties such as the date system, in `xl/workbook.bin`
+pub const WORKBOOK_PROPERTIES: u16 = 0x0099;
+/// BrtFmt: a number format code, in `xl/styles.bin`
+pub const FORMAT: u16 = 0x002C;
+/// BrtXF: a cell style
+pub const CELL_STYLE: u16 = 0x002F;
+/// BrtBeginCellXFs / BrtEndCellXFs: the cell styles that cells refer to
+pub const BEGIN_CELL_STYLES: u16 = 0x0269;
+pub const END_CELL_STYLES: u16 = 0x026A;
+
+/// Reads the records of a binary (.xlsb) part one at a time
+pub struct Records<R> {
+    reader: R,
+    buf: Vec<u8>,
+}
+
+impl<R: Read> Records<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            buf: Vec::new(),
+        }
+    }
+
+    /// The next record's type and payload, or `None` at the end of the part
+    pub fn next_record(&mut self) -> Result<Option<(u16, &[u8])>> {
+        // Types take one or two bytes and sizes up to four, 7 bits each
+        let Some(first) = self.read_byte()? else {
+            return Ok(None);
+        };
+        let mut typ = u16::from(first & 0x7F);
+        if first & 0x80 != 0 {
+            let second = self.expect_byte()?;
+            typ |= u16::from(second & 0x7F) << 7;
+        }
+        let mut len = 0usize;
+        for i in 0..4 {
+            let byte = self.expect_byte()?;
+            len |= usize::from(byte & 0x7F) << (7 * i);
+            if byte & 0x80 == 0 {
+                break;
+            }
+        }
+        self.buf.resize(len, 0);
+        self.reader.read_exact(&mut self.buf)?;
+        Ok(Some((typ, &self.buf)))
+    }
+
+    fn read_byte(&mut self) -> Result<Option<u8>> {
+        let mut byte = [0u8];
+        match self.reader.read_exact(&mut byte) {
+            Ok(()) => Ok(Some(byte[0])),
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn expect_byte(&mut self) -> Result<u8> {
+        self.read_byte()?
+            .ok_or_else(|| anyhow!("Truncated record in binary workbook"))
+    }
+}
+
+pub fn read_u16(data: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(data.get(at..at + 2)?.try_into().ok()?))
+}
+
+pub fn read_u32(data: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(data.get(at..at + 4)?.try_into().ok()?))
+}
+
+/// An XLWideString at `at`: a character count and UTF-16 text. Returns the
+/// string and the offset just past it; a null string (count 0xFFFFFFFF) is empty.
+pub fn wide_string(data: &[u8], at: usize) -> Option<(String, usize)> {
+    let chars = read_u32(data, at)?;
+    if chars == u32::MAX {
+        return Some((String::new(), at + 4));
+    }
+    let end = at + 4 + chars as usize * 2;
+    let units: Vec<u16> = data
+        .get(at + 4..end)?
+        .chunks_exact(2)
+        .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+        .collect();
+    Some((String::from_utf16_lossy(&units), end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_records() {
+        let mut bytes = vec![
+            // BrtRowHdr with a 4-byte payload
+            0x00, 0x04, 7, 0, 0, 0,
+            // BrtBeginCellXFs: a two-byte type (0x0269) and a 4-byte payload
+            0xE9, 0x04, 0x04, 1, 0, 0, 0,
+        ];
+        // BrtFmt with a 130-byte payload, so its size takes two bytes
+        bytes.extend([0x2C, 0x82, 0x01]);
+        bytes.extend([164, 0, 62, 0, 0, 0]);
+        bytes.extend(
+            "0.00%"
+                .repeat(12)
+                .bytes()
+                .chain("00".bytes())
+                .flat_map(|b| [b, 0]),
+        );
+        let mut records = Records::new(bytes.as_slice());
+
+        let (typ, payload) = records.next_record().unwrap().unwrap();
+        assert_eq!((typ, read_u32(payload, 0)), (ROW_HEADER, Some(7)));
+        let (typ, _) = records.next_record().unwrap().unwrap();
+        assert_eq!(typ, BEGIN_CELL_STYLES);
+        let (typ, payload) = records.next_record().unwrap().unwrap();
+        assert_eq!(typ, FORMAT);
+        assert_eq!(payload.len(), 130);
+        assert_eq!(read_u16(payload, 0), Some(164));
+        let (code, end) = wide_string(payload, 2).unwrap();
+        assert!(code.starts_with("0.00%0.00%"));
+        assert_eq!(end, 130);
+        assert!(records.next_record().unwrap().is_none());
+    }
+}
@@ -0,0 +1,19 @@
+use anyhow::Result;
+use quick_xml::events::BytesStart;
+
+/// An attribute's unescaped value, matched by its qualified name (e.g. `table:name`)
+pub fn attribute(element: &BytesStart, name: &str) -> Result<Option<String>> {
+    Ok(match element.try_get_attribute(name)? {
+        Some(attr) => {
+            Some(quick_xml::escape::unescape(&String::from_utf8_lossy(&attr.value))?.into_owned())
+        }
+        None => None,
+    })
+}
+
+/// A count attribute such as `table:number-columns-repeated`, 1 when absent
+pub fn number_attribute(element: &BytesStart, name: &str) -> Result<u32> {
+    Ok(attribute(element, name)?
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(1))
+}